@@ -3,16 +3,14 @@ use std::net::UdpSocket;
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::process::Command;
-use serde::Deserialize;
 use sysinfo::{System, Signal};
 use std::sync::atomic::{AtomicBool, Ordering};
+use telemetria::Metrics;
 
-#[derive(Deserialize, Debug, Clone)]
-struct Metrics {
-    cpu: u32,
-    network: String,
-    memory: u32,
-}
+//Conexiones a medio abrir a partir de las que se sospecha un SYN flood
+const UMBRAL_SYN_RECV: u32 = 100;
+//Conexiones desde una sola dirección remota a partir de las que se sospecha un DDoS
+const UMBRAL_CONEXIONES_POR_PAR: u32 = 200;
 
 enum Theme {
     Dark,
@@ -22,7 +20,6 @@ enum Theme {
 struct DefenseApp {
     received_metrics: Arc<Mutex<Vec<Metrics>>>,
     logs: Arc<Mutex<Vec<String>>>,
-    last_ddos_count: Arc<Mutex<usize>>,
     current_theme: Theme,
     monitoring_active: Arc<AtomicBool>,
 }
@@ -31,7 +28,6 @@ impl DefenseApp {
     fn new() -> Self {
         let metrics = Arc::new(Mutex::new(Vec::new()));
         let logs = Arc::new(Mutex::new(Vec::new()));
-        let last_ddos_count = Arc::new(Mutex::new(0));
         let monitoring_active = Arc::new(AtomicBool::new(true));
 
         let metrics_clone = Arc::clone(&metrics);
@@ -52,70 +48,81 @@ impl DefenseApp {
                     continue;
                 }
 
-                if let Ok((amt, _)) = socket.recv_from(&mut buf) {
-                    if let Ok(text) = std::str::from_utf8(&buf[..amt]) {
-                        if let Ok(parsed) = serde_json::from_str::<Metrics>(text) {
-                            metrics_clone.lock().unwrap().push(parsed.clone());
+                if let Ok((amt, _)) = socket.recv_from(&mut buf)
+                    && let Ok(text) = std::str::from_utf8(&buf[..amt])
+                    && let Ok(parsed) = serde_json::from_str::<Metrics>(text)
+                {
+                    metrics_clone.lock().unwrap().push(parsed.clone());
 
-                            // Detección de DDoS
-                            if let Some(mb_str) = parsed.network.strip_suffix(" MB/s") {
-                                if let Ok(mb_val) = mb_str.parse::<u32>() {
-                                    last_network_values.push(mb_val);
-                                    if last_network_values.len() > 5 {
-                                        last_network_values.remove(0);
-                                    }
+                    // Detección de DDoS
+                    if let Some(mb_val) = parsed.network_mb_por_segundo() {
+                        last_network_values.push(mb_val);
+                        if last_network_values.len() > 5 {
+                            last_network_values.remove(0);
+                        }
 
-                                    let ddos_spikes = last_network_values.iter().filter(|&&v| v > 70).count();
+                        let ddos_spikes = last_network_values.iter().filter(|&&v| v > 70).count();
 
-                                    if ddos_spikes >= 3 {
-                                        logs_clone.lock().unwrap().push(format!(
-                                            "Posible DDoS detectado: 3+ picos > 70 MB/s en ventana reciente ({:?})",
-                                            last_network_values
-                                        ));
-                                        buscar_y_matar_proceso_sospechoso(20.0, 50);
-                                    }
-                                }
-                            }
-                            
-                            // Detección de fuga de memoria
-                            last_memory_values.push(parsed.memory);
-                            if last_memory_values.len() > 5 {
-                                last_memory_values.remove(0);
-                            }
+                        if ddos_spikes >= 3 {
+                            logs_clone.lock().unwrap().push(format!(
+                                "Posible DDoS detectado: 3+ picos > 70 MB/s en ventana reciente ({:?})",
+                                last_network_values
+                            ));
+                            buscar_y_matar_proceso_sospechoso(20.0, 50);
+                        }
+                    }
 
-                            if last_memory_values.len() >= 4 {
-                                let mut total_increase = 0;
-                                let mut large_jumps = 0;
+                    // Detección por conexiones (solo si quien manda incluye la sección sockets)
+                    if let Some(syn_recv) = parsed.tcp_syn_recv.filter(|&n| n >= UMBRAL_SYN_RECV) {
+                        logs_clone.lock().unwrap().push(format!(
+                            "Posible SYN flood: {} conexiones TCP a medio abrir",
+                            syn_recv
+                        ));
+                    }
+                    if let Some(conexiones) = parsed.top_peer_connections.filter(|&n| n >= UMBRAL_CONEXIONES_POR_PAR) {
+                        logs_clone.lock().unwrap().push(format!(
+                            "Posible DDoS: {} conexiones TCP desde una misma dirección",
+                            conexiones
+                        ));
+                    }
+                    
+                    // Detección de fuga de memoria
+                    last_memory_values.push(parsed.memory);
+                    if last_memory_values.len() > 5 {
+                        last_memory_values.remove(0);
+                    }
 
-                                for win in last_memory_values.windows(2) {
-                                    if win[1] > win[0] {
-                                        let diff = win[1] - win[0];
-                                        total_increase += diff;
-                                        if diff > 10 {
-                                            large_jumps += 1;
-                                        }
-                                    }
-                                }
+                    if last_memory_values.len() >= 4 {
+                        let mut total_increase = 0;
+                        let mut large_jumps = 0;
 
-                                if total_increase >= 20 || large_jumps >= 2 {
-                                    logs_clone.lock().unwrap().push(format!(
-                                        "Posible fuga de memoria: uso de RAM subió {}% en ventana reciente {:?}",
-                                        total_increase, last_memory_values
-                                    ));
-                                    buscar_y_matar_proceso_sospechoso(10.0, 100);
+                        for win in last_memory_values.windows(2) {
+                            if win[1] > win[0] {
+                                let diff = win[1] - win[0];
+                                total_increase += diff;
+                                if diff > 10 {
+                                    large_jumps += 1;
                                 }
                             }
+                        }
 
-                            // Detección de pico de CPU
-                            if parsed.cpu > 90 {
-                                logs_clone.lock().unwrap().push(format!(
-                                    "Pico de CPU detectado: uso actual del {}%",
-                                    parsed.cpu
-                                ));
-                                kill_high_cpu_process();
-                            }
+                        if total_increase >= 20 || large_jumps >= 2 {
+                            logs_clone.lock().unwrap().push(format!(
+                                "Posible fuga de memoria: uso de RAM subió {}% en ventana reciente {:?}",
+                                total_increase, last_memory_values
+                            ));
+                            buscar_y_matar_proceso_sospechoso(10.0, 100);
                         }
                     }
+
+                    // Detección de pico de CPU
+                    if parsed.cpu > 90 {
+                        logs_clone.lock().unwrap().push(format!(
+                            "Pico de CPU detectado: uso actual del {}%",
+                            parsed.cpu
+                        ));
+                        kill_high_cpu_process();
+                    }
                 }
             }
         });
@@ -123,7 +130,6 @@ impl DefenseApp {
         Self {
             received_metrics: metrics,
             logs,
-            last_ddos_count,
             current_theme: Theme::Pink,
             monitoring_active,
         }
@@ -190,6 +196,24 @@ impl eframe::App for DefenseApp {
 
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.heading("Métricas recibidas");
+            match self.received_metrics.lock().unwrap().last() {
+                Some(ultima) => {
+                    ui.label(format!("CPU: {}%", ultima.cpu));
+                    ui.label(format!("Memoria: {}%", ultima.memory));
+                    ui.label(format!("Red: {}", ultima.network));
+                    if let Some(establecidas) = ultima.tcp_established {
+                        ui.label(format!(
+                            "Conexiones TCP: {} establecidas, {} a medio abrir, {} de la dirección con más",
+                            establecidas,
+                            ultima.tcp_syn_recv.unwrap_or(0),
+                            ultima.top_peer_connections.unwrap_or(0)
+                        ));
+                    }
+                }
+                None => {
+                    ui.label("Sin métricas todavía.");
+                }
+            }
 
             ui.separator();
             ui.label("Log de eventos:");
@@ -209,9 +233,10 @@ fn buscar_y_matar_proceso_sospechoso(cpu_umbral: f32, memoria_umbral_mb: u64) {
     let mut sistema = System::new_all();
     sistema.refresh_all();
 
-    for (_pid, proceso) in sistema.processes() {
+    for proceso in sistema.processes().values() {
         let uso_cpu = proceso.cpu_usage();
-        let uso_memoria_mb = proceso.memory() / 1024;
+        //sysinfo 0.30 da la memoria en bytes
+        let uso_memoria_mb = proceso.memory() / (1024 * 1024);
 
         if uso_cpu > cpu_umbral || uso_memoria_mb > memoria_umbral_mb {
             let nombre = proceso.name();
@@ -228,7 +253,7 @@ fn buscar_y_matar_proceso_sospechoso(cpu_umbral: f32, memoria_umbral_mb: u64) {
 
 fn matar_proceso_windows(nombre: &str) {
     let _ = Command::new("taskkill")
-        .args(&["/IM", nombre, "/F"])
+        .args(["/IM", nombre, "/F"])
         .output();
 }
 
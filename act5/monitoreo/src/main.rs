@@ -1,98 +1,86 @@
 use eframe::{egui, App, Frame}; 
-use sysinfo::{System, Networks};
-use serde::Serialize;
-use chrono::Local;
-use futures::stream::StreamExt;
-use heim::disk;
-use heim::units::information::byte;
 use std::time::Duration;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use std::time::Instant;
+use telemetria::{Datosuwu, Recolector};
+use telemetria::datos::Usuario;
+use telemetria::unidades::{Bytes, BytesPorSegundo};
+use uom::si::information::{kibibyte, mebibyte};
+use uom::si::information_rate::kibibyte_per_second;
+use uom::si::frequency::megahertz;
 
 #[derive(Default)]
 struct MetricsApp {
     datos: Arc<Mutex<Option<Datosuwu>>>,
+    //El mismo recolector entre actualizaciones, para que las tasas se calculen contra la muestra anterior
+    recolector: Arc<Mutex<Recolector>>,
     last_update: Option<Instant>,
+    //Columna por la que se ordena la tabla de usuarios (de mayor a menor, salvo el nombre)
+    orden_usuarios: OrdenUsuarios,
 }
 
-#[derive(Serialize, Clone)]
-struct Datosuwu {
-    timestamp: String,
-    cpu_total_usage: f32,
-    cpu_frequency_mhz: u64,
-    cpu_cores_usage: Vec<String>,
-    used_memory_mb: u64,
-    total_memory_mb: u64,
-    used_swap_mb: u64,
-    total_swap_mb: u64,
-    free_memory_mb: u64,
-    total_received_mb: u64,
-    total_transmitted_mb: u64,
-    disk_reads_mb: f64,
-    disk_writes_mb: f64,
-    top_cpu_processes: Vec<String>,
+#[derive(Default, Clone, Copy, PartialEq)]
+enum OrdenUsuarios {
+    Usuario,
+    Procesos,
+    #[default]
+    Cpu,
+    Memoria,
+    Disco,
+}
+
+fn mib(valor: Bytes) -> u64 {
+    valor.get::<mebibyte>()
+}
+
+fn gib(valor: Bytes) -> f64 {
+    mib(valor) as f64 / 1024.0
+}
+
+fn kib_por_segundo(valor: Option<BytesPorSegundo>) -> String {
+    match valor {
+        Some(v) => format!("{:.1} KiB/s", v.get::<kibibyte_per_second>()),
+        None => "-".to_string(),
+    }
+}
+
+//Lectura más escritura, o None si falta alguna (en la primera muestra)
+fn disco_total(lectura: Option<BytesPorSegundo>, escritura: Option<BytesPorSegundo>) -> Option<BytesPorSegundo> {
+    lectura.zip(escritura).map(|(lectura, escritura)| lectura + escritura)
+}
+
+fn ordenar_usuarios(usuarios: &mut [Usuario], orden: OrdenUsuarios) {
+    match orden {
+        OrdenUsuarios::Usuario => usuarios.sort_by(|a, b| a.user.cmp(&b.user)),
+        OrdenUsuarios::Procesos => usuarios.sort_by_key(|u| std::cmp::Reverse(u.processes)),
+        OrdenUsuarios::Cpu => usuarios.sort_by(|a, b| b.cpu_usage_percent.total_cmp(&a.cpu_usage_percent)),
+        OrdenUsuarios::Memoria => usuarios.sort_by_key(|u| std::cmp::Reverse(u.memory_bytes.get::<kibibyte>())),
+        OrdenUsuarios::Disco => usuarios.sort_by(|a, b| {
+            let disco = |u: &Usuario| disco_total(u.disk_read_bytes_per_sec, u.disk_write_bytes_per_sec).map_or(0.0, |v| v.get::<kibibyte_per_second>());
+            disco(b).total_cmp(&disco(a))
+        }),
+    }
 }
 
 impl MetricsApp {
-    async fn obtener_datos() -> Datosuwu {
-        let mut system = System::new_all();
-        system.refresh_all();
-
-        let cpu_total_usage = system.global_cpu_info().cpu_usage();
-        let cpu_frequency_mhz = system.global_cpu_info().frequency();
-        let cpu_cores_usage: Vec<String> = system
-            .cpus()
-            .iter()
-            .enumerate()
-            .map(|(i, cpu)| format!("Core {}: {:.2}%", i, cpu.cpu_usage()))
-            .collect();
-
-        let used_memory_mb = system.used_memory() / 1024;
-        let total_memory_mb = system.total_memory() / 1024;
-        let used_swap_mb = system.used_swap() / 1024;
-        let total_swap_mb = system.total_swap() / 1024;
-        let free_memory_mb = system.free_memory() / 1024;
-        
-        let networks = Networks::new_with_refreshed_list();
-        let mut total_received_mb = 0;
-        let mut total_transmitted_mb = 0;
-
-        for (_, data) in &networks {
-            total_received_mb += data.total_received() / (1024 * 1024);
-            total_transmitted_mb += data.total_transmitted() / (1024 * 1024);
-        }
+    async fn obtener_datos(recolector: &Mutex<Recolector>) -> Datosuwu {
+        recolector.lock().await.obtener_datos().await
+    }
 
-        let mut disk_reads_mb = 0.0;
-        let mut disk_writes_mb = 0.0;
-        let mut disk_stream = disk::io_counters().await.unwrap();
-        while let Some(Ok(disk)) = disk_stream.next().await {
-            disk_reads_mb += disk.read_bytes().get::<byte>() as f64 / (1024.0 * 1024.0);
-            disk_writes_mb += disk.write_bytes().get::<byte>() as f64 / (1024.0 * 1024.0);
-        }
+    fn actualizar(&mut self, ctx: &egui::Context) {
+        let ctx = ctx.clone();
+        let datos_arc = Arc::clone(&self.datos);
+        let recolector = Arc::clone(&self.recolector);
 
-        let mut processes: Vec<_> = system.processes().values().collect();
-        processes.sort_by(|a, b| b.cpu_usage().partial_cmp(&a.cpu_usage()).unwrap());
-        let top_cpu_processes: Vec<String> = processes.iter().take(5).map(|p| {
-            format!("{}: {:.2}% CPU, {} KB memoria", p.name(), p.cpu_usage(), p.memory())
-        }).collect();
-
-        Datosuwu {
-            timestamp: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
-            cpu_total_usage,
-            cpu_frequency_mhz,
-            cpu_cores_usage,
-            used_memory_mb,
-            total_memory_mb,
-            used_swap_mb,
-            total_swap_mb,
-            free_memory_mb,
-            total_received_mb,
-            total_transmitted_mb,
-            disk_reads_mb,
-            disk_writes_mb,
-            top_cpu_processes,
-        }
+        tokio::spawn(async move {
+            let nuevos_datos = MetricsApp::obtener_datos(&recolector).await;
+            let mut datos = datos_arc.lock().await;
+            *datos = Some(nuevos_datos);
+            ctx.request_repaint();
+        });
+
+        self.last_update = Some(Instant::now());
     }
 }
 
@@ -105,17 +93,7 @@ impl App for MetricsApp {
         };
 
         if should_update {
-            let ctx = ctx.clone();
-            let datos_arc = Arc::clone(&self.datos);
-            
-            tokio::spawn(async move {
-                let nuevos_datos = MetricsApp::obtener_datos().await;
-                let mut datos = datos_arc.lock().await;
-                *datos = Some(nuevos_datos);
-                ctx.request_repaint();
-            });
-            
-            self.last_update = Some(Instant::now());
+            self.actualizar(ctx);
         }
 
         egui::CentralPanel::default().show(ctx, |ui| {
@@ -138,52 +116,224 @@ impl App for MetricsApp {
             };
 
 
-            ui.label(format!("Hora: {}", datos.timestamp));
+            ui.label(format!("Hora: {}", datos.local_time.as_ref().unwrap_or(&datos.timestamp)));
+            if let Some(equipo) = &datos.host {
+                ui.label(format!(
+                    "Equipo: {} ({}, kernel {})",
+                    equipo.hostname.as_deref().unwrap_or("-"),
+                    equipo.os.as_deref().unwrap_or("-"),
+                    equipo.kernel_version.as_deref().unwrap_or("-")
+                ));
+            }
             ui.separator();
 
-            ui.label(format!("CPU Total: {:.2}%", datos.cpu_total_usage));
-            ui.label(format!("Frecuencia CPU: {} MHz", datos.cpu_frequency_mhz));
-            for core in &datos.cpu_cores_usage {
-                ui.label(core);
+            //Las secciones que falten (colector deshabilitado o con error) no se muestran
+            for error in &datos.errors {
+                ui.colored_label(egui::Color32::RED, format!("Error en {}: {}", error.collector, error.message));
             }
 
-            ui.separator();
-            ui.label(format!(
-                "Memoria usada: {} MB / {} MB",
-                datos.used_memory_mb, datos.total_memory_mb
-            ));
-            ui.label(format!(
-                "Swap usada: {} MB / {} MB",
-                datos.used_swap_mb, datos.total_swap_mb
-            ));
-            ui.label(format!("Memoria libre: {} MB", datos.free_memory_mb));
+            if let Some(cpu) = &datos.cpu {
+                ui.label(format!("CPU Total: {:.2}%", cpu.total_usage_percent));
+                ui.label(format!("Frecuencia CPU: {} MHz", cpu.frequency_mhz.get::<megahertz>()));
+                for core in &cpu.cores {
+                    ui.label(format!("Core {}: {:.2}%", core.core, core.usage_percent));
+                }
+            }
 
-            ui.separator();
-            ui.label(format!("Red recibida: {:.2} MB", datos.total_received_mb));
-            ui.label(format!("Red enviada: {:.2} MB", datos.total_transmitted_mb));
+            if let Some(memory) = &datos.memory {
+                ui.separator();
+                //Lo disponible (libre + caché que el kernel puede soltar) es lo que de verdad queda
+                let disponible = memory.available_bytes.unwrap_or(memory.free_bytes);
+                ui.heading(format!(
+                    "Memoria disponible: {} MiB / {} MiB",
+                    mib(disponible), mib(memory.total_bytes)
+                ));
+                ui.label(format!(
+                    "Memoria usada: {} MiB, libre: {} MiB",
+                    mib(memory.used_bytes), mib(memory.free_bytes)
+                ));
+                ui.label(format!(
+                    "Swap usada: {} MiB / {} MiB",
+                    mib(memory.used_swap_bytes), mib(memory.total_swap_bytes)
+                ));
+                if let Some(detalle) = &memory.breakdown {
+                    ui.collapsing("Desglose", |ui| {
+                        ui.label(format!("Caché: {} MiB, búferes: {} MiB", mib(detalle.cached_bytes), mib(detalle.buffers_bytes)));
+                        ui.label(format!("Compartida: {} MiB, slab: {} MiB", mib(detalle.shared_bytes), mib(detalle.slab_bytes)));
+                        ui.label(format!("Sucia: {} MiB, escribiéndose: {} MiB", mib(detalle.dirty_bytes), mib(detalle.writeback_bytes)));
+                        ui.label(format!(
+                            "Hugepages: {} / {} libres de {} KiB",
+                            detalle.hugepages_free, detalle.hugepages_total, detalle.hugepage_size_bytes.get::<kibibyte>()
+                        ));
+                        ui.label(format!(
+                            "Swap: entrada {}, salida {}",
+                            kib_por_segundo(detalle.swap_in_bytes_per_sec), kib_por_segundo(detalle.swap_out_bytes_per_sec)
+                        ));
+                    });
+                }
+            }
 
-            ui.separator();
-            ui.label(format!("Lecturas de disco: {:.2} MB", datos.disk_reads_mb));
-            ui.label(format!("Escrituras de disco: {:.2} MB", datos.disk_writes_mb));
+            if let Some(network) = &datos.network {
+                ui.separator();
+                ui.label(format!(
+                    "Red recibida: {} MiB ({})",
+                    mib(network.received_bytes), kib_por_segundo(network.rx_bytes_per_sec)
+                ));
+                ui.label(format!(
+                    "Red enviada: {} MiB ({})",
+                    mib(network.transmitted_bytes), kib_por_segundo(network.tx_bytes_per_sec)
+                ));
+                ui.collapsing("Por interfaz", |ui| {
+                    for interfaz in &network.interfaces {
+                        ui.label(format!(
+                            "{}: ↓ {} ↑ {}, {} / {} paquetes, {} / {} errores",
+                            interfaz.name,
+                            kib_por_segundo(interfaz.rx_bytes_per_sec), kib_por_segundo(interfaz.tx_bytes_per_sec),
+                            interfaz.rx_packets, interfaz.tx_packets, interfaz.rx_errors, interfaz.tx_errors
+                        ));
+                    }
+                });
+            }
 
-            ui.separator();
-            ui.label("Top procesos por CPU:");
-            for proc in &datos.top_cpu_processes {
-                ui.label(proc);
+            if let Some(sockets) = &datos.sockets {
+                let estados: Vec<String> = sockets.tcp_states.iter().map(|(estado, n)| format!("{} {}", n, estado)).collect();
+                ui.label(format!("Conexiones TCP: {}; {} sockets UDP", estados.join(", "), sockets.udp_sockets));
+                ui.collapsing("Puertos en escucha", |ui| {
+                    for puerto in &sockets.listening {
+                        ui.label(format!(
+                            "{} {}:{} ({})",
+                            puerto.protocol, puerto.address, puerto.port,
+                            puerto.process.as_deref().unwrap_or("-")
+                        ));
+                    }
+                });
+                ui.collapsing("Direcciones remotas con más conexiones", |ui| {
+                    for par in &sockets.top_peers {
+                        ui.label(format!("{}: {}", par.address, par.connections));
+                    }
+                });
             }
 
-            if ui.button("Actualizar métricas ahora").clicked() {
-                let ctx = ctx.clone();
-                let datos_arc = Arc::clone(&self.datos);
-                
-                tokio::spawn(async move {
-                    let nuevos_datos = MetricsApp::obtener_datos().await;
-                    let mut datos = datos_arc.lock().await;
-                    *datos = Some(nuevos_datos);
-                    ctx.request_repaint();
+            if let Some(disk) = &datos.disk {
+                ui.separator();
+                ui.label(format!(
+                    "Lecturas de disco: {} MiB ({})",
+                    mib(disk.read_bytes), kib_por_segundo(disk.read_bytes_per_sec)
+                ));
+                ui.label(format!(
+                    "Escrituras de disco: {} MiB ({})",
+                    mib(disk.written_bytes), kib_por_segundo(disk.write_bytes_per_sec)
+                ));
+                ui.collapsing("Por dispositivo", |ui| {
+                    for dispositivo in &disk.devices {
+                        ui.label(format!(
+                            "{}: lectura {}, escritura {}, {} / {} operaciones",
+                            dispositivo.name,
+                            kib_por_segundo(dispositivo.read_bytes_per_sec), kib_por_segundo(dispositivo.write_bytes_per_sec),
+                            dispositivo.reads, dispositivo.writes
+                        ));
+                    }
+                });
+            }
+
+            if let Some(estadisticas) = &datos.disk_stats {
+                let ms = |latencia: Option<f64>| latencia.map_or("-".to_string(), |ms| format!("{:.2} ms", ms));
+                ui.collapsing("Latencia y uso de disco", |ui| {
+                    for disco in estadisticas {
+                        ui.label(format!(
+                            "{}: {:.0} / {:.0} IOPS, latencia {} / {}, cola {:.2}, {} en curso, {:.0}% ocupado",
+                            disco.name,
+                            disco.reads_per_sec.unwrap_or(0.0), disco.writes_per_sec.unwrap_or(0.0),
+                            ms(disco.read_latency_ms), ms(disco.write_latency_ms),
+                            disco.avg_queue_depth.unwrap_or(0.0), disco.in_flight,
+                            disco.utilization_percent.unwrap_or(0.0)
+                        ));
+                    }
+                });
+            }
+
+            if let Some(sistemas) = &datos.filesystems {
+                ui.separator();
+                ui.label("Sistemas de archivos:");
+                for sistema in sistemas {
+                    let total = mib(sistema.total_bytes).max(1) as f32;
+                    let usado = mib(sistema.used_bytes) as f32 / total;
+                    let inodos = match (sistema.inodes_used, sistema.inodes_total) {
+                        (Some(usados), Some(total)) if total > 0 => format!(", inodos {:.0}%", usados as f64 * 100.0 / total as f64),
+                        _ => String::new(),
+                    };
+                    ui.label(format!(
+                        "{} ({}, {}): {:.1} / {:.1} GiB, {:.1} GiB disponibles{}",
+                        sistema.mount_point, sistema.device, sistema.fs_type,
+                        gib(sistema.used_bytes), gib(sistema.total_bytes), gib(sistema.available_bytes), inodos
+                    ));
+                    ui.add(egui::ProgressBar::new(usado).show_percentage());
+                }
+            }
+
+            for (titulo, lista) in [
+                ("Top procesos por CPU:", &datos.top_processes),
+                ("Top procesos por memoria:", &datos.top_processes_by_memory),
+                ("Top procesos por E/S de disco:", &datos.top_processes_by_disk_io),
+            ] {
+                let Some(procesos) = lista else {
+                    continue;
+                };
+                ui.separator();
+                ui.label(titulo);
+                for proc in procesos {
+                    let disco = disco_total(proc.disk_read_bytes_per_sec, proc.disk_write_bytes_per_sec);
+                    ui.label(format!(
+                        "{} (PID {}, {}): {:.2}% CPU, {} MiB memoria, disco {}",
+                        proc.name,
+                        proc.pid.map_or("-".to_string(), |pid| pid.to_string()),
+                        proc.user.as_deref().unwrap_or("-"),
+                        proc.cpu_usage_percent, mib(proc.memory_bytes), kib_por_segundo(disco)
+                    ))
+                    //La línea de comandos completa al pasar el mouse
+                    .on_hover_text(proc.cmd.join(" "));
+                }
+            }
+
+            if let Some(usuarios) = &datos.per_user {
+                ui.separator();
+                ui.label("Consumo por usuario (clic en una columna para ordenar):");
+                let mut usuarios = usuarios.clone();
+                ordenar_usuarios(&mut usuarios, self.orden_usuarios);
+                egui::Grid::new("por_usuario").striped(true).show(ui, |ui| {
+                    for (titulo, orden) in [
+                        ("Usuario", OrdenUsuarios::Usuario),
+                        ("Procesos", OrdenUsuarios::Procesos),
+                        ("CPU", OrdenUsuarios::Cpu),
+                        ("Memoria", OrdenUsuarios::Memoria),
+                        ("Disco", OrdenUsuarios::Disco),
+                    ] {
+                        if ui.selectable_label(self.orden_usuarios == orden, titulo).clicked() {
+                            self.orden_usuarios = orden;
+                        }
+                    }
+                    ui.end_row();
+                    for usuario in &usuarios {
+                        ui.label(&usuario.user);
+                        ui.label(usuario.processes.to_string());
+                        ui.label(format!("{:.2}%", usuario.cpu_usage_percent));
+                        ui.label(format!("{} MiB", mib(usuario.memory_bytes)));
+                        ui.label(kib_por_segundo(disco_total(usuario.disk_read_bytes_per_sec, usuario.disk_write_bytes_per_sec)));
+                        ui.end_row();
+                    }
                 });
-                
-                self.last_update = Some(Instant::now());
+            }
+
+            if let Some(propio) = &datos.self_overhead {
+                ui.separator();
+                ui.label(format!(
+                    "Consumo del monitor: {:.2}% CPU, {} MiB memoria",
+                    propio.cpu_usage_percent, mib(propio.rss_bytes)
+                ));
+            }
+
+            if ui.button("Actualizar métricas ahora").clicked() {
+                self.actualizar(ctx);
             }
         });
     }
@@ -191,8 +341,12 @@ impl App for MetricsApp {
 
 #[tokio::main]
 async fn main() -> eframe::Result<()> {
-    let datos_iniciales = MetricsApp::obtener_datos().await;
+    let mut recolector = Recolector::new();
+    recolector.preparar().await;
+    tokio::time::sleep(telemetria::VENTANA_MEDICION).await;
+    let datos_iniciales = recolector.obtener_datos().await;
     let datos_shared = Arc::new(Mutex::new(Some(datos_iniciales)));
+    let recolector_shared = Arc::new(Mutex::new(recolector));
     
     let native_options = eframe::NativeOptions::default();
 
@@ -202,7 +356,9 @@ async fn main() -> eframe::Result<()> {
         Box::new(move |_cc| {
             Box::new(MetricsApp {
                 datos: Arc::clone(&datos_shared),
+                recolector: Arc::clone(&recolector_shared),
                 last_update: Some(Instant::now()),
+                orden_usuarios: OrdenUsuarios::default(),
             })
         }),
     )
@@ -0,0 +1,541 @@
+//Configuración de act4: archivo TOML, variables de entorno ACT4_* y argumentos, en ese orden de prioridad
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use serde::Deserialize;
+use telemetria::{Compresion, ConfigColector, ConfigRecolector, OrdenProcesos, Periodo, PoliticaRotacion, ZonaHoraria};
+
+//Archivo que se lee si no se indica otro con --config o ACT4_CONFIG
+const ARCHIVO_POR_DEFECTO: &str = "act4.toml";
+//Intervalo por defecto del modo daemon (el mismo que la tarea programada de scriptuwu.ps1)
+const INTERVALO_POR_DEFECTO_SEGS: u64 = 300;
+//Cada cuánto se revisa la tabla de procesos para los eventos; un proceso que dura menos puede no verse
+const INTERVALO_EVENTOS_PROCESOS_MS: u64 = 1000;
+
+//Un nivel de configuración; lo que no se indica (None) se toma del nivel anterior
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Ajustes {
+    pub output: Option<PathBuf>,
+    pub interval_secs: Option<u64>,
+    pub host_label: Option<String>,
+    pub timestamp_format: Option<String>,
+    pub timezone: Option<String>,
+    pub top_processes: Option<usize>,
+    //Qué tops se arman: "cpu", "memory" y/o "disk_io"
+    pub top_processes_by: Option<Vec<String>>,
+    pub ignored_processes: Option<Vec<String>>,
+    pub proc_root: Option<PathBuf>,
+    pub cgroup_root: Option<PathBuf>,
+    pub hwmon_root: Option<PathBuf>,
+    pub block_root: Option<PathBuf>,
+    pub net_root: Option<PathBuf>,
+    #[serde(default)]
+    pub collectors: HashMap<String, AjustesColector>,
+    #[serde(default)]
+    pub rotation: AjustesRotacion,
+    //Archivo JSONL de eventos (procesos que arrancan y terminan...), aparte de las muestras
+    pub events_output: Option<PathBuf>,
+    #[serde(default)]
+    pub process_events: AjustesEventosProcesos,
+    #[serde(default)]
+    pub kernel_events: AjustesEventosKernel,
+    //Dirección donde el daemon sirve /metrics para Prometheus, como "0.0.0.0:9184"; sin ella no se sirve
+    pub metrics_listen: Option<String>,
+    //Destinos a los que también se manda cada muestra; una lista en un nivel reemplaza a la del anterior
+    pub sinks: Option<Vec<AjustesDestino>>,
+}
+
+#[derive(Deserialize, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AjustesColector {
+    pub enabled: Option<bool>,
+    pub interval_secs: Option<u64>,
+    pub timeout_secs: Option<u64>,
+    //Patrones con * y ? de interfaces (network) o dispositivos (disk); exclude reemplaza al de por defecto
+    pub include: Option<Vec<String>>,
+    pub exclude: Option<Vec<String>>,
+}
+
+//Tabla [rotation]: cuándo se rota el archivo de salida y cuántos segmentos se conservan
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct AjustesRotacion {
+    pub max_bytes: Option<u64>,
+    pub period: Option<Periodo>,
+    pub compression: Option<Compresion>,
+    pub max_files: Option<usize>,
+    pub max_age_days: Option<u64>,
+}
+
+//Tabla [process_events]: en modo daemon se revisa la tabla de procesos cada interval_ms
+//para registrar los que arrancan y terminan entre muestras
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct AjustesEventosProcesos {
+    pub enabled: Option<bool>,
+    pub interval_ms: Option<u64>,
+}
+
+//Tabla [kernel_events]: en modo daemon se sigue el log del kernel (source, /dev/kmsg por defecto)
+//para registrar OOM kills, segfaults y errores de hardware
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct AjustesEventosKernel {
+    pub enabled: Option<bool>,
+    pub source: Option<PathBuf>,
+}
+
+//Tablas [[sinks]]: InfluxDB (line protocol) en un archivo, por UDP o por HTTP, Graphite (plaintext) por TCP y
+//defense_system (Metrics en JSON) por UDP. En los argumentos y en ACT4_SINKS se escriben como <type>=<path|address|url>
+#[derive(Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum AjustesDestino {
+    InfluxFile { path: PathBuf },
+    InfluxUdp { address: String },
+    //Solo http://; el token va en el encabezado Authorization (InfluxDB 2)
+    InfluxHttp { url: String, token: Option<String> },
+    //prefix es el primer nodo de la ruta de cada métrica; "act4" si no se indica
+    Graphite { address: String, prefix: Option<String> },
+    //defense_system escucha en 127.0.0.1:4000
+    DefenseSystem { address: String },
+}
+
+impl FromStr for AjustesDestino {
+    type Err = String;
+
+    fn from_str(texto: &str) -> Result<Self, Self::Err> {
+        let (tipo, destino) = texto
+            .split_once('=')
+            .ok_or(format!("se esperaba <tipo>=<destino>: {}", texto))?;
+        let destino = destino.to_string();
+        match tipo {
+            "influx_file" => Ok(AjustesDestino::InfluxFile { path: PathBuf::from(destino) }),
+            "influx_udp" => Ok(AjustesDestino::InfluxUdp { address: destino }),
+            "influx_http" => Ok(AjustesDestino::InfluxHttp { url: destino, token: None }),
+            "graphite" => Ok(AjustesDestino::Graphite { address: destino, prefix: None }),
+            "defense_system" => Ok(AjustesDestino::DefenseSystem { address: destino }),
+            otro => Err(format!(
+                "tipo de destino desconocido: {} (influx_file, influx_udp, influx_http, graphite o defense_system)",
+                otro
+            )),
+        }
+    }
+}
+
+//Configuración final ya combinada
+pub struct Config {
+    pub salida: PathBuf,
+    pub rotacion: PoliticaRotacion,
+    //Se rota con la misma política que la salida
+    pub salida_eventos: PathBuf,
+    //None si los eventos de procesos están deshabilitados
+    pub eventos_procesos: Option<Duration>,
+    //Log del kernel a seguir; None si los eventos del kernel están deshabilitados
+    pub eventos_kernel: Option<PathBuf>,
+    //None si no se sirve /metrics
+    pub escucha_metricas: Option<SocketAddr>,
+    pub destinos: Vec<AjustesDestino>,
+    pub intervalo: Duration,
+    pub recolector: ConfigRecolector,
+}
+
+//Segundos mayores que 0
+pub fn leer_segundos(valor: &str) -> Result<Duration, String> {
+    match valor.parse::<u64>() {
+        Ok(segundos) => segundos_positivos(segundos),
+        Err(_) => Err(format!("intervalo inválido: {}", valor)),
+    }
+}
+
+fn segundos_positivos(segundos: u64) -> Result<Duration, String> {
+    if segundos == 0 {
+        return Err("el intervalo debe ser mayor que 0".to_string());
+    }
+    Ok(Duration::from_secs(segundos))
+}
+
+fn lista_por_comas(lista: &str) -> Vec<String> {
+    lista.split(',').map(str::trim).filter(|n| !n.is_empty()).map(String::from).collect()
+}
+
+impl Ajustes {
+    fn desde_archivo(ruta: &Path) -> Result<Ajustes, String> {
+        let texto = std::fs::read_to_string(ruta).map_err(|e| format!("{}: {}", ruta.display(), e))?;
+        toml::from_str(&texto).map_err(|e| format!("{}: {}", ruta.display(), e))
+    }
+
+    //ACT4_OUTPUT, ACT4_INTERVAL_SECS, ACT4_HOST_LABEL, ACT4_PROC_ROOT, ACT4_CGROUP_ROOT, ACT4_HWMON_ROOT, ACT4_BLOCK_ROOT,
+    //ACT4_NET_ROOT, ACT4_TIMESTAMP_FORMAT, ACT4_TIMEZONE,
+    //ACT4_TOP_PROCESSES, ACT4_TOP_PROCESSES_BY, ACT4_IGNORED_PROCESSES y ACT4_DISABLED_COLLECTORS (listas separadas por comas),
+    //ACT4_ROTATION_MAX_BYTES, ACT4_ROTATION_PERIOD, ACT4_ROTATION_COMPRESSION, ACT4_ROTATION_MAX_FILES
+    //ACT4_ROTATION_MAX_AGE_DAYS, ACT4_EVENTS_OUTPUT, ACT4_PROCESS_EVENTS y ACT4_KERNEL_EVENTS (true o false),
+    //ACT4_PROCESS_EVENTS_INTERVAL_MS, ACT4_KERNEL_EVENTS_SOURCE y ACT4_METRICS_LISTEN; ACT4_NETWORK_INCLUDE, ACT4_NETWORK_EXCLUDE, ACT4_DISK_INCLUDE,
+    //ACT4_DISK_EXCLUDE y ACT4_SINKS también son listas separadas por comas
+    fn desde_entorno(variable: impl Fn(&str) -> Option<String>) -> Result<Ajustes, String> {
+        let numero = |nombre: &str| -> Result<Option<u64>, String> {
+            variable(nombre)
+                .map(|v| v.parse().map_err(|_| format!("{} no es un número: {}", nombre, v)))
+                .transpose()
+        };
+        let booleano = |nombre: &str| -> Result<Option<bool>, String> {
+            variable(nombre)
+                .map(|v| v.parse().map_err(|_| format!("{} no es true ni false: {}", nombre, v)))
+                .transpose()
+        };
+        let mut ajustes = Ajustes {
+            output: variable("ACT4_OUTPUT").map(PathBuf::from),
+            interval_secs: numero("ACT4_INTERVAL_SECS")?,
+            host_label: variable("ACT4_HOST_LABEL"),
+            timestamp_format: variable("ACT4_TIMESTAMP_FORMAT"),
+            timezone: variable("ACT4_TIMEZONE"),
+            top_processes: numero("ACT4_TOP_PROCESSES")?.map(|n| n as usize),
+            top_processes_by: variable("ACT4_TOP_PROCESSES_BY").map(|lista| lista_por_comas(&lista)),
+            ignored_processes: variable("ACT4_IGNORED_PROCESSES").map(|lista| lista_por_comas(&lista)),
+            proc_root: variable("ACT4_PROC_ROOT").map(PathBuf::from),
+            cgroup_root: variable("ACT4_CGROUP_ROOT").map(PathBuf::from),
+            hwmon_root: variable("ACT4_HWMON_ROOT").map(PathBuf::from),
+            block_root: variable("ACT4_BLOCK_ROOT").map(PathBuf::from),
+            net_root: variable("ACT4_NET_ROOT").map(PathBuf::from),
+            collectors: HashMap::new(),
+            rotation: AjustesRotacion {
+                max_bytes: numero("ACT4_ROTATION_MAX_BYTES")?,
+                period: variable("ACT4_ROTATION_PERIOD").map(|v| v.parse()).transpose()?,
+                compression: variable("ACT4_ROTATION_COMPRESSION").map(|v| v.parse()).transpose()?,
+                max_files: numero("ACT4_ROTATION_MAX_FILES")?.map(|n| n as usize),
+                max_age_days: numero("ACT4_ROTATION_MAX_AGE_DAYS")?,
+            },
+            events_output: variable("ACT4_EVENTS_OUTPUT").map(PathBuf::from),
+            process_events: AjustesEventosProcesos {
+                enabled: booleano("ACT4_PROCESS_EVENTS")?,
+                interval_ms: numero("ACT4_PROCESS_EVENTS_INTERVAL_MS")?,
+            },
+            kernel_events: AjustesEventosKernel {
+                enabled: booleano("ACT4_KERNEL_EVENTS")?,
+                source: variable("ACT4_KERNEL_EVENTS_SOURCE").map(PathBuf::from),
+            },
+            metrics_listen: variable("ACT4_METRICS_LISTEN"),
+            sinks: variable("ACT4_SINKS")
+                .map(|lista| lista_por_comas(&lista).iter().map(|d| d.parse()).collect::<Result<_, _>>())
+                .transpose()?,
+        };
+        if let Some(lista) = variable("ACT4_DISABLED_COLLECTORS") {
+            for nombre in lista_por_comas(&lista) {
+                ajustes.collectors.entry(nombre).or_default().enabled = Some(false);
+            }
+        }
+        for colector in ["network", "disk"] {
+            let prefijo = format!("ACT4_{}_", colector.to_uppercase());
+            let include = variable(&format!("{}INCLUDE", prefijo)).map(|lista| lista_por_comas(&lista));
+            let exclude = variable(&format!("{}EXCLUDE", prefijo)).map(|lista| lista_por_comas(&lista));
+            if include.is_some() || exclude.is_some() {
+                let ajustes = ajustes.collectors.entry(colector.to_string()).or_default();
+                ajustes.include = include;
+                ajustes.exclude = exclude;
+            }
+        }
+        Ok(ajustes)
+    }
+
+    //Los valores de `otro` tienen prioridad sobre los de `self`
+    fn combinar(mut self, otro: Ajustes) -> Ajustes {
+        self.output = otro.output.or(self.output);
+        self.interval_secs = otro.interval_secs.or(self.interval_secs);
+        self.host_label = otro.host_label.or(self.host_label);
+        self.timestamp_format = otro.timestamp_format.or(self.timestamp_format);
+        self.timezone = otro.timezone.or(self.timezone);
+        self.top_processes = otro.top_processes.or(self.top_processes);
+        self.top_processes_by = otro.top_processes_by.or(self.top_processes_by);
+        self.ignored_processes = otro.ignored_processes.or(self.ignored_processes);
+        self.proc_root = otro.proc_root.or(self.proc_root);
+        self.cgroup_root = otro.cgroup_root.or(self.cgroup_root);
+        self.hwmon_root = otro.hwmon_root.or(self.hwmon_root);
+        self.block_root = otro.block_root.or(self.block_root);
+        self.net_root = otro.net_root.or(self.net_root);
+        for (nombre, colector) in otro.collectors {
+            let actual = self.collectors.entry(nombre).or_default();
+            actual.enabled = colector.enabled.or(actual.enabled);
+            actual.interval_secs = colector.interval_secs.or(actual.interval_secs);
+            actual.timeout_secs = colector.timeout_secs.or(actual.timeout_secs);
+            actual.include = colector.include.or(actual.include.take());
+            actual.exclude = colector.exclude.or(actual.exclude.take());
+        }
+        let (rotacion, otra) = (&mut self.rotation, otro.rotation);
+        rotacion.max_bytes = otra.max_bytes.or(rotacion.max_bytes);
+        rotacion.period = otra.period.or(rotacion.period);
+        rotacion.compression = otra.compression.or(rotacion.compression);
+        rotacion.max_files = otra.max_files.or(rotacion.max_files);
+        rotacion.max_age_days = otra.max_age_days.or(rotacion.max_age_days);
+        self.events_output = otro.events_output.or(self.events_output);
+        let (eventos, otros) = (&mut self.process_events, otro.process_events);
+        eventos.enabled = otros.enabled.or(eventos.enabled);
+        eventos.interval_ms = otros.interval_ms.or(eventos.interval_ms);
+        let (kernel, otro_kernel) = (&mut self.kernel_events, otro.kernel_events);
+        kernel.enabled = otro_kernel.enabled.or(kernel.enabled);
+        kernel.source = otro_kernel.source.or(kernel.source.take());
+        self.metrics_listen = otro.metrics_listen.or(self.metrics_listen);
+        self.sinks = otro.sinks.or(self.sinks);
+        self
+    }
+
+    fn en_config(self) -> Result<Config, String> {
+        let mut recolector = ConfigRecolector::default();
+        if let Some(formato) = self.timestamp_format {
+            recolector.formato_timestamp = formato;
+        }
+        if let Some(zona) = self.timezone {
+            recolector.zona_horaria = zona.parse::<ZonaHoraria>()?;
+        }
+        if let Some(top) = self.top_processes {
+            recolector.top_procesos = top;
+        }
+        if let Some(ordenes) = self.top_processes_by {
+            recolector.listas_procesos = ordenes.iter().map(|o| o.parse::<OrdenProcesos>()).collect::<Result<_, _>>()?;
+        }
+        if let Some(ignorados) = self.ignored_processes {
+            recolector.procesos_ignorados = ignorados;
+        }
+        if let Some(raiz) = self.proc_root {
+            recolector.raiz_proc = raiz;
+        }
+        if let Some(raiz) = self.cgroup_root {
+            recolector.raiz_cgroup = raiz;
+        }
+        if let Some(raiz) = self.hwmon_root {
+            recolector.raiz_hwmon = raiz;
+        }
+        if let Some(raiz) = self.block_root {
+            recolector.raiz_bloques = raiz;
+        }
+        if let Some(raiz) = self.net_root {
+            recolector.raiz_red = raiz;
+        }
+        recolector.host_label = self.host_label;
+        for (nombre, ajustes) in self.collectors {
+            let mut colector = ConfigColector::default();
+            if let Some(habilitado) = ajustes.enabled {
+                colector.habilitado = habilitado;
+            }
+            if let Some(segundos) = ajustes.interval_secs {
+                colector.intervalo = Some(segundos_positivos(segundos)?);
+            }
+            if let Some(segundos) = ajustes.timeout_secs {
+                colector.limite = segundos_positivos(segundos)?;
+            }
+            if ajustes.include.is_some() || ajustes.exclude.is_some() {
+                let filtro = match nombre.as_str() {
+                    "network" => &mut recolector.filtro_interfaces,
+                    "disk" => &mut recolector.filtro_discos,
+                    _ => return Err(format!("el colector {} no acepta include ni exclude (solo network y disk)", nombre)),
+                };
+                if let Some(incluir) = ajustes.include {
+                    filtro.incluir = incluir;
+                }
+                if let Some(excluir) = ajustes.exclude {
+                    filtro.excluir = excluir;
+                }
+            }
+            recolector.colectores.insert(nombre, colector);
+        }
+        let rotacion = PoliticaRotacion {
+            max_bytes: self.rotation.max_bytes,
+            periodo: self.rotation.period,
+            compresion: self.rotation.compression.unwrap_or_default(),
+            max_archivos: self.rotation.max_files,
+            max_antiguedad: self.rotation.max_age_days.map(|dias| Duration::from_secs(dias * 24 * 60 * 60)),
+        };
+        let eventos_procesos = match self.process_events.enabled {
+            Some(false) => None,
+            _ => match self.process_events.interval_ms.unwrap_or(INTERVALO_EVENTOS_PROCESOS_MS) {
+                0 => return Err("process_events.interval_ms debe ser mayor que 0".to_string()),
+                ms => Some(Duration::from_millis(ms)),
+            },
+        };
+        //Por defecto se sigue /dev/kmsg en Linux; en otros sistemas solo si se indica otro source
+        let kernel = self.kernel_events;
+        let eventos_kernel = kernel
+            .enabled
+            .unwrap_or(cfg!(target_os = "linux") || kernel.source.is_some())
+            .then(|| kernel.source.unwrap_or_else(|| PathBuf::from("/dev/kmsg")));
+        let escucha_metricas = self
+            .metrics_listen
+            .map(|direccion| {
+                direccion
+                    .parse::<SocketAddr>()
+                    .map_err(|_| format!("metrics_listen debe ser <ip>:<puerto>: {}", direccion))
+            })
+            .transpose()?;
+        Ok(Config {
+            salida: self.output.unwrap_or_else(|| PathBuf::from("datosuwu.jsonl")),
+            rotacion,
+            salida_eventos: self.events_output.unwrap_or_else(|| PathBuf::from("eventosuwu.jsonl")),
+            eventos_procesos,
+            eventos_kernel,
+            escucha_metricas,
+            destinos: self.sinks.unwrap_or_default(),
+            intervalo: segundos_positivos(self.interval_secs.unwrap_or(INTERVALO_POR_DEFECTO_SEGS))?,
+            recolector,
+        })
+    }
+}
+
+impl Config {
+    //`archivo` viene de --config; si no, de ACT4_CONFIG; si no, act4.toml solo si existe
+    pub fn cargar(archivo: Option<PathBuf>, argumentos: Ajustes) -> Result<Config, String> {
+        let archivo = archivo.or_else(|| std::env::var_os("ACT4_CONFIG").map(PathBuf::from));
+        let base = match archivo {
+            Some(ruta) => Ajustes::desde_archivo(&ruta)?,
+            None if Path::new(ARCHIVO_POR_DEFECTO).exists() => Ajustes::desde_archivo(Path::new(ARCHIVO_POR_DEFECTO))?,
+            None => Ajustes::default(),
+        };
+        let entorno = Ajustes::desde_entorno(|nombre| std::env::var(nombre).ok())?;
+        base.combinar(entorno).combinar(argumentos).en_config()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EJEMPLO: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/act4.example.toml");
+
+    fn ejemplo() -> Config {
+        Ajustes::desde_archivo(Path::new(EJEMPLO)).and_then(Ajustes::en_config).unwrap()
+    }
+
+    #[test]
+    fn el_ejemplo_se_carga() {
+        let config = ejemplo();
+        assert_eq!(config.salida, PathBuf::from("datosuwu.jsonl"));
+        assert_eq!(config.salida_eventos, PathBuf::from("eventosuwu.jsonl"));
+        assert_eq!(config.intervalo, Duration::from_secs(300));
+        assert_eq!(config.eventos_procesos, Some(Duration::from_millis(1000)));
+        assert_eq!(config.eventos_kernel, Some(PathBuf::from("/dev/kmsg")));
+        assert!(config.escucha_metricas.is_none());
+        assert!(config.destinos.is_empty());
+        assert_eq!(config.recolector.host_label.as_deref(), Some("laptop-pau"));
+        assert_eq!(config.recolector.filtro_discos.incluir, ["sd*", "nvme*", "vd*"]);
+        assert_eq!(config.recolector.raiz_red, PathBuf::from("/sys/class/net"));
+        assert_eq!(config.recolector.colectores["processes"].intervalo, Some(Duration::from_secs(600)));
+        assert_eq!(config.rotacion.max_archivos, Some(30));
+        //Los nombres de [collectors.*] también tienen que existir
+        telemetria::Recolector::con_config(config.recolector).unwrap();
+    }
+
+    //Lo comentado en el ejemplo (metrics_listen, [[sinks]]) también tiene que ser válido al descomentarlo
+    #[test]
+    fn el_ejemplo_descomentado_se_carga() {
+        let texto = std::fs::read_to_string(EJEMPLO).unwrap();
+        let descomentado: String = texto
+            .lines()
+            .map(|linea| match linea.strip_prefix('#') {
+                Some(resto) if !resto.starts_with(' ') && !resto.is_empty() => resto,
+                _ => linea,
+            })
+            .map(|linea| format!("{}\n", linea))
+            .collect();
+        let ajustes: Ajustes = toml::from_str(&descomentado).unwrap();
+        let config = ajustes.en_config().unwrap();
+        assert_eq!(config.escucha_metricas, Some("0.0.0.0:9184".parse().unwrap()));
+        let tipos: Vec<_> = config
+            .destinos
+            .iter()
+            .map(|destino| match destino {
+                AjustesDestino::InfluxFile { .. } => "influx_file",
+                AjustesDestino::InfluxUdp { .. } => "influx_udp",
+                AjustesDestino::InfluxHttp { token, .. } => {
+                    assert!(token.is_some());
+                    "influx_http"
+                }
+                AjustesDestino::Graphite { prefix, .. } => {
+                    assert_eq!(prefix.as_deref(), Some("act4"));
+                    "graphite"
+                }
+                AjustesDestino::DefenseSystem { address } => {
+                    assert_eq!(address, "127.0.0.1:4000");
+                    "defense_system"
+                }
+            })
+            .collect();
+        assert_eq!(tipos, ["influx_file", "influx_udp", "influx_http", "graphite", "defense_system"]);
+    }
+
+    //Un entorno de mentira con las variables dadas
+    fn entorno(variables: &[(&str, &str)]) -> Result<Ajustes, String> {
+        let variables: HashMap<String, String> =
+            variables.iter().map(|(nombre, valor)| (nombre.to_string(), valor.to_string())).collect();
+        Ajustes::desde_entorno(|nombre| variables.get(nombre).cloned())
+    }
+
+    const ARCHIVO: &str = r#"
+output = "archivo.jsonl"
+interval_secs = 60
+host_label = "archivo"
+proc_root = "/archivo/proc"
+top_processes = 5
+
+[collectors.network]
+include = ["eth*"]
+exclude = ["lo"]
+
+[rotation]
+compression = "gzip"
+max_files = 5
+
+[[sinks]]
+type = "influx_udp"
+address = "127.0.0.1:8089"
+"#;
+
+    #[test]
+    fn archivo_luego_entorno_luego_argumentos() {
+        let archivo: Ajustes = toml::from_str(ARCHIVO).unwrap();
+        let entorno = entorno(&[
+            ("ACT4_INTERVAL_SECS", "30"),
+            ("ACT4_HOST_LABEL", "entorno"),
+            ("ACT4_NETWORK_EXCLUDE", "docker*, veth*"),
+            ("ACT4_NET_ROOT", "/host/sys/class/net"),
+            ("ACT4_ROTATION_MAX_FILES", "7"),
+            ("ACT4_DISABLED_COLLECTORS", "temperatures"),
+            ("ACT4_SINKS", "graphite=127.0.0.1:2003,defense_system=127.0.0.1:4000"),
+        ])
+        .unwrap();
+        let argumentos = Ajustes { interval_secs: Some(10), top_processes: Some(3), ..Ajustes::default() };
+        let config = archivo.combinar(entorno).combinar(argumentos).en_config().unwrap();
+        //Lo que solo está en el archivo se queda
+        assert_eq!(config.salida, PathBuf::from("archivo.jsonl"));
+        assert_eq!(config.recolector.raiz_proc, PathBuf::from("/archivo/proc"));
+        assert_eq!(config.rotacion.compresion, Compresion::Gzip);
+        assert_eq!(config.recolector.filtro_interfaces.incluir, ["eth*"]);
+        //El entorno gana al archivo, también dentro de las tablas
+        assert_eq!(config.recolector.host_label.as_deref(), Some("entorno"));
+        assert_eq!(config.recolector.filtro_interfaces.excluir, ["docker*", "veth*"]);
+        assert_eq!(config.recolector.raiz_red, PathBuf::from("/host/sys/class/net"));
+        assert_eq!(config.rotacion.max_archivos, Some(7));
+        assert!(!config.recolector.colectores["temperatures"].habilitado);
+        //Una lista de destinos reemplaza a la anterior entera
+        assert!(matches!(
+            config.destinos.as_slice(),
+            [AjustesDestino::Graphite { .. }, AjustesDestino::DefenseSystem { .. }]
+        ));
+        //Y los argumentos a los dos
+        assert_eq!(config.intervalo, Duration::from_secs(10));
+        assert_eq!(config.recolector.top_procesos, 3);
+    }
+
+    #[test]
+    fn valores_invalidos_en_el_entorno() {
+        let error = |variables: &[(&str, &str)]| entorno(variables).err().unwrap();
+        assert_eq!(error(&[("ACT4_INTERVAL_SECS", "5m")]), "ACT4_INTERVAL_SECS no es un número: 5m");
+        assert_eq!(error(&[("ACT4_PROCESS_EVENTS", "yes")]), "ACT4_PROCESS_EVENTS no es true ni false: yes");
+        assert!(error(&[("ACT4_ROTATION_PERIOD", "weekly")]).contains("hourly o daily"));
+        assert!(error(&[("ACT4_ROTATION_COMPRESSION", "xz")]).contains("none, gzip o zstd"));
+        assert!(error(&[("ACT4_SINKS", "ftp=127.0.0.1:21")]).starts_with("tipo de destino desconocido: ftp"));
+        assert!(error(&[("ACT4_SINKS", "127.0.0.1:2003")]).starts_with("se esperaba <tipo>=<destino>"));
+        //Un intervalo 0 se lee, pero no pasa al armar la configuración
+        let cero = entorno(&[("ACT4_INTERVAL_SECS", "0")]).unwrap();
+        assert_eq!(cero.en_config().err().unwrap(), "el intervalo debe ser mayor que 0");
+    }
+}
@@ -0,0 +1,493 @@
+//Destinos adicionales de las muestras: InfluxDB (line protocol) en un archivo, por UDP o por HTTP, Graphite
+//(plaintext) por TCP y defense_system (Metrics) por UDP. Cada destino corre en su propia tarea con su cola: si no responde, las muestras esperan
+//ahí y se reintenta con una espera que va creciendo, sin detener el muestreo ni la escritura de datosuwu.jsonl
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use telemetria::{puntos, Datosuwu, EscritorRotativo, Metrics, PoliticaRotacion};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use crate::config::AjustesDestino;
+
+//Muestras que pueden esperar en la cola de un destino caído; cuando se llena se descartan las más viejas
+const MAX_PENDIENTES: usize = 1000;
+//Muestras entre el ciclo principal y la tarea del destino, que las pasa enseguida a su cola
+const CAPACIDAD_CANAL: usize = 16;
+const ESPERA_INICIAL: Duration = Duration::from_secs(1);
+const ESPERA_MAXIMA: Duration = Duration::from_secs(60);
+//Lo máximo que puede tardar un intento (conexión incluida)
+const LIMITE_ENVIO: Duration = Duration::from_secs(10);
+//Al terminar se hace un último intento con lo pendiente; lo que no salga en este tiempo se pierde
+const LIMITE_CIERRE: Duration = Duration::from_secs(15);
+//InfluxDB por UDP: varias líneas por datagrama, pero sin pasar del MTU típico para que no se fragmenten
+const MAX_DATAGRAMA: usize = 1400;
+const PREFIJO_GRAPHITE: &str = "act4";
+
+pub struct Destinos {
+    //Descripción para los avisos, cola y tarea de cada destino
+    destinos: Vec<(String, mpsc::Sender<Arc<Datosuwu>>, JoinHandle<()>)>,
+}
+
+impl Destinos {
+    pub fn iniciar(config: &[AjustesDestino], rotacion: &PoliticaRotacion) -> Result<Destinos, String> {
+        let mut destinos = Vec::new();
+        for ajustes in config {
+            let transporte = Transporte::new(ajustes, rotacion)?;
+            let (cola, recibidas) = mpsc::channel(CAPACIDAD_CANAL);
+            let nombre = descripcion(ajustes);
+            destinos.push((nombre.clone(), cola, tokio::spawn(correr(transporte, nombre, recibidas))));
+        }
+        Ok(Destinos { destinos })
+    }
+
+    //No espera a ningún destino
+    pub fn enviar(&self, datos: &Datosuwu) {
+        let datos = Arc::new(datos.clone());
+        for (nombre, cola, _) in &self.destinos {
+            if cola.try_send(datos.clone()).is_err() {
+                eprintln!("{}: no alcanza a recibir las muestras; se descarta una", nombre);
+            }
+        }
+    }
+
+    //Cierra las colas y espera a que cada destino mande lo que tiene pendiente, hasta LIMITE_CIERRE
+    pub async fn cerrar(self) {
+        let tareas: Vec<JoinHandle<()>> = self.destinos.into_iter().map(|(_, _, tarea)| tarea).collect();
+        let limite = Instant::now() + LIMITE_CIERRE;
+        for mut tarea in tareas {
+            if tokio::time::timeout_at(limite, &mut tarea).await.is_err() {
+                tarea.abort();
+            }
+        }
+    }
+}
+
+fn descripcion(ajustes: &AjustesDestino) -> String {
+    match ajustes {
+        AjustesDestino::InfluxFile { path } => format!("influx_file {}", path.display()),
+        AjustesDestino::InfluxUdp { address } => format!("influx_udp {}", address),
+        AjustesDestino::InfluxHttp { url, .. } => format!("influx_http {}", url),
+        AjustesDestino::Graphite { address, .. } => format!("graphite {}", address),
+        AjustesDestino::DefenseSystem { address } => format!("defense_system {}", address),
+    }
+}
+
+//Cómo falló un envío: lo que el destino rechaza por inválido no se reintenta
+enum Fallo {
+    Reintentar(String),
+    Descartar(String),
+}
+
+//"http://host:8086/api/v2/write?org=...&bucket=...&precision=ns"
+struct Url {
+    //host:puerto, para conectarse
+    direccion: String,
+    //Para el encabezado Host
+    host: String,
+    ruta: String,
+}
+
+impl Url {
+    fn new(texto: &str) -> Result<Url, String> {
+        let resto = texto
+            .strip_prefix("http://")
+            .ok_or(format!("la url de influx_http debe empezar con http://: {}", texto))?;
+        let (host, ruta) = match resto.find('/') {
+            Some(i) => (&resto[..i], &resto[i..]),
+            None => (resto, "/"),
+        };
+        if host.is_empty() {
+            return Err(format!("falta el equipo en la url de influx_http: {}", texto));
+        }
+        //"[::1]:8086" e "influx:8086" tienen puerto; "[::1]" e "influx" no
+        let con_puerto = host.contains(':') && !host.ends_with(']');
+        let direccion = if con_puerto { host.to_string() } else { format!("{}:80", host) };
+        Ok(Url { direccion, host: host.to_string(), ruta: ruta.to_string() })
+    }
+}
+
+enum Transporte {
+    Archivo(EscritorRotativo),
+    Udp { direccion: String, socket: Option<UdpSocket> },
+    Http { url: Url, token: Option<String> },
+    Graphite { direccion: String, prefijo: String, conexion: Option<TcpStream> },
+    //Un datagrama con el JSON de Metrics por muestra
+    Defensa { direccion: String, socket: Option<UdpSocket> },
+}
+
+impl Transporte {
+    fn new(ajustes: &AjustesDestino, rotacion: &PoliticaRotacion) -> Result<Transporte, String> {
+        Ok(match ajustes {
+            //Se rota con la misma política que la salida
+            AjustesDestino::InfluxFile { path } => Transporte::Archivo(EscritorRotativo::new(path.clone(), rotacion.clone())),
+            AjustesDestino::InfluxUdp { address } => Transporte::Udp { direccion: address.clone(), socket: None },
+            AjustesDestino::InfluxHttp { url, token } => Transporte::Http { url: Url::new(url)?, token: token.clone() },
+            AjustesDestino::Graphite { address, prefix } => Transporte::Graphite {
+                direccion: address.clone(),
+                prefijo: prefix.clone().unwrap_or_else(|| PREFIJO_GRAPHITE.to_string()),
+                conexion: None,
+            },
+            AjustesDestino::DefenseSystem { address } => Transporte::Defensa { direccion: address.clone(), socket: None },
+        })
+    }
+
+    //Las líneas de la muestra en el formato del destino, sin el último salto de línea
+    fn formatear(&self, datos: &Datosuwu) -> String {
+        match self {
+            Transporte::Graphite { prefijo, .. } => puntos::graphite(datos, prefijo),
+            Transporte::Defensa { .. } => serde_json::to_string(&Metrics::from(datos)).expect("Metrics siempre se serializa"),
+            _ => puntos::influx(datos),
+        }
+    }
+
+    //Después de un error se vuelve a conectar en el siguiente intento
+    fn reiniciar(&mut self) {
+        match self {
+            Transporte::Udp { socket, .. } | Transporte::Defensa { socket, .. } => *socket = None,
+            Transporte::Graphite { conexion, .. } => *conexion = None,
+            _ => {}
+        }
+    }
+
+    async fn enviar(&mut self, lote: &str) -> Result<(), Fallo> {
+        let reintentar = |e: std::io::Error| Fallo::Reintentar(e.to_string());
+        match self {
+            Transporte::Archivo(escritor) => {
+                if let Some(aviso) = escritor.escribir_linea(lote).map_err(reintentar)? {
+                    eprintln!("{}", aviso);
+                }
+            }
+            Transporte::Udp { direccion, socket } => {
+                if socket.is_none() {
+                    *socket = Some(conectar_udp(direccion).await.map_err(reintentar)?);
+                }
+                let socket = socket.as_ref().expect("se acaba de conectar");
+                for datagrama in datagramas(lote) {
+                    socket.send(datagrama.as_bytes()).await.map_err(reintentar)?;
+                }
+            }
+            Transporte::Http { url, token } => enviar_http(url, token.as_deref(), lote).await?,
+            Transporte::Graphite { direccion, conexion, .. } => {
+                if conexion.is_none() {
+                    *conexion = Some(TcpStream::connect(direccion.as_str()).await.map_err(reintentar)?);
+                }
+                let conexion = conexion.as_mut().expect("se acaba de conectar");
+                conexion.write_all(format!("{}\n", lote).as_bytes()).await.map_err(reintentar)?;
+            }
+            Transporte::Defensa { direccion, socket } => {
+                if socket.is_none() {
+                    *socket = Some(conectar_udp(direccion).await.map_err(reintentar)?);
+                }
+                let socket = socket.as_ref().expect("se acaba de conectar");
+                socket.send(lote.as_bytes()).await.map_err(reintentar)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+//El socket local se abre en la misma familia (IPv4 o IPv6) que la dirección de destino
+async fn conectar_udp(direccion: &str) -> std::io::Result<UdpSocket> {
+    let destino = tokio::net::lookup_host(direccion)
+        .await?
+        .next()
+        .ok_or_else(|| std::io::Error::other(format!("{} no tiene direcciones", direccion)))?;
+    let local: SocketAddr = if destino.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" }.parse().expect("dirección fija");
+    let socket = UdpSocket::bind(local).await?;
+    socket.connect(destino).await?;
+    Ok(socket)
+}
+
+//Agrupa líneas completas hasta MAX_DATAGRAMA; una línea más larga va sola
+fn datagramas(lote: &str) -> Vec<String> {
+    let mut datagramas: Vec<String> = Vec::new();
+    for linea in lote.lines() {
+        match datagramas.last_mut() {
+            Some(actual) if actual.len() + 1 + linea.len() <= MAX_DATAGRAMA => {
+                actual.push('\n');
+                actual.push_str(linea);
+            }
+            _ => datagramas.push(linea.to_string()),
+        }
+    }
+    datagramas
+}
+
+//POST del lote y se espera la respuesta completa (Connection: close). InfluxDB responde 204 si todo se escribió;
+//un 4xx (salvo 408 y 429) es que rechazó los datos, y reintentar no lo va a arreglar
+async fn enviar_http(url: &Url, token: Option<&str>, lote: &str) -> Result<(), Fallo> {
+    let reintentar = |e: std::io::Error| Fallo::Reintentar(e.to_string());
+    let mut conexion = TcpStream::connect(url.direccion.as_str()).await.map_err(reintentar)?;
+    let mut solicitud = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n",
+        url.ruta,
+        url.host,
+        lote.len()
+    );
+    if let Some(token) = token {
+        solicitud.push_str(&format!("Authorization: Token {}\r\n", token));
+    }
+    solicitud.push_str("\r\n");
+    solicitud.push_str(lote);
+    conexion.write_all(solicitud.as_bytes()).await.map_err(reintentar)?;
+    let mut respuesta = Vec::new();
+    conexion.read_to_end(&mut respuesta).await.map_err(reintentar)?;
+    let respuesta = String::from_utf8_lossy(&respuesta);
+    //"HTTP/1.1 204 No Content"
+    let estado: u16 = respuesta
+        .lines()
+        .next()
+        .and_then(|linea| linea.split(' ').nth(1)?.parse().ok())
+        .ok_or_else(|| Fallo::Reintentar("respuesta HTTP inválida".to_string()))?;
+    let cuerpo = respuesta.split_once("\r\n\r\n").map(|(_, cuerpo)| cuerpo.trim()).unwrap_or_default();
+    match estado {
+        200..=299 => Ok(()),
+        400..=499 if estado != 408 && estado != 429 => Err(Fallo::Descartar(format!("HTTP {}: {}", estado, cuerpo))),
+        _ => Err(Fallo::Reintentar(format!("HTTP {}: {}", estado, cuerpo))),
+    }
+}
+
+fn encolar(pendientes: &mut VecDeque<String>, lote: String, nombre: &str) {
+    if pendientes.len() == MAX_PENDIENTES {
+        pendientes.pop_front();
+        eprintln!("{}: la cola está llena; se descarta la muestra más vieja", nombre);
+    }
+    pendientes.push_back(lote);
+}
+
+//Cada reintento espera el doble que el anterior, hasta ESPERA_MAXIMA
+fn siguiente_espera(espera: Duration) -> Duration {
+    (espera * 2).min(ESPERA_MAXIMA)
+}
+
+//Manda las muestras en orden; la que falla se queda al frente de la cola hasta que salga
+async fn correr(mut transporte: Transporte, nombre: String, mut recibidas: mpsc::Receiver<Arc<Datosuwu>>) {
+    let mut pendientes: VecDeque<String> = VecDeque::new();
+    let mut espera = ESPERA_INICIAL;
+    let mut fallando = false;
+    //Falso cuando el ciclo principal ya terminó; entonces se hace un último intento y se sale
+    let mut abierta = true;
+    loop {
+        if pendientes.is_empty() {
+            match recibidas.recv().await {
+                Some(datos) => encolar(&mut pendientes, transporte.formatear(&datos), &nombre),
+                None => return,
+            }
+        }
+        while let Ok(datos) = recibidas.try_recv() {
+            encolar(&mut pendientes, transporte.formatear(&datos), &nombre);
+        }
+        let lote = pendientes.front().expect("la cola no está vacía");
+        let resultado = tokio::time::timeout(LIMITE_ENVIO, transporte.enviar(lote))
+            .await
+            .unwrap_or_else(|_| Err(Fallo::Reintentar(format!("no respondió en {:?}", LIMITE_ENVIO))));
+        match resultado {
+            Ok(()) => {
+                pendientes.pop_front();
+                if fallando {
+                    eprintln!("{}: se reanudó el envío", nombre);
+                    fallando = false;
+                }
+                espera = ESPERA_INICIAL;
+            }
+            Err(Fallo::Descartar(e)) => {
+                pendientes.pop_front();
+                eprintln!("{}: se descarta una muestra: {}", nombre, e);
+            }
+            Err(Fallo::Reintentar(e)) => {
+                transporte.reiniciar();
+                if !abierta {
+                    eprintln!("{}: {}; se pierden {} muestras sin enviar", nombre, e, pendientes.len());
+                    return;
+                }
+                //Solo se avisa al empezar a fallar, no en cada reintento
+                if !fallando {
+                    eprintln!("{}: {}; se reintentará", nombre, e);
+                    fallando = true;
+                }
+                //Mientras se espera se siguen recibiendo muestras
+                let hasta = Instant::now() + espera;
+                espera = siguiente_espera(espera);
+                loop {
+                    tokio::select! {
+                        _ = tokio::time::sleep_until(hasta) => break,
+                        datos = recibidas.recv() => match datos {
+                            Some(datos) => encolar(&mut pendientes, transporte.formatear(&datos), &nombre),
+                            None => {
+                                abierta = false;
+                                break;
+                            }
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use telemetria::datos::ErrorColector;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+    use tokio::net::TcpListener;
+
+    fn muestra(secuencia: u64) -> Datosuwu {
+        let mut datos = Datosuwu::vacia("2024-05-01T12:00:00.000Z".to_string(), 10.0);
+        datos.sequence = Some(secuencia);
+        datos.host_label = Some("vm".to_string());
+        datos
+    }
+
+    #[test]
+    fn urls_de_influx_http() {
+        let url = Url::new("http://influx:8086/api/v2/write?bucket=b").unwrap();
+        assert_eq!((url.direccion.as_str(), url.host.as_str()), ("influx:8086", "influx:8086"));
+        assert_eq!(url.ruta, "/api/v2/write?bucket=b");
+        let url = Url::new("http://influx").unwrap();
+        assert_eq!((url.direccion.as_str(), url.host.as_str(), url.ruta.as_str()), ("influx:80", "influx", "/"));
+        assert_eq!(Url::new("http://[::1]:8086/write").unwrap().direccion, "[::1]:8086");
+        assert_eq!(Url::new("http://[::1]/write").unwrap().direccion, "[::1]:80");
+        assert!(Url::new("https://influx:8086/write").is_err());
+        assert!(Url::new("http:///write").is_err());
+    }
+
+    #[test]
+    fn datagramas_de_hasta_1400_bytes() {
+        //14 líneas de 99 bytes con sus saltos de línea son 1399; la siguiente ya no cabe
+        let linea = "x".repeat(99);
+        let lote = vec![linea.as_str(); 20].join("\n");
+        let partes = datagramas(&lote);
+        assert_eq!(partes.iter().map(|d| d.lines().count()).collect::<Vec<_>>(), [14, 6]);
+        assert_eq!(partes[0].len(), 1399);
+        assert_eq!(partes.join("\n"), lote);
+        //Justo 1400 cabe
+        let justo = format!("{}\n{}", "a".repeat(699), "b".repeat(700));
+        assert_eq!(datagramas(&justo), [justo.as_str()]);
+        //Una línea más larga que el límite va sola
+        let larga = "y".repeat(2000);
+        assert_eq!(datagramas(&format!("a\n{}\nb", larga)), ["a", larga.as_str(), "b"]);
+    }
+
+    #[test]
+    fn la_espera_se_duplica_hasta_un_minuto() {
+        let mut espera = ESPERA_INICIAL;
+        let mut esperas = Vec::new();
+        for _ in 0..9 {
+            esperas.push(espera.as_secs());
+            espera = siguiente_espera(espera);
+        }
+        assert_eq!(esperas, [1, 2, 4, 8, 16, 32, 60, 60, 60]);
+    }
+
+    #[test]
+    fn la_cola_llena_descarta_lo_mas_viejo() {
+        let mut pendientes = VecDeque::new();
+        for i in 0..MAX_PENDIENTES + 5 {
+            encolar(&mut pendientes, i.to_string(), "prueba");
+        }
+        assert_eq!(pendientes.len(), MAX_PENDIENTES);
+        assert_eq!(pendientes.front().unwrap(), "5");
+        assert_eq!(pendientes.back().unwrap(), &(MAX_PENDIENTES + 4).to_string());
+    }
+
+    #[tokio::test]
+    async fn influx_udp_parte_la_muestra_en_datagramas() {
+        let escucha = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let ajustes = [AjustesDestino::InfluxUdp { address: escucha.local_addr().unwrap().to_string() }];
+        let destinos = Destinos::iniciar(&ajustes, &PoliticaRotacion::default()).unwrap();
+        //Unas 30 líneas de más de 100 bytes no caben en un datagrama
+        let mut datos = muestra(1);
+        for i in 0..30 {
+            datos.errors.push(ErrorColector { collector: format!("colector{}", i), message: "x".repeat(80) });
+        }
+        destinos.enviar(&datos);
+        destinos.cerrar().await;
+        let esperado = puntos::influx(&datos);
+        let mut recibidos = Vec::new();
+        let mut buffer = [0u8; 2048];
+        while recibidos.join("\n").len() < esperado.len() {
+            let n = tokio::time::timeout(Duration::from_secs(5), escucha.recv(&mut buffer)).await.unwrap().unwrap();
+            assert!(n <= MAX_DATAGRAMA);
+            recibidos.push(String::from_utf8(buffer[..n].to_vec()).unwrap());
+        }
+        assert!(recibidos.len() > 1);
+        assert_eq!(recibidos.join("\n"), esperado);
+    }
+
+    //Lee una solicitud completa (encabezados y cuerpo según Content-Length), responde con `estado` y cierra
+    async fn atender_http(escucha: &TcpListener, estado: &str) -> (String, String) {
+        let (conexion, _) = tokio::time::timeout(Duration::from_secs(5), escucha.accept()).await.unwrap().unwrap();
+        let mut lector = BufReader::new(conexion);
+        let mut encabezados = String::new();
+        loop {
+            let mut linea = String::new();
+            lector.read_line(&mut linea).await.unwrap();
+            if linea == "\r\n" {
+                break;
+            }
+            encabezados.push_str(&linea);
+        }
+        let largo: usize = encabezados.lines().find_map(|l| l.strip_prefix("Content-Length: ")).unwrap().parse().unwrap();
+        let mut cuerpo = vec![0; largo];
+        lector.read_exact(&mut cuerpo).await.unwrap();
+        let mut conexion = lector.into_inner();
+        let respuesta = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", estado);
+        conexion.write_all(respuesta.as_bytes()).await.unwrap();
+        conexion.shutdown().await.unwrap();
+        (encabezados, String::from_utf8(cuerpo).unwrap())
+    }
+
+    #[tokio::test]
+    async fn influx_http_reintenta_los_errores_del_servidor_y_descarta_lo_rechazado() {
+        let escucha = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/api/v2/write?bucket=b", escucha.local_addr().unwrap());
+        let ajustes = [AjustesDestino::InfluxHttp { url, token: Some("secreto".to_string()) }];
+        let destinos = Destinos::iniciar(&ajustes, &PoliticaRotacion::default()).unwrap();
+        destinos.enviar(&muestra(1));
+        let (encabezados, primero) = atender_http(&escucha, "503 Service Unavailable").await;
+        assert!(encabezados.starts_with("POST /api/v2/write?bucket=b HTTP/1.1\r\n"));
+        assert!(encabezados.contains("Authorization: Token secreto\r\n"));
+        assert_eq!(primero, puntos::influx(&muestra(1)));
+        //Un 503 se reintenta con la misma muestra
+        let (_, reintento) = atender_http(&escucha, "400 Bad Request").await;
+        assert_eq!(reintento, primero);
+        //Un 400 no: lo siguiente que llega ya es la muestra siguiente
+        destinos.enviar(&muestra(2));
+        let (_, siguiente) = atender_http(&escucha, "204 No Content").await;
+        assert_eq!(siguiente, puntos::influx(&muestra(2)));
+        destinos.cerrar().await;
+    }
+
+    //Con Graphite caído las muestras esperan en la cola, sin pasar de MAX_PENDIENTES, y salen en orden al reconectar
+    #[tokio::test]
+    async fn graphite_reintenta_y_la_cola_no_pasa_del_limite() {
+        //Un puerto en el que nadie escucha hasta más abajo
+        let direccion = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        let transporte =
+            Transporte::Graphite { direccion: direccion.to_string(), prefijo: "act4".to_string(), conexion: None };
+        let (cola, recibidas) = mpsc::channel(CAPACIDAD_CANAL);
+        let tarea = tokio::spawn(correr(transporte, "graphite".to_string(), recibidas));
+        for i in 0..MAX_PENDIENTES + 5 {
+            cola.send(Arc::new(muestra(i as u64))).await.unwrap();
+        }
+        let escucha = TcpListener::bind(direccion).await.unwrap();
+        //Al cerrar la cola se hace el último intento enseguida
+        drop(cola);
+        let (conexion, _) = tokio::time::timeout(Duration::from_secs(5), escucha.accept()).await.unwrap().unwrap();
+        let mut lineas = BufReader::new(conexion).lines();
+        let mut secuencias = Vec::new();
+        while let Some(linea) = lineas.next_line().await.unwrap() {
+            if let Some(resto) = linea.strip_prefix("act4.vm.sample.sequence ") {
+                assert!(resto.ends_with(" 1714564800"));
+                secuencias.push(resto.split(' ').next().unwrap().parse::<usize>().unwrap());
+            }
+        }
+        tarea.await.unwrap();
+        assert_eq!(secuencias, (5..MAX_PENDIENTES + 5).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,264 @@
+//Endpoint /metrics para que Prometheus lea el daemon directamente. Es un servidor HTTP/1.1 mínimo: solo
+//GET y HEAD de /metrics, una solicitud por conexión, con la exposición de la última muestra ya armada
+use std::net::SocketAddr;
+use std::time::Duration;
+use telemetria::{prometheus, Datosuwu};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+//Una solicitud de Prometheus (o curl) cabe de sobra; lo que no termine sus encabezados antes se rechaza
+const MAX_SOLICITUD: usize = 8192;
+const LIMITE_SOLICITUD: Duration = Duration::from_secs(5);
+
+pub struct Exportador {
+    //Muestra con la última sección que dio cada colector
+    ultima: Option<Datosuwu>,
+    exposicion: watch::Sender<Option<String>>,
+}
+
+impl Exportador {
+    //Abre el puerto y empieza a atender; hasta la primera muestra /metrics responde 503
+    pub async fn iniciar(direccion: SocketAddr) -> Result<(Exportador, JoinHandle<()>), String> {
+        let escucha = TcpListener::bind(direccion)
+            .await
+            .map_err(|e| format!("No se pudo abrir {} para /metrics: {}", direccion, e))?;
+        let (exposicion, recibida) = watch::channel(None);
+        let servidor = tokio::spawn(servir(escucha, recibida));
+        Ok((Exportador { ultima: None, exposicion }, servidor))
+    }
+
+    //Los colectores con un intervalo propio no llenan su sección en cada muestra; mientras tanto se sigue
+    //exportando la anterior, para que sus series no desaparezcan en Prometheus entre una y otra. La de un
+    //colector que sí se ejecutó (o que reportó un error) no se repite: si falló, su sección deja de exportarse
+    //en vez de quedar con valores viejos como si fueran nuevos
+    pub fn publicar(&mut self, datos: &Datosuwu, ejecutados: &[&str]) {
+        let mut actual = datos.clone();
+        if let Some(anterior) = self.ultima.take() {
+            let repetir =
+                |colector: &str| !ejecutados.contains(&colector) && !datos.errors.iter().any(|e| e.collector == colector);
+            if repetir("cpu") {
+                actual.cpu = anterior.cpu;
+            }
+            if repetir("load") {
+                actual.load = anterior.load;
+            }
+            if repetir("memory") {
+                actual.memory = anterior.memory;
+            }
+            if repetir("network") {
+                actual.network = anterior.network;
+            }
+            if repetir("sockets") {
+                actual.sockets = anterior.sockets;
+            }
+            if repetir("disk") {
+                actual.disk = anterior.disk;
+            }
+            if repetir("diskstats") {
+                actual.disk_stats = anterior.disk_stats;
+            }
+            if repetir("filesystems") {
+                actual.filesystems = anterior.filesystems;
+            }
+            if repetir("temperatures") {
+                actual.temperatures = anterior.temperatures;
+            }
+            //Los tops y per_user los llena el mismo colector
+            if repetir("processes") {
+                actual.top_processes = anterior.top_processes;
+                actual.top_processes_by_memory = anterior.top_processes_by_memory;
+                actual.top_processes_by_disk_io = anterior.top_processes_by_disk_io;
+                actual.per_user = anterior.per_user;
+            }
+            if repetir("cgroups") {
+                actual.cgroups = anterior.cgroups;
+            }
+            if repetir("self_overhead") {
+                actual.self_overhead = anterior.self_overhead;
+            }
+        }
+        self.exposicion.send_replace(Some(prometheus::exposicion(&actual)));
+        self.ultima = Some(actual);
+    }
+}
+
+async fn servir(escucha: TcpListener, exposicion: watch::Receiver<Option<String>>) {
+    loop {
+        match escucha.accept().await {
+            Ok((conexion, _)) => {
+                tokio::spawn(atender(conexion, exposicion.clone()));
+            }
+            //Por ejemplo sin descriptores libres; se espera un poco para no dar vueltas en el error
+            Err(e) => {
+                eprintln!("/metrics: no se pudo aceptar la conexión: {}", e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        }
+    }
+}
+
+async fn atender(mut conexion: TcpStream, exposicion: watch::Receiver<Option<String>>) {
+    let respuesta = match tokio::time::timeout(LIMITE_SOLICITUD, leer_encabezados(&mut conexion)).await {
+        Ok(Some(solicitud)) => responder(&solicitud, &exposicion),
+        Ok(None) => respuesta("400 Bad Request", "text/plain; charset=utf-8", "solicitud inválida\n", true),
+        //Quien no manda nada en 5 s no está esperando respuesta
+        Err(_) => return,
+    };
+    let _ = conexion.write_all(&respuesta).await;
+    let _ = conexion.shutdown().await;
+}
+
+//Lee hasta la línea vacía que cierra los encabezados; None si la conexión se cierra antes o son demasiado largos
+async fn leer_encabezados(conexion: &mut TcpStream) -> Option<String> {
+    let mut leido = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !leido.windows(4).any(|w| w == b"\r\n\r\n") {
+        let n = conexion.read(&mut buffer).await.ok()?;
+        if n == 0 || leido.len() + n > MAX_SOLICITUD {
+            return None;
+        }
+        leido.extend_from_slice(&buffer[..n]);
+    }
+    Some(String::from_utf8_lossy(&leido).into_owned())
+}
+
+//"GET /metrics HTTP/1.1"; los parámetros (?...) se ignoran
+fn responder(solicitud: &str, exposicion: &watch::Receiver<Option<String>>) -> Vec<u8> {
+    let mut partes = solicitud.lines().next().unwrap_or_default().split(' ');
+    let (metodo, ruta) = (partes.next().unwrap_or_default(), partes.next().unwrap_or_default());
+    let con_cuerpo = metodo != "HEAD";
+    if ruta.split('?').next() != Some("/metrics") {
+        return respuesta("404 Not Found", "text/plain; charset=utf-8", "solo se sirve /metrics\n", con_cuerpo);
+    }
+    if metodo != "GET" && metodo != "HEAD" {
+        return respuesta("405 Method Not Allowed", "text/plain; charset=utf-8", "solo GET y HEAD\n", true);
+    }
+    match exposicion.borrow().as_deref() {
+        Some(texto) => respuesta("200 OK", prometheus::CONTENT_TYPE, texto, con_cuerpo),
+        None => respuesta("503 Service Unavailable", "text/plain; charset=utf-8", "todavía no hay muestras\n", con_cuerpo),
+    }
+}
+
+fn respuesta(estado: &str, tipo: &str, cuerpo: &str, con_cuerpo: bool) -> Vec<u8> {
+    let mut respuesta = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        estado,
+        tipo,
+        cuerpo.len()
+    );
+    if estado.starts_with("405") {
+        respuesta.push_str("Allow: GET, HEAD\r\n");
+    }
+    respuesta.push_str("\r\n");
+    let mut respuesta = respuesta.into_bytes();
+    if con_cuerpo {
+        respuesta.extend_from_slice(cuerpo.as_bytes());
+    }
+    respuesta
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use telemetria::datos::{Carga, ErrorColector};
+
+    fn carga() -> Carga {
+        Carga {
+            load_1: 0.5,
+            load_5: 0.25,
+            load_15: 0.1,
+            context_switches: 1000,
+            interrupts: 500,
+            context_switches_per_sec: None,
+            interrupts_per_sec: None,
+            procs_running: 1,
+            procs_blocked: 0,
+            pressure: None,
+        }
+    }
+
+    fn error(colector: &str) -> ErrorColector {
+        ErrorColector { collector: colector.to_string(), message: "no respondió en 5s".to_string() }
+    }
+
+    #[tokio::test]
+    async fn solo_se_repiten_las_secciones_de_colectores_que_no_tocaban() {
+        let (mut exportador, servidor) = Exportador::iniciar("127.0.0.1:0".parse().unwrap()).await.unwrap();
+        let mut primera = Datosuwu::vacia("2024-05-01T12:00:00.000Z".to_string(), 0.0);
+        primera.cpu = Some(
+            serde_json::from_str(r#"{"total_usage_percent":12.5,"frequency_mhz":2400,"cores":[]}"#).unwrap(),
+        );
+        primera.load = Some(carga());
+        exportador.publicar(&primera, &["cpu", "load"]);
+
+        //A cpu le tocaba y no respondió; a load no le tocaba
+        let mut segunda = Datosuwu::vacia("2024-05-01T12:00:10.000Z".to_string(), 10.0);
+        segunda.errors.push(error("cpu"));
+        exportador.publicar(&segunda, &["cpu"]);
+        let ultima = exportador.ultima.as_ref().unwrap();
+        assert!(ultima.cpu.is_none());
+        assert_eq!(ultima.load.as_ref().unwrap().load_1, 0.5);
+        let texto = exportador.exposicion.borrow().clone().unwrap();
+        assert!(!texto.contains("cpu_usage"));
+        assert!(texto.contains("load1"));
+
+        //Un error sin que le tocara (por ejemplo de la lectura base) tampoco deja repetir la sección
+        let mut tercera = Datosuwu::vacia("2024-05-01T12:00:20.000Z".to_string(), 10.0);
+        tercera.errors.push(error("load"));
+        exportador.publicar(&tercera, &[]);
+        assert!(exportador.ultima.as_ref().unwrap().load.is_none());
+        servidor.abort();
+    }
+
+    fn texto(respuesta: Vec<u8>) -> String {
+        String::from_utf8(respuesta).unwrap()
+    }
+
+    #[test]
+    fn solo_se_sirve_metrics() {
+        let (_, exposicion) = watch::channel(Some("act4_load1 0.5\n".to_string()));
+        let get = texto(responder("GET / HTTP/1.1\r\nHost: x\r\n\r\n", &exposicion));
+        assert!(get.starts_with("HTTP/1.1 404 Not Found\r\n"));
+        assert!(get.ends_with("\r\n\r\nsolo se sirve /metrics\n"));
+        let head = texto(responder("HEAD /metricas HTTP/1.1\r\n\r\n", &exposicion));
+        assert!(head.starts_with("HTTP/1.1 404 Not Found\r\n"));
+        assert!(head.contains("Content-Length: 23\r\n"));
+        assert!(head.ends_with("\r\n\r\n"));
+    }
+
+    #[test]
+    fn solo_get_y_head() {
+        let (_, exposicion) = watch::channel(Some("act4_load1 0.5\n".to_string()));
+        let post = texto(responder("POST /metrics HTTP/1.1\r\n\r\n", &exposicion));
+        assert!(post.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+        assert!(post.contains("Allow: GET, HEAD\r\n"));
+        assert!(post.ends_with("solo GET y HEAD\n"));
+    }
+
+    #[test]
+    fn sin_muestras_responde_503() {
+        let (_, exposicion) = watch::channel(None);
+        let get = texto(responder("GET /metrics HTTP/1.1\r\n\r\n", &exposicion));
+        assert!(get.starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+        assert!(get.ends_with("todavía no hay muestras\n"));
+    }
+
+    #[test]
+    fn get_y_head_de_la_ultima_exposicion() {
+        let (envio, exposicion) = watch::channel(None);
+        envio.send_replace(Some("act4_load1 0.5\n".to_string()));
+        let get = texto(responder("GET /metrics?debug=1 HTTP/1.1\r\n\r\n", &exposicion));
+        assert_eq!(
+            get,
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: 15\r\nConnection: close\r\n\r\nact4_load1 0.5\n",
+                prometheus::CONTENT_TYPE
+            )
+        );
+        //HEAD lleva el mismo Content-Length pero no el cuerpo
+        let head = texto(responder("HEAD /metrics HTTP/1.1\r\n\r\n", &exposicion));
+        assert_eq!(head, get.strip_suffix("act4_load1 0.5\n").unwrap());
+    }
+}
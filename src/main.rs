@@ -1,114 +1,339 @@
 //PAULINA AMEZCUA GARCÍA 09/04/24 Monitor de sistema personalizado
-use sysinfo::{System, Networks, Components}; 
-//System permite acceder a información general del sistema 
-//Networks permite acceder a la información de las interfaces de red del sistema 
-//Components permite acceder a información de los componentes físicos
-use std::fs::File;
 use std::io::Write; //Para importar el trait Write del módulo std::io (entrada/salida estándar)
-use std::fs::OpenOptions; //Importa la estructura OpenOptions del módulo std::fs (sistema de archivos) 
-use tokio; //Es una runtime (entorno de ejecución) 
-use serde::Serialize; //Serialización y deserialización de datos
-use chrono::Local; //Para manejar fechas y horas.
-use heim::disk; //Para obtener información sobre el hardware y el sistema 
-use futures::stream::StreamExt; //Para trabajar con futuros y flujos 
-use heim::units::information::byte; //Para obtener información sobre el sistema y hardware 
-#[tokio::main] //Se ejecutará dentro del entorno de ejecución de Tokio, que es un runtime 
+use std::path::PathBuf;
+//La biblioteca compartida con monitoreo y defense_system: tipos de la muestra, recolección y lector
+use telemetria::{lector, Datosuwu, EscritorRotativo, Evento, Recolector, VigilanteKernel, VigilanteProcesos, VENTANA_MEDICION};
+use tokio::sync::mpsc;
+
+mod config;
+mod destinos;
+mod exportador;
+use config::{leer_segundos, Ajustes, Config};
+use destinos::Destinos;
+use exportador::Exportador;
+
+//Opciones de la línea de comandos
+struct Opciones {
+    daemon: bool,
+    //Archivo a reescribir en el formato actual por la salida estándar, en lugar de tomar muestras
+    normalizar: Option<PathBuf>,
+    archivo_config: Option<PathBuf>,
+    //Lo que se indique aquí tiene prioridad sobre el archivo y las variables de entorno
+    ajustes: Ajustes,
+}
+
+const USO: &str = "Uso: act4 [--daemon] [--config <archivo.toml>] [--salida <archivo>] [--intervalo <segundos>]
+            [--host <etiqueta>] [--proc <directorio>] [--cgroup <directorio>] [--hwmon <directorio>]
+            [--block <directorio>] [--net <directorio>] [--formato-fecha <strftime>] [--zona <local|utc|-06:00>]
+            [--top <n>] [--top-por <cpu,memory,disk_io>] [--ignorar <proceso>]...
+            [--deshabilitar <colector>]... [--habilitar <colector>]... [--intervalo-colector <colector>=<segundos>]...
+            [--rotar-cada <hourly|daily>] [--rotar-bytes <n>] [--comprimir <none|gzip|zstd>]
+            [--conservar <segmentos>] [--conservar-dias <dias>]
+            [--salida-eventos <archivo>] [--sin-eventos-procesos] [--kmsg <archivo>] [--sin-eventos-kernel]
+            [--metricas <ip:puerto>] [--destino <influx_file|influx_udp|influx_http|graphite|defense_system>=<destino>]...
+     act4 --normalizar <archivo>";
+
+impl Opciones {
+    //Lee los argumentos: `--daemon` activa el modo continuo y `--normalizar <archivo>` convierte
+    //un datosuwu.jsonl de formatos anteriores al actual (con sus segmentos rotados); el resto
+    //sobrescribe la configuración
+    fn desde_args() -> Result<Opciones, String> {
+        let mut opciones = Opciones {
+            daemon: false,
+            normalizar: None,
+            archivo_config: None,
+            ajustes: Ajustes::default(),
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut valor = |nombre: &str| args.next().ok_or(format!("falta el valor de {}", nombre));
+            let ajustes = &mut opciones.ajustes;
+            match arg.as_str() {
+                "--daemon" | "-d" => opciones.daemon = true,
+                "--normalizar" => opciones.normalizar = Some(PathBuf::from(valor(&arg)?)),
+                "--config" | "-c" => opciones.archivo_config = Some(PathBuf::from(valor(&arg)?)),
+                "--salida" | "-o" => ajustes.output = Some(PathBuf::from(valor(&arg)?)),
+                "--intervalo" | "-i" => ajustes.interval_secs = Some(leer_segundos(&valor(&arg)?)?.as_secs()),
+                "--host" => ajustes.host_label = Some(valor(&arg)?),
+                "--proc" => ajustes.proc_root = Some(PathBuf::from(valor(&arg)?)),
+                "--cgroup" => ajustes.cgroup_root = Some(PathBuf::from(valor(&arg)?)),
+                "--hwmon" => ajustes.hwmon_root = Some(PathBuf::from(valor(&arg)?)),
+                "--block" => ajustes.block_root = Some(PathBuf::from(valor(&arg)?)),
+                "--net" => ajustes.net_root = Some(PathBuf::from(valor(&arg)?)),
+                "--formato-fecha" => ajustes.timestamp_format = Some(valor(&arg)?),
+                "--zona" => ajustes.timezone = Some(valor(&arg)?),
+                "--top" => ajustes.top_processes = Some(numero(&arg, valor(&arg)?)? as usize),
+                "--top-por" => ajustes.top_processes_by = Some(valor(&arg)?.split(',').map(|o| o.trim().to_string()).collect()),
+                "--ignorar" => ajustes.ignored_processes.get_or_insert_with(Vec::new).push(valor(&arg)?),
+                "--deshabilitar" => {
+                    ajustes.collectors.entry(valor(&arg)?).or_default().enabled = Some(false);
+                }
+                "--habilitar" => {
+                    ajustes.collectors.entry(valor(&arg)?).or_default().enabled = Some(true);
+                }
+                "--intervalo-colector" => {
+                    let texto = valor(&arg)?;
+                    let (nombre, segundos) = texto
+                        .split_once('=')
+                        .ok_or(format!("se esperaba <colector>=<segundos>: {}", texto))?;
+                    ajustes.collectors.entry(nombre.to_string()).or_default().interval_secs =
+                        Some(leer_segundos(segundos)?.as_secs());
+                }
+                "--rotar-cada" => ajustes.rotation.period = Some(valor(&arg)?.parse()?),
+                "--rotar-bytes" => ajustes.rotation.max_bytes = Some(numero(&arg, valor(&arg)?)?),
+                "--comprimir" => ajustes.rotation.compression = Some(valor(&arg)?.parse()?),
+                "--conservar" => ajustes.rotation.max_files = Some(numero(&arg, valor(&arg)?)? as usize),
+                "--conservar-dias" => ajustes.rotation.max_age_days = Some(numero(&arg, valor(&arg)?)?),
+                "--salida-eventos" => ajustes.events_output = Some(PathBuf::from(valor(&arg)?)),
+                "--sin-eventos-procesos" => ajustes.process_events.enabled = Some(false),
+                "--kmsg" => {
+                    ajustes.kernel_events.source = Some(PathBuf::from(valor(&arg)?));
+                    ajustes.kernel_events.enabled = Some(true);
+                }
+                "--sin-eventos-kernel" => ajustes.kernel_events.enabled = Some(false),
+                "--metricas" => ajustes.metrics_listen = Some(valor(&arg)?),
+                "--destino" => ajustes.sinks.get_or_insert_with(Vec::new).push(valor(&arg)?.parse()?),
+                otro => return Err(format!("argumento desconocido: {}", otro)),
+            }
+        }
+        Ok(opciones)
+    }
+}
+
+fn numero(nombre: &str, texto: String) -> Result<u64, String> {
+    texto.parse().map_err(|_| format!("{} inválido: {}", nombre, texto))
+}
+
+//Cómo terminó una muestra, de mejor a peor; el peor resultado de la ejecución es el código de salida
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Resultado {
+    Completa,
+    //Se guardó, pero a uno o más colectores les faltó su sección (ver `errors` en la línea)
+    Parcial,
+    //No se pudo escribir en datosuwu.jsonl
+    NoGuardada,
+}
+
+impl Resultado {
+    fn codigo_de_salida(self) -> i32 {
+        match self {
+            Resultado::Completa => 0,
+            Resultado::NoGuardada => 1,
+            Resultado::Parcial => 3,
+        }
+    }
+}
+
+fn guardar(datosuwu: &Datosuwu, salida: &mut EscritorRotativo) -> Resultado {
+    //Se convierte de datosuwu a Json
+    let json_line = match serde_json::to_string(datosuwu) {
+        Ok(json_line) => json_line,
+        Err(e) => {
+            eprintln!("No se pudo convertir la muestra a JSON: {}", e);
+            return Resultado::NoGuardada;
+        }
+    };
+    //Se escribe el Json, rotando antes el archivo si toca
+    match salida.escribir_linea(&json_line) {
+        Ok(Some(aviso)) => eprintln!("{}", aviso),
+        Ok(None) => {}
+        Err(e) => {
+            //La muestra va a la salida estándar para que no se pierda del todo
+            eprintln!("No se pudo escribir en {}: {}", salida.ruta().display(), e);
+            println!("{}", json_line);
+            return Resultado::NoGuardada;
+        }
+    }
+    for error in &datosuwu.errors {
+        eprintln!("colector {}: {}", error.collector, error.message);
+    }
+    if datosuwu.es_parcial() {
+        Resultado::Parcial
+    } else {
+        Resultado::Completa
+    }
+}
+
+fn guardar_evento(evento: &Evento, salida: &mut EscritorRotativo) {
+    let linea = match serde_json::to_string(evento) {
+        Ok(linea) => linea,
+        Err(e) => {
+            eprintln!("No se pudo convertir el evento a JSON: {}", e);
+            return;
+        }
+    };
+    match salida.escribir_linea(&linea) {
+        Ok(Some(aviso)) => eprintln!("{}", aviso),
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("No se pudo escribir en {}: {}", salida.ruta().display(), e);
+            println!("{}", linea);
+        }
+    }
+}
+
+//Escribe los eventos que van llegando hasta que se cierre el canal
+async fn guardar_eventos(mut eventos: mpsc::Receiver<Evento>, mut salida: EscritorRotativo) {
+    while let Some(evento) = eventos.recv().await {
+        guardar_evento(&evento, &mut salida);
+    }
+}
+
+//Espera a SIGINT (Ctrl+C) o, en Unix, a SIGTERM para terminar el modo daemon
+async fn senal_de_salida() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = sigterm.recv() => {}
+            },
+            Err(e) => {
+                eprintln!("No se pudo registrar SIGTERM, solo se atiende Ctrl+C: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+#[tokio::main] //Se ejecutará dentro del entorno de ejecución de Tokio, que es un runtime
 async fn main() {
-    let mut system = System::new_all();
-    system.refresh_all();
-    // Para obtener la información de CPU
-    let cpu_total_usage = system.global_cpu_info().cpu_usage();
-    let cpu_frequency_mhz = system.global_cpu_info().frequency();
-    // Para obtener el uso por núcleo
-    let cpu_cores_usage: Vec<String> = system
-        .cpus()
-        .iter()
-        .enumerate()
-        .map(|(i, cpu)| format!("Core {}: {:.2}%", i, cpu.cpu_usage()))
-        .collect();
-    // Para obtener la información de memoria
-    let used_memory_mb = system.used_memory() / 1024;
-    let total_memory_mb = system.total_memory() / 1024;
-    let used_swap_mb = system.used_swap() / 1024;
-    let total_swap_mb = system.total_swap() / 1024;
-    let free_memory_mb = system.free_memory() / 1024;
-    // Para obtener la información de red
-    let networks = Networks::new_with_refreshed_list();
-    let mut total_received_mb = 0.0;
-    let mut total_transmitted_mb = 0.0;
-    for (_, data) in &networks {
-        total_received_mb += data.total_received() as f64 / (1024.0 * 1024.0);
-        total_transmitted_mb += data.total_transmitted() as f64 / (1024.0 * 1024.0);
-    }
-    // Para obtener la temperatura de componentes
-    let components = Components::new_with_refreshed_list();
-    let component_temperatures: Vec<String> = components
-        .iter()
-        .map(|c| format!("{}: {:.2}°C", c.label(), c.temperature()))
-        .collect();
-    // Para obtener la información de disco de forma asíncrona
-    let mut disk_reads_mb = 0.0;
-    let mut disk_writes_mb = 0.0;
-    let mut disk_stream = disk::io_counters().await.unwrap();
-    while let Some(Ok(disk)) = disk_stream.next().await {
-        disk_reads_mb += disk.read_bytes().get::<byte>() as f64 / (1024.0 * 1024.0);
-        disk_writes_mb += disk.write_bytes().get::<byte>() as f64 / (1024.0 * 1024.0);
-    }
-    // Para obtener los top 5 procesos que más consumen
-    let mut processes: Vec<_> = system.processes().values().collect();
-    processes.sort_by(|a, b| b.cpu_usage().partial_cmp(&a.cpu_usage()).unwrap());
-    let top_cpu_processes: Vec<String> = processes.iter().take(5).map(|procesouvu| {
-        format!(
-            "{}: {:.2}% CPU, {} KB memoria",
-            procesouvu.name(),
-            procesouvu.cpu_usage(),
-            procesouvu.memory()
-        )
-    }).collect();
-    #[derive(Serialize)]
-    //Aquí se define la estructura datosuwu
-    struct Datosuwu {
-        timestamp: String,
-        cpu_total_usage: f32,
-        cpu_frequency_mhz: u64,
-        cpu_cores_usage: Vec<String>,
-        used_memory_mb: u64,
-        total_memory_mb: u64,
-        used_swap_mb: u64,
-        total_swap_mb: u64,
-        free_memory_mb: u64,
-        total_received_mb: f64,
-        total_transmitted_mb: f64,
-        disk_reads_mb: f64,
-        disk_writes_mb: f64,
-        component_temperatures: Vec<String>,
-        top_cpu_processes: Vec<String>,
-    }
-    //Aquí se define la instancia Datosuwu
-    let datosuwu = Datosuwu {
-        timestamp: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
-        cpu_total_usage,
-        cpu_frequency_mhz,
-        cpu_cores_usage,
-        used_memory_mb,
-        total_memory_mb,
-        used_swap_mb,
-        total_swap_mb,
-        free_memory_mb,
-        total_received_mb,
-        total_transmitted_mb,
-        disk_reads_mb,
-        disk_writes_mb,
-        component_temperatures,
-        top_cpu_processes,
+    let opciones = match Opciones::desde_args() {
+        Ok(opciones) => opciones,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("{}", USO);
+            std::process::exit(2);
+        }
     };
-    //Se convierte de datosuwu a Json 
-    let json_line = serde_json::to_string(&datosuwu).unwrap();
-    //Se abre el archivo y se escribe el Json
-    let mut file = OpenOptions::new()
-        .append(true)
-        .create(true)
-        .open("datosuwu.jsonl")
-        .unwrap();
-    writeln!(file, "{}", json_line).unwrap();
-}
\ No newline at end of file
+    if let Some(ruta) = &opciones.normalizar {
+        match lector::leer_serie(ruta) {
+            Ok(registros) => {
+                let mut salida = std::io::stdout().lock();
+                for registro in &registros {
+                    //Si la salida se cierra (por ejemplo con `| head`) se deja de escribir sin pánico
+                    let escrito = serde_json::to_string(registro)
+                        .map_err(std::io::Error::from)
+                        .and_then(|linea| writeln!(salida, "{}", linea));
+                    if escrito.is_err() {
+                        break;
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    let config = match Config::cargar(opciones.archivo_config, opciones.ajustes) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    let mut salida = EscritorRotativo::new(config.salida, config.rotacion.clone());
+    let mut recolector = match Recolector::con_config(config.recolector) {
+        Ok(recolector) => recolector,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    //La numeración de las muestras sigue la del archivo de salida, también entre ejecuciones de una sola muestra
+    if let Some(ultima) = lector::ultima_secuencia(salida.ruta()) {
+        recolector.continuar_secuencia(ultima);
+    }
+    //Los destinos (InfluxDB, Graphite, defense_system) reciben cada muestra después de guardarla en la salida
+    let destinos = match Destinos::iniciar(&config.destinos, &config.rotacion) {
+        Ok(destinos) => destinos,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    //El endpoint /metrics solo se sirve en modo daemon; si no se puede abrir el puerto es un error de configuración
+    let (mut exportador, servidor) = match config.escucha_metricas.filter(|_| opciones.daemon) {
+        Some(direccion) => match Exportador::iniciar(direccion).await {
+            Ok((exportador, servidor)) => (Some(exportador), Some(servidor)),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(2);
+            }
+        },
+        None => (None, None),
+    };
+    //Muestra base + espera, para que la primera muestra ya tenga tasas y % de CPU reales
+    recolector.preparar().await;
+    tokio::time::sleep(VENTANA_MEDICION).await;
+    if !opciones.daemon {
+        //Modo de una sola muestra (el que usa la tarea programada)
+        let datosuwu = recolector.obtener_datos().await;
+        let resultado = guardar(&datosuwu, &mut salida);
+        destinos.enviar(&datosuwu);
+        //Solo los eventos de los colectores (como thermal_warning); los vigilantes corren únicamente en modo daemon
+        let mut salida_eventos = EscritorRotativo::new(config.salida_eventos, config.rotacion);
+        for evento in recolector.tomar_eventos() {
+            guardar_evento(&evento, &mut salida_eventos);
+        }
+        //Un destino caído no cambia el código de salida; la muestra ya quedó en la salida
+        destinos.cerrar().await;
+        std::process::exit(resultado.codigo_de_salida());
+    }
+    //Modo daemon: una muestra por intervalo hasta recibir la señal de salida, y los eventos aparte
+    let (eventos, recibidos) = mpsc::channel(1024);
+    let escritor_eventos = tokio::spawn(guardar_eventos(recibidos, EscritorRotativo::new(config.salida_eventos, config.rotacion)));
+    let mut vigilantes = Vec::new();
+    if let Some(intervalo) = config.eventos_procesos {
+        let vigilante = VigilanteProcesos::new();
+        vigilantes.push(tokio::spawn(vigilante.vigilar(intervalo, eventos.clone())));
+    }
+    //Sin permiso para leer /dev/kmsg (por ejemplo sin ser root con dmesg_restrict) se sigue sin estos eventos
+    if let Some(ruta) = &config.eventos_kernel {
+        match VigilanteKernel::new(ruta) {
+            Ok(vigilante) => vigilantes.push(tokio::spawn(vigilante.vigilar(eventos.clone()))),
+            Err(e) => eprintln!("No se seguirá el log del kernel: {}", e),
+        }
+    }
+    let mut intervalo = tokio::time::interval(config.intervalo);
+    intervalo.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let senal = senal_de_salida();
+    tokio::pin!(senal);
+    let mut peor = Resultado::Completa;
+    loop {
+        tokio::select! {
+            _ = &mut senal => break,
+            _ = intervalo.tick() => {
+                //La muestra termina antes de volver a revisar la señal, así no se escriben líneas a medias
+                let datosuwu = recolector.obtener_datos().await;
+                peor = peor.max(guardar(&datosuwu, &mut salida));
+                destinos.enviar(&datosuwu);
+                if let Some(exportador) = &mut exportador {
+                    exportador.publicar(&datosuwu, recolector.ejecutados());
+                }
+                for evento in recolector.tomar_eventos() {
+                    let _ = eventos.send(evento).await;
+                }
+            }
+        }
+    }
+    if let Some(servidor) = servidor {
+        servidor.abort();
+    }
+    //Se termina de escribir lo que ya estaba en el canal antes de salir
+    for vigilante in vigilantes {
+        vigilante.abort();
+        let _ = vigilante.await;
+    }
+    drop(eventos);
+    let _ = escritor_eventos.await;
+    destinos.cerrar().await;
+    std::process::exit(peor.codigo_de_salida());
+}
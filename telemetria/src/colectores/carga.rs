@@ -0,0 +1,249 @@
+//Carga y presión del sistema leídas de /proc (solo Linux): loadavg, /proc/stat y PSI de /proc/pressure.
+//La raíz es configurable para poder leer un /proc de prueba o el de otro equipo montado
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+use crate::datos::{Carga, Presion, PresionRecurso, PromediosPresion};
+use super::{ColectorBloqueante, Seccion};
+
+pub struct ColectorCarga {
+    raiz: PathBuf,
+    //Última lectura de los contadores de /proc/stat, para las tasas
+    anterior: Option<Stat>,
+}
+
+//Lo que se usa de /proc/stat
+#[derive(Clone, Copy)]
+struct Stat {
+    instante: Instant,
+    cambios_de_contexto: u64,
+    interrupciones: u64,
+    ejecutables: u64,
+    bloqueados: u64,
+}
+
+impl ColectorCarga {
+    pub fn new(raiz: PathBuf) -> ColectorCarga {
+        ColectorCarga { raiz, anterior: None }
+    }
+
+    fn leer(&self, archivo: &str) -> Result<String, String> {
+        let ruta = self.raiz.join(archivo);
+        fs::read_to_string(&ruta).map_err(|e| format!("{}: {}", ruta.display(), e))
+    }
+
+    fn leer_stat(&self) -> Result<Stat, String> {
+        stat_de_texto(&self.leer("stat")?)
+    }
+
+    //Los kernels anteriores a 4.20 (o sin CONFIG_PSI) no tienen /proc/pressure
+    fn leer_presion(&self) -> Option<Presion> {
+        let recurso = |nombre: &str| self.leer(&format!("pressure/{}", nombre)).ok().and_then(|t| presion_de_texto(&t));
+        let presion = Presion { cpu: recurso("cpu"), memory: recurso("memory"), io: recurso("io") };
+        (presion.cpu.is_some() || presion.memory.is_some() || presion.io.is_some()).then_some(presion)
+    }
+}
+
+fn stat_de_texto(texto: &str) -> Result<Stat, String> {
+    let campo = |nombre: &str| -> Result<u64, String> {
+        texto
+            .lines()
+            .find_map(|linea| linea.strip_prefix(nombre)?.strip_prefix(' '))
+            //En "intr" el primer número es el total y los demás son por interrupción
+            .and_then(|valores| valores.split_whitespace().next()?.parse().ok())
+            .ok_or(format!("falta {} en /proc/stat", nombre))
+    };
+    Ok(Stat {
+        instante: Instant::now(),
+        cambios_de_contexto: campo("ctxt")?,
+        interrupciones: campo("intr")?,
+        ejecutables: campo("procs_running")?,
+        bloqueados: campo("procs_blocked")?,
+    })
+}
+
+//"0.61 0.50 0.53 4/72 31526"
+fn loadavg_de_texto(texto: &str) -> Option<(f64, f64, f64)> {
+    let mut campos = texto.split_whitespace().map(|c| c.parse::<f64>());
+    Some((campos.next()?.ok()?, campos.next()?.ok()?, campos.next()?.ok()?))
+}
+
+//"some avg10=2.99 avg60=4.84 avg300=4.92 total=168904100" y, salvo en cpu en kernels viejos, "full ..."
+fn presion_de_texto(texto: &str) -> Option<PresionRecurso> {
+    let linea = |tipo: &str| texto.lines().find_map(|l| l.strip_prefix(tipo)?.strip_prefix(' ')).and_then(promedios_de_texto);
+    Some(PresionRecurso { some: linea("some")?, full: linea("full") })
+}
+
+fn promedios_de_texto(linea: &str) -> Option<PromediosPresion> {
+    let valor = |clave: &str| linea.split_whitespace().find_map(|par| par.strip_prefix(clave)?.strip_prefix('='));
+    Some(PromediosPresion {
+        avg10: valor("avg10")?.parse().ok()?,
+        avg60: valor("avg60")?.parse().ok()?,
+        avg300: valor("avg300")?.parse().ok()?,
+        total_us: valor("total")?.parse().ok()?,
+    })
+}
+
+//Cambios por segundo de un contador de /proc/stat
+fn por_segundo(actual: u64, anterior: u64, segundos: f64) -> Option<f64> {
+    (segundos > 0.0).then(|| actual.saturating_sub(anterior) as f64 / segundos)
+}
+
+impl ColectorBloqueante for ColectorCarga {
+    fn nombre(&self) -> &'static str {
+        "load"
+    }
+
+    fn preparar(&mut self) -> Result<(), String> {
+        self.leer_stat().map(|stat| self.anterior = Some(stat))
+    }
+
+    fn recolectar(&mut self) -> Result<Seccion, String> {
+        let (load_1, load_5, load_15) =
+            loadavg_de_texto(&self.leer("loadavg")?).ok_or("formato de /proc/loadavg desconocido".to_string())?;
+        let stat = self.leer_stat()?;
+        let (context_switches_per_sec, interrupts_per_sec) = match self.anterior.replace(stat) {
+            Some(anterior) => {
+                let segundos = stat.instante.duration_since(anterior.instante).as_secs_f64();
+                (
+                    por_segundo(stat.cambios_de_contexto, anterior.cambios_de_contexto, segundos),
+                    por_segundo(stat.interrupciones, anterior.interrupciones, segundos),
+                )
+            }
+            None => (None, None),
+        };
+        Ok(Seccion::Carga(Box::new(Carga {
+            load_1,
+            load_5,
+            load_15,
+            context_switches: stat.cambios_de_contexto,
+            interrupts: stat.interrupciones,
+            context_switches_per_sec,
+            interrupts_per_sec,
+            procs_running: stat.ejecutables,
+            procs_blocked: stat.bloqueados,
+            pressure: self.leer_presion(),
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prueba::arbol;
+
+    const STAT: &str = "cpu  237260 0 29762 376393 3675 0 24 7810 0 0
+cpu0 237260 0 29762 376393 3675 0 24 7810 0 0
+intr 1016591 0 0 0 0 0 1305 234 0 118 1 96281
+ctxt 1973613
+btime 1760765000
+processes 29974
+procs_running 2
+procs_blocked 1
+softirq 824352 0 215634 2 98003 0 0 1 287616 0 223096
+";
+
+    const PRESION_CPU: &str = "some avg10=10.96 avg60=8.71 avg300=5.85 total=285546912
+full avg10=0.00 avg60=0.00 avg300=0.00 total=0
+";
+
+    //Así es /proc/pressure/cpu antes de 5.13: sin línea full
+    const PRESION_CPU_VIEJA: &str = "some avg10=2.99 avg60=4.84 avg300=4.92 total=168904100\n";
+
+    const PRESION_IO: &str = "some avg10=1.53 avg60=1.40 avg300=2.44 total=49264358
+full avg10=1.03 avg60=1.00 avg300=1.95 total=37309011
+";
+
+    fn carga(raiz: PathBuf) -> Carga {
+        match ColectorCarga::new(raiz).recolectar() {
+            Ok(Seccion::Carga(carga)) => *carga,
+            Ok(_) => panic!("sección equivocada"),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    fn loadavg() {
+        assert_eq!(loadavg_de_texto("0.70 0.58 0.44 2/71 29974\n"), Some((0.70, 0.58, 0.44)));
+        assert_eq!(loadavg_de_texto("0.70 0.58\n"), None);
+        assert_eq!(loadavg_de_texto("a b c 2/71 29974"), None);
+    }
+
+    #[test]
+    fn stat() {
+        let stat = stat_de_texto(STAT).unwrap();
+        assert_eq!(stat.cambios_de_contexto, 1973613);
+        //El total de intr, no la primera interrupción
+        assert_eq!(stat.interrupciones, 1016591);
+        assert_eq!((stat.ejecutables, stat.bloqueados), (2, 1));
+        let sin_ctxt = STAT.replace("ctxt 1973613\n", "");
+        assert_eq!(stat_de_texto(&sin_ctxt).err().unwrap(), "falta ctxt en /proc/stat");
+    }
+
+    #[test]
+    fn presion() {
+        let cpu = presion_de_texto(PRESION_CPU).unwrap();
+        assert_eq!((cpu.some.avg10, cpu.some.avg60, cpu.some.avg300), (10.96, 8.71, 5.85));
+        assert_eq!(cpu.some.total_us, 285546912);
+        assert_eq!(cpu.full.unwrap().total_us, 0);
+        let io = presion_de_texto(PRESION_IO).unwrap();
+        assert_eq!(io.full.unwrap().avg300, 1.95);
+        let vieja = presion_de_texto(PRESION_CPU_VIEJA).unwrap();
+        assert_eq!(vieja.some.avg60, 4.84);
+        assert!(vieja.full.is_none());
+        assert!(presion_de_texto("").is_none());
+        assert!(presion_de_texto("some avg10=1.00 total=5\n").is_none());
+    }
+
+    #[test]
+    fn con_psi() {
+        let raiz = arbol(&[
+            ("loadavg", "0.70 0.58 0.44 2/71 29974\n"),
+            ("stat", STAT),
+            ("pressure/cpu", PRESION_CPU),
+            ("pressure/io", PRESION_IO),
+        ]);
+        let carga = carga(raiz.path().to_path_buf());
+        assert_eq!((carga.load_1, carga.load_5, carga.load_15), (0.70, 0.58, 0.44));
+        assert_eq!(carga.context_switches, 1973613);
+        //Sin lectura anterior no hay tasas
+        assert!(carga.context_switches_per_sec.is_none());
+        let presion = carga.pressure.unwrap();
+        assert!(presion.cpu.is_some() && presion.io.is_some());
+        //Falta pressure/memory: solo ese recurso queda vacío
+        assert!(presion.memory.is_none());
+    }
+
+    //Kernel anterior a 4.20 o sin CONFIG_PSI: no hay /proc/pressure y la carga sale igual, sin presión
+    #[test]
+    fn sin_psi() {
+        let raiz = arbol(&[("loadavg", "1.00 0.50 0.25 1/80 100\n"), ("stat", STAT)]);
+        let carga = carga(raiz.path().to_path_buf());
+        assert_eq!(carga.load_1, 1.0);
+        assert!(carga.pressure.is_none());
+    }
+
+    #[test]
+    fn tasas_entre_lecturas() {
+        let raiz = arbol(&[("loadavg", "0.70 0.58 0.44 2/71 29974\n"), ("stat", STAT)]);
+        let mut colector = ColectorCarga::new(raiz.path().to_path_buf());
+        colector.anterior = Some(Stat {
+            instante: Instant::now() - std::time::Duration::from_secs(2),
+            cambios_de_contexto: 1973613 - 2000,
+            interrupciones: 1016591 - 1000,
+            ejecutables: 0,
+            bloqueados: 0,
+        });
+        let Ok(Seccion::Carga(carga)) = colector.recolectar() else { panic!("sin carga") };
+        let por_segundo = carga.context_switches_per_sec.unwrap();
+        assert!((900.0..=1000.0).contains(&por_segundo), "{}", por_segundo);
+        let interrupciones = carga.interrupts_per_sec.unwrap();
+        assert!((450.0..=500.0).contains(&interrupciones), "{}", interrupciones);
+    }
+
+    #[test]
+    fn sin_loadavg() {
+        let raiz = arbol(&[("stat", STAT)]);
+        assert!(ColectorCarga::new(raiz.path().to_path_buf()).recolectar().err().unwrap().contains("loadavg"));
+    }
+}
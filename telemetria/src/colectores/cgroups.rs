@@ -0,0 +1,285 @@
+//Recorre la jerarquía de cgroup v2 (solo Linux) y reporta CPU, memoria e IO de cada cgroup.
+//La raíz es configurable para poder leer una jerarquía de prueba o la del host desde un contenedor
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use crate::datos::{Cgroup, EventosMemoria, Estrangulamiento};
+use crate::unidades::bytes;
+use super::{ColectorBloqueante, Seccion};
+
+pub struct ColectorCgroups {
+    raiz: PathBuf,
+    //Último usage_usec de cada cgroup, para el % de CPU
+    anteriores: HashMap<String, (Instant, u64)>,
+}
+
+impl ColectorCgroups {
+    pub fn new(raiz: PathBuf) -> ColectorCgroups {
+        //En los sistemas híbridos (v1 y v2 a la vez) la jerarquía v2 está en <raiz>/unified
+        let unificada = raiz.join("unified");
+        let raiz = if !raiz.join("cgroup.controllers").exists() && unificada.join("cgroup.controllers").exists() {
+            unificada
+        } else {
+            raiz
+        };
+        ColectorCgroups { raiz, anteriores: HashMap::new() }
+    }
+
+    //Todos los cgroups bajo la raíz, como rutas relativas que empiezan con "/" (la raíz es "/")
+    fn recorrer(&self) -> Result<Vec<String>, String> {
+        if !self.raiz.join("cgroup.controllers").exists() {
+            return Err(format!("{} no es una jerarquía cgroup v2", self.raiz.display()));
+        }
+        let mut cgroups = Vec::new();
+        let mut pendientes = vec![self.raiz.clone()];
+        while let Some(directorio) = pendientes.pop() {
+            let relativa = directorio.strip_prefix(&self.raiz).unwrap_or(Path::new(""));
+            cgroups.push(format!("/{}", relativa.to_string_lossy()));
+            //Un cgroup puede desaparecer mientras se recorre; se ignora
+            let Ok(entradas) = fs::read_dir(&directorio) else {
+                continue;
+            };
+            for entrada in entradas.flatten() {
+                if entrada.file_type().is_ok_and(|tipo| tipo.is_dir()) {
+                    pendientes.push(entrada.path());
+                }
+            }
+        }
+        cgroups.sort();
+        Ok(cgroups)
+    }
+
+    fn leer(&self, cgroup: &str, archivo: &str) -> Option<String> {
+        fs::read_to_string(self.raiz.join(cgroup.trim_start_matches('/')).join(archivo)).ok()
+    }
+
+    fn cgroup(&self, ruta: &str, instante: Instant) -> Option<(Cgroup, u64)> {
+        //cpu.stat siempre existe en v2; los campos de estrangulamiento solo con el controlador cpu
+        let cpu_stat = self.leer(ruta, "cpu.stat")?;
+        let cpu = campos(&cpu_stat);
+        let uso = *cpu.get("usage_usec")?;
+        let throttling = match (cpu.get("nr_periods"), cpu.get("nr_throttled"), cpu.get("throttled_usec")) {
+            (Some(&nr_periods), Some(&nr_throttled), Some(&throttled_usec)) => {
+                Some(Estrangulamiento { nr_periods, nr_throttled, throttled_usec })
+            }
+            _ => None,
+        };
+        let cpu_usage_percent = self.anteriores.get(ruta).and_then(|&(antes, uso_antes)| {
+            let microsegundos = instante.duration_since(antes).as_micros() as f64;
+            (microsegundos > 0.0).then(|| uso.saturating_sub(uso_antes) as f64 * 100.0 / microsegundos)
+        });
+        //"max" es sin límite
+        let memory_max_bytes = self.leer(ruta, "memory.max").and_then(|t| t.trim().parse().ok()).map(bytes);
+        let memory_events = self.leer(ruta, "memory.events").map(|texto| {
+            let eventos = campos(&texto);
+            let evento = |nombre: &str| eventos.get(nombre).copied().unwrap_or(0);
+            EventosMemoria {
+                low: evento("low"),
+                high: evento("high"),
+                max: evento("max"),
+                oom: evento("oom"),
+                oom_kill: evento("oom_kill"),
+            }
+        });
+        let io = self.leer(ruta, "io.stat").map(|texto| io_de_texto(&texto));
+        let cgroup = Cgroup {
+            path: ruta.to_string(),
+            container_id: id_contenedor(ruta),
+            cpu_usage_usec: uso,
+            cpu_usage_percent,
+            throttling,
+            memory_current_bytes: self.leer(ruta, "memory.current").and_then(|t| t.trim().parse().ok()).map(bytes),
+            memory_max_bytes,
+            memory_events,
+            io_read_bytes: io.map(|(leidos, _)| bytes(leidos)),
+            io_written_bytes: io.map(|(_, escritos)| bytes(escritos)),
+        };
+        Some((cgroup, uso))
+    }
+}
+
+//Líneas "clave valor" como las de cpu.stat y memory.events
+fn campos(texto: &str) -> HashMap<&str, u64> {
+    texto
+        .lines()
+        .filter_map(|linea| {
+            let (clave, valor) = linea.split_once(' ')?;
+            Some((clave, valor.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+//"8:0 rbytes=1024 wbytes=2048 rios=1 wios=2 dbytes=0 dios=0", una línea por dispositivo; se suman
+fn io_de_texto(texto: &str) -> (u64, u64) {
+    let mut leidos = 0;
+    let mut escritos = 0;
+    for par in texto.split_whitespace() {
+        if let Some(valor) = par.strip_prefix("rbytes=") {
+            leidos += valor.parse::<u64>().unwrap_or(0);
+        } else if let Some(valor) = par.strip_prefix("wbytes=") {
+            escritos += valor.parse::<u64>().unwrap_or(0);
+        }
+    }
+    (leidos, escritos)
+}
+
+//El ID de contenedor que usan los runtimes en el nombre del cgroup: "docker-<id>.scope" (systemd),
+//"cri-containerd-<id>.scope", "crio-<id>.scope", "libpod-<id>.scope" o "/docker/<id>" (cgroupfs)
+pub(crate) fn id_contenedor(cgroup: &str) -> Option<String> {
+    cgroup.rsplit('/').find_map(|parte| {
+        let sin_scope = parte.strip_suffix(".scope").unwrap_or(parte);
+        let id = sin_scope.rsplit('-').next()?;
+        (id.len() == 64 && id.chars().all(|c| c.is_ascii_hexdigit())).then(|| id.to_string())
+    })
+}
+
+impl ColectorBloqueante for ColectorCgroups {
+    fn nombre(&self) -> &'static str {
+        "cgroups"
+    }
+
+    fn preparar(&mut self) -> Result<(), String> {
+        self.recorrer().map(|rutas| {
+            let instante = Instant::now();
+            self.anteriores = rutas
+                .into_iter()
+                .filter_map(|ruta| Some((ruta.clone(), (instante, self.cgroup(&ruta, instante)?.1))))
+                .collect();
+        })
+    }
+
+    fn recolectar(&mut self) -> Result<Seccion, String> {
+        self.recorrer().map(|rutas| {
+            let instante = Instant::now();
+            let (cgroups, usos): (Vec<_>, Vec<_>) = rutas
+                .iter()
+                .filter_map(|ruta| self.cgroup(ruta, instante))
+                .map(|(cgroup, uso)| {
+                    let clave = cgroup.path.clone();
+                    (cgroup, (clave, (instante, uso)))
+                })
+                .unzip();
+            //Los cgroups que desaparecieron se olvidan
+            self.anteriores = usos.into_iter().collect();
+            Seccion::Cgroups(cgroups)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::prueba::{arbol, escribir};
+
+    const ID: &str = "3f4e5d6c7b8a99887766554433221100ffeeddccbbaa00112233445566778899";
+
+    #[test]
+    fn id_de_cada_runtime() {
+        let rutas = [
+            //docker y podman con systemd, docker con cgroupfs
+            format!("/system.slice/docker-{}.scope", ID),
+            format!("/machine.slice/libpod-{}.scope", ID),
+            format!("/user.slice/user-1000.slice/user@1000.service/user.slice/libpod-{}.scope", ID),
+            format!("/docker/{}", ID),
+            //containerd y CRI-O en Kubernetes, con systemd y con cgroupfs
+            format!("/kubepods.slice/kubepods-burstable.slice/kubepods-burstable-pod1234.slice/cri-containerd-{}.scope", ID),
+            format!("/kubepods.slice/kubepods-besteffort.slice/crio-{}.scope", ID),
+            format!("/kubepods/burstable/pod1234/{}", ID),
+            //Un cgroup hijo del contenedor sigue siendo del contenedor
+            format!("/system.slice/docker-{}.scope/init", ID),
+        ];
+        for ruta in rutas {
+            assert_eq!(id_contenedor(&ruta).as_deref(), Some(ID), "{}", ruta);
+        }
+        for ruta in [
+            "/",
+            "/system.slice/sshd.service",
+            "/user.slice/user-1000.slice/session-3.scope",
+            "/docker/3f4e5d6c7b8a",
+            &format!("/docker/{}z", &ID[1..]),
+        ] {
+            assert_eq!(id_contenedor(ruta), None, "{}", ruta);
+        }
+    }
+
+    #[test]
+    fn io_de_todos_los_dispositivos() {
+        let texto = "8:0 rbytes=1024 wbytes=2048 rios=1 wios=2 dbytes=0 dios=0\n259:0 rbytes=100 wbytes=0 rios=3 wios=0\n";
+        assert_eq!(io_de_texto(texto), (1124, 2048));
+        //Un dispositivo sin E/S no aparece; un valor ilegible cuenta como 0
+        assert_eq!(io_de_texto(""), (0, 0));
+        assert_eq!(io_de_texto("8:0 rbytes=x wbytes=10"), (0, 10));
+    }
+
+    #[test]
+    fn campos_clave_valor() {
+        let campos = campos("usage_usec 1500\nuser_usec 1000\nsin_valor\nnr_periods x\nthrottled_usec  20\n");
+        assert_eq!(campos.len(), 3);
+        assert_eq!(campos["usage_usec"], 1500);
+        assert_eq!(campos["user_usec"], 1000);
+        assert_eq!(campos["throttled_usec"], 20);
+    }
+
+    fn recolectados(colector: &mut ColectorCgroups) -> Vec<Cgroup> {
+        let Ok(Seccion::Cgroups(cgroups)) = colector.recolectar() else { panic!("sin cgroups") };
+        cgroups
+    }
+
+    #[test]
+    fn recorre_la_jerarquia() {
+        let contenedor = format!("system.slice/docker-{}.scope", ID);
+        let dir = arbol(&[
+            ("cgroup.controllers", "cpu memory io\n"),
+            ("cpu.stat", "usage_usec 5000000\nuser_usec 3000000\nsystem_usec 2000000\n"),
+            (&format!("{}/cpu.stat", contenedor), "usage_usec 1000\nnr_periods 10\nnr_throttled 2\nthrottled_usec 300\n"),
+            (&format!("{}/memory.current", contenedor), "4096\n"),
+            (&format!("{}/memory.max", contenedor), "max\n"),
+            (&format!("{}/memory.events", contenedor), "low 0\nhigh 1\nmax 2\noom 0\noom_kill 0\n"),
+            (&format!("{}/io.stat", contenedor), "8:0 rbytes=1024 wbytes=2048\n"),
+            ("user.slice/cpu.stat", "usage_usec 200\n"),
+            ("user.slice/memory.max", "1048576\n"),
+            //Sin cpu.stat (por ejemplo, si desapareció a la mitad) no se reporta
+            ("vacio.slice/", ""),
+        ]);
+        let mut colector = ColectorCgroups::new(dir.path().to_path_buf());
+        let cgroups = recolectados(&mut colector);
+        let rutas: Vec<&str> = cgroups.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(rutas, ["/", format!("/{}", contenedor).as_str(), "/user.slice"]);
+        assert_eq!(
+            serde_json::to_value(&cgroups[1]).unwrap(),
+            json!({
+                "path": format!("/{}", contenedor),
+                "container_id": ID,
+                "cpu_usage_usec": 1000,
+                "cpu_usage_percent": null,
+                "throttling": {"nr_periods": 10, "nr_throttled": 2, "throttled_usec": 300},
+                "memory_current_bytes": 4096,
+                "memory_events": {"low": 0, "high": 1, "max": 2, "oom": 0, "oom_kill": 0},
+                "io_read_bytes": 1024,
+                "io_written_bytes": 2048,
+            })
+        );
+        assert!(cgroups[0].throttling.is_none());
+        assert_eq!(cgroups[2].memory_max_bytes, Some(bytes(1048576)));
+        assert!(cgroups[2].memory_events.is_none() && cgroups[2].io_read_bytes.is_none());
+
+        //El % de CPU sale de la diferencia con la lectura anterior
+        escribir(dir.path(), &format!("{}/cpu.stat", contenedor), "usage_usec 1000000000\n");
+        let cgroups = recolectados(&mut colector);
+        assert!(cgroups[1].cpu_usage_percent.is_some_and(|uso| uso > 0.0));
+        assert_eq!(cgroups[0].cpu_usage_percent, Some(0.0));
+    }
+
+    #[test]
+    fn jerarquia_hibrida_y_v1() {
+        let hibrida = arbol(&[("cpu/", ""), ("unified/cgroup.controllers", ""), ("unified/cpu.stat", "usage_usec 10\n")]);
+        let mut colector = ColectorCgroups::new(hibrida.path().to_path_buf());
+        assert_eq!(recolectados(&mut colector)[0].cpu_usage_usec, 10);
+
+        let v1 = arbol(&[("cpu/cpuacct.usage", "10\n")]);
+        let error = ColectorCgroups::new(v1.path().to_path_buf()).recolectar().err().unwrap();
+        assert!(error.contains("no es una jerarquía cgroup v2"), "{}", error);
+    }
+}
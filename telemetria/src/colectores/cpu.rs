@@ -0,0 +1,70 @@
+use sysinfo::{CpuRefreshKind, System};
+use crate::datos::{Cpu, Nucleo};
+use crate::unidades::megahercios;
+use super::{ColectorBloqueante, Seccion};
+
+pub struct ColectorCpu {
+    system: System,
+}
+
+impl ColectorCpu {
+    pub fn new() -> ColectorCpu {
+        ColectorCpu { system: System::new() }
+    }
+
+    fn refrescar(&mut self) {
+        self.system.refresh_cpu_specifics(CpuRefreshKind::everything());
+    }
+}
+
+impl Default for ColectorCpu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ColectorBloqueante for ColectorCpu {
+    fn nombre(&self) -> &'static str {
+        "cpu"
+    }
+
+    //El % de uso es la diferencia entre dos refrescos
+    fn preparar(&mut self) -> Result<(), String> {
+        self.refrescar();
+        Ok(())
+    }
+
+    fn recolectar(&mut self) -> Result<Seccion, String> {
+        self.refrescar();
+        let system = &self.system;
+        let cpu = Cpu {
+            total_usage_percent: system.global_cpu_info().cpu_usage(),
+            frequency_mhz: megahercios(system.global_cpu_info().frequency()),
+            cores: system
+                .cpus()
+                .iter()
+                .enumerate()
+                .map(|(i, cpu)| Nucleo { core: i, usage_percent: cpu.cpu_usage() })
+                .collect(),
+        };
+        Ok(Seccion::Cpu(cpu))
+    }
+}
+
+//El CPU sale de sysinfo, no de un texto de /proc; solo se revisa la forma de la sección
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn un_nucleo_por_cpu_y_porcentajes_validos() {
+        let mut colector = ColectorCpu::new();
+        colector.preparar().unwrap();
+        std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+        let Ok(Seccion::Cpu(cpu)) = colector.recolectar() else { panic!("sin cpu") };
+        assert_eq!(cpu.cores.len(), colector.system.cpus().len());
+        assert!(cpu.cores.iter().enumerate().all(|(i, nucleo)| nucleo.core == i));
+        assert!((0.0..=100.0).contains(&cpu.total_usage_percent));
+        assert!(cpu.cores.iter().all(|nucleo| (0.0..=100.0).contains(&nucleo.usage_percent)));
+    }
+}
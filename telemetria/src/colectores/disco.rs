@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use futures::future::BoxFuture;
+use futures::stream::StreamExt;
+use heim::disk;
+use heim::units::information::byte as heim_byte;
+use crate::config::{ConfigRecolector, Filtro};
+use crate::datos::{Disco, Dispositivo};
+use crate::unidades::bytes;
+use super::{Collector, Contadores, Seccion};
+
+pub struct ColectorDisco {
+    //heim lee los contadores del /proc del equipo; las particiones y io_ticks salen de este /sys/class/block,
+    //el mismo que usa diskstats
+    raiz_bloques: PathBuf,
+    filtro: Filtro,
+    //Última lectura del total y de cada dispositivo, para las tasas
+    anterior: Option<Contadores>,
+    anteriores: HashMap<String, Contadores>,
+}
+
+//Contadores de un dispositivo que pasó el filtro
+struct Lectura {
+    nombre: String,
+    contadores: Contadores,
+    operaciones: (u64, u64),
+    ocupado_ms: Option<u64>,
+}
+
+impl ColectorDisco {
+    pub fn new(raiz_bloques: PathBuf, filtro: Filtro) -> ColectorDisco {
+        ColectorDisco { raiz_bloques, filtro, anterior: None, anteriores: HashMap::new() }
+    }
+
+    //Lee de forma asíncrona cada dispositivo que pasa el filtro y suma sus lecturas y escrituras acumuladas
+    async fn leer(&self) -> Result<(Contadores, Vec<Lectura>), String> {
+        let mut lecturas = Vec::new();
+        let mut disk_stream = disk::io_counters().await.map_err(|e| e.to_string())?;
+        while let Some(disk) = disk_stream.next().await {
+            let disk = disk.map_err(|e| e.to_string())?;
+            let nombre = disk.device_name().to_string_lossy().into_owned();
+            if !self.filtro.permite(&nombre) || es_particion(&self.raiz_bloques, &nombre) {
+                continue;
+            }
+            lecturas.push(Lectura {
+                contadores: Contadores {
+                    instante: Instant::now(),
+                    entrada: bytes(disk.read_bytes().get::<heim_byte>()),
+                    salida: bytes(disk.write_bytes().get::<heim_byte>()),
+                },
+                operaciones: (disk.read_count(), disk.write_count()),
+                ocupado_ms: tiempo_ocupado(&self.raiz_bloques, &nombre),
+                nombre,
+            });
+        }
+        lecturas.sort_by(|a, b| a.nombre.cmp(&b.nombre));
+        let total = Contadores {
+            instante: Instant::now(),
+            entrada: lecturas.iter().map(|l| l.contadores.entrada).sum(),
+            salida: lecturas.iter().map(|l| l.contadores.salida).sum(),
+        };
+        Ok((total, lecturas))
+    }
+}
+
+//En Linux los contadores de una partición ya están sumados en los de su disco
+pub(super) fn es_particion(raiz_bloques: &Path, dispositivo: &str) -> bool {
+    raiz_bloques.join(dispositivo).join("partition").exists()
+}
+
+//Campo io_ticks (el décimo) de /sys/class/block/<dispositivo>/stat, en milisegundos
+fn tiempo_ocupado(raiz_bloques: &Path, dispositivo: &str) -> Option<u64> {
+    let stat = std::fs::read_to_string(raiz_bloques.join(dispositivo).join("stat")).ok()?;
+    stat.split_whitespace().nth(9)?.parse().ok()
+}
+
+impl Default for ColectorDisco {
+    fn default() -> Self {
+        let config = ConfigRecolector::default();
+        Self::new(config.raiz_bloques, config.filtro_discos)
+    }
+}
+
+impl Collector for ColectorDisco {
+    fn nombre(&self) -> &'static str {
+        "disk"
+    }
+
+    fn preparar(&mut self) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(async move {
+            let (total, lecturas) = self.leer().await?;
+            self.anterior = Some(total);
+            self.anteriores = lecturas.into_iter().map(|l| (l.nombre, l.contadores)).collect();
+            Ok(())
+        })
+    }
+
+    fn recolectar(&mut self) -> BoxFuture<'_, Result<Seccion, String>> {
+        Box::pin(async move {
+            let (actual, lecturas) = self.leer().await?;
+            let (read_bytes_per_sec, write_bytes_per_sec) = actual.tasas(self.anterior.replace(actual));
+            let mut anteriores = HashMap::new();
+            let devices = lecturas
+                .into_iter()
+                .map(|l| {
+                    let (read_bytes_per_sec, write_bytes_per_sec) = l.contadores.tasas(self.anteriores.get(&l.nombre).copied());
+                    anteriores.insert(l.nombre.clone(), l.contadores);
+                    Dispositivo {
+                        name: l.nombre,
+                        read_bytes: l.contadores.entrada,
+                        written_bytes: l.contadores.salida,
+                        read_bytes_per_sec,
+                        write_bytes_per_sec,
+                        reads: l.operaciones.0,
+                        writes: l.operaciones.1,
+                        busy_time_ms: l.ocupado_ms,
+                    }
+                })
+                .collect();
+            //Los dispositivos que desaparecieron se olvidan
+            self.anteriores = anteriores;
+            Ok(Seccion::Disco(Disco {
+                read_bytes: actual.entrada,
+                written_bytes: actual.salida,
+                read_bytes_per_sec,
+                write_bytes_per_sec,
+                devices,
+            }))
+        })
+    }
+}
@@ -0,0 +1,244 @@
+//IOPS, latencia, cola y % de uso por dispositivo a partir de /proc/diskstats (solo Linux). heim solo da bytes
+//acumulados, y un disco lento se nota en la latencia, no en los bytes. Usa el mismo filtro que el colector disk
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+use crate::config::Filtro;
+use crate::datos::EstadisticasDisco;
+use super::disco::es_particion;
+use super::{ColectorBloqueante, Seccion};
+
+pub struct ColectorDiskstats {
+    raiz: PathBuf,
+    //El /sys/class/block que corresponde a ese /proc
+    raiz_bloques: PathBuf,
+    filtro: Filtro,
+    //Última lectura de cada dispositivo, para las diferencias
+    anteriores: HashMap<String, Lectura>,
+    instante: Option<Instant>,
+}
+
+//Los campos de una línea de /proc/diskstats que se usan (Documentation/admin-guide/iostats.rst)
+#[derive(Clone, Copy)]
+struct Lectura {
+    lecturas: u64,
+    ms_leyendo: u64,
+    escrituras: u64,
+    ms_escribiendo: u64,
+    en_curso: u64,
+    ms_ocupado: u64,
+    //Milisegundos de cada solicitud en curso sumados; su diferencia entre el tiempo transcurrido es la cola promedio
+    ms_ponderados: u64,
+}
+
+impl ColectorDiskstats {
+    pub fn new(raiz: PathBuf, raiz_bloques: PathBuf, filtro: Filtro) -> ColectorDiskstats {
+        ColectorDiskstats { raiz, raiz_bloques, filtro, anteriores: HashMap::new(), instante: None }
+    }
+
+    fn leer(&self) -> Result<Vec<(String, Lectura)>, String> {
+        let ruta = self.raiz.join("diskstats");
+        let texto = fs::read_to_string(&ruta).map_err(|e| format!("{}: {}", ruta.display(), e))?;
+        let mut dispositivos: Vec<_> = diskstats_de_texto(&texto)
+            .into_iter()
+            .filter(|(nombre, _)| self.filtro.permite(nombre) && !es_particion(&self.raiz_bloques, nombre))
+            .collect();
+        dispositivos.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(dispositivos)
+    }
+}
+
+//"   8       0 sda 1234 56 78901 2345 678 90 12345 6789 0 3456 9134 ..."; los kernels anteriores a 4.18
+//tienen 11 campos después del nombre y los nuevos agregan discard y flush, que aquí no se usan
+fn diskstats_de_texto(texto: &str) -> Vec<(String, Lectura)> {
+    texto
+        .lines()
+        .filter_map(|linea| {
+            let campos: Vec<&str> = linea.split_whitespace().collect();
+            let nombre = campos.get(2)?.to_string();
+            let campo = |i: usize| campos.get(i + 2)?.parse::<u64>().ok();
+            Some((
+                nombre,
+                Lectura {
+                    lecturas: campo(1)?,
+                    ms_leyendo: campo(4)?,
+                    escrituras: campo(5)?,
+                    ms_escribiendo: campo(8)?,
+                    en_curso: campo(9)?,
+                    ms_ocupado: campo(10)?,
+                    ms_ponderados: campo(11)?,
+                },
+            ))
+        })
+        .collect()
+}
+
+//Compara dos lecturas del mismo dispositivo separadas por `segundos`
+fn estadisticas(nombre: String, actual: Lectura, anterior: Option<(Lectura, f64)>) -> EstadisticasDisco {
+    let mut estadisticas = EstadisticasDisco {
+        name: nombre,
+        in_flight: actual.en_curso,
+        reads_per_sec: None,
+        writes_per_sec: None,
+        read_latency_ms: None,
+        write_latency_ms: None,
+        avg_queue_depth: None,
+        utilization_percent: None,
+    };
+    let Some((anterior, segundos)) = anterior.filter(|(_, s)| *s > 0.0) else {
+        return estadisticas;
+    };
+    //Los contadores de 32 bits pueden dar la vuelta; saturating_sub evita tasas absurdas en ese intervalo
+    let diferencia = |actual: u64, anterior: u64| actual.saturating_sub(anterior) as f64;
+    let lecturas = diferencia(actual.lecturas, anterior.lecturas);
+    let escrituras = diferencia(actual.escrituras, anterior.escrituras);
+    let latencia = |ms: f64, operaciones: f64| (operaciones > 0.0).then(|| ms / operaciones);
+    let milisegundos = segundos * 1000.0;
+    estadisticas.reads_per_sec = Some(lecturas / segundos);
+    estadisticas.writes_per_sec = Some(escrituras / segundos);
+    estadisticas.read_latency_ms = latencia(diferencia(actual.ms_leyendo, anterior.ms_leyendo), lecturas);
+    estadisticas.write_latency_ms = latencia(diferencia(actual.ms_escribiendo, anterior.ms_escribiendo), escrituras);
+    estadisticas.avg_queue_depth = Some(diferencia(actual.ms_ponderados, anterior.ms_ponderados) / milisegundos);
+    //io_ticks se actualiza por jiffies, así que puede pasarse un poco del tiempo transcurrido
+    estadisticas.utilization_percent =
+        Some((diferencia(actual.ms_ocupado, anterior.ms_ocupado) * 100.0 / milisegundos).min(100.0));
+    estadisticas
+}
+
+impl ColectorBloqueante for ColectorDiskstats {
+    fn nombre(&self) -> &'static str {
+        "diskstats"
+    }
+
+    fn preparar(&mut self) -> Result<(), String> {
+        self.leer().map(|dispositivos| {
+            self.anteriores = dispositivos.into_iter().collect();
+            self.instante = Some(Instant::now());
+        })
+    }
+
+    fn recolectar(&mut self) -> Result<Seccion, String> {
+        self.leer().map(|dispositivos| {
+            let ahora = Instant::now();
+            let segundos = self.instante.replace(ahora).map(|antes| ahora.duration_since(antes).as_secs_f64());
+            let mut anteriores = HashMap::new();
+            let seccion = dispositivos
+                .into_iter()
+                .map(|(nombre, actual)| {
+                    let anterior = self.anteriores.get(&nombre).copied().zip(segundos);
+                    anteriores.insert(nombre.clone(), actual);
+                    estadisticas(nombre, actual, anterior)
+                })
+                .collect();
+            //Los dispositivos que desaparecieron se olvidan
+            self.anteriores = anteriores;
+            Seccion::Diskstats(seccion)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prueba::arbol;
+
+    //Un kernel 5.x (17 campos después del nombre) y un 4.x (11 campos), con una partición y un loop
+    const DISKSTATS: &str = "\
+   7       0 loop0 57 0 2202 12 0 0 0 0 0 36 12 0 0 0 0 0 0
+ 253       0 vda 178043 52301 10823694 61833 1236870 803219 25638528 1439025 0 1047436 1555921 0 0 0 0 102321 55062
+ 253       1 vda1 177843 52301 10815014 61807 1236870 803219 25638528 1439025 0 1047400 1500832 0 0 0 0 0 0
+ 259       0 nvme0n1 4000 0 320000 8000 2000 0 160000 12000 3 9000 20000
+   8       0 sdz 1 2
+";
+
+    //(lecturas, ms leyendo), (escrituras, ms escribiendo), ms ocupado y ms ponderados
+    fn lectura(leidas: (u64, u64), escritas: (u64, u64), ms_ocupado: u64, ms_ponderados: u64) -> Lectura {
+        Lectura {
+            lecturas: leidas.0,
+            ms_leyendo: leidas.1,
+            escrituras: escritas.0,
+            ms_escribiendo: escritas.1,
+            en_curso: 0,
+            ms_ocupado,
+            ms_ponderados,
+        }
+    }
+
+    #[test]
+    fn campos_de_diskstats() {
+        let dispositivos = diskstats_de_texto(DISKSTATS);
+        let nombres: Vec<_> = dispositivos.iter().map(|(nombre, _)| nombre.as_str()).collect();
+        //La línea cortada se ignora
+        assert_eq!(nombres, ["loop0", "vda", "vda1", "nvme0n1"]);
+        let vda = dispositivos[1].1;
+        assert_eq!((vda.lecturas, vda.ms_leyendo), (178043, 61833));
+        assert_eq!((vda.escrituras, vda.ms_escribiendo), (1236870, 1439025));
+        assert_eq!((vda.en_curso, vda.ms_ocupado, vda.ms_ponderados), (0, 1047436, 1555921));
+        //Formato de antes de 4.18, sin discard ni flush
+        let nvme = dispositivos[3].1;
+        assert_eq!((nvme.lecturas, nvme.escrituras, nvme.en_curso), (4000, 2000, 3));
+        assert_eq!((nvme.ms_ocupado, nvme.ms_ponderados), (9000, 20000));
+    }
+
+    #[test]
+    fn tasas_latencia_y_uso() {
+        let anterior = lectura((1000, 5000), (2000, 8000), 10000, 30000);
+        //En 2 s: 200 lecturas que tardaron 400 ms, 100 escrituras de 1000 ms, 1500 ms ocupado y 3000 ms ponderados
+        let mut actual = lectura((1200, 5400), (2100, 9000), 11500, 33000);
+        actual.en_curso = 4;
+        let estadisticas = estadisticas("vda".to_string(), actual, Some((anterior, 2.0)));
+        assert_eq!(estadisticas.name, "vda");
+        assert_eq!(estadisticas.in_flight, 4);
+        assert_eq!(estadisticas.reads_per_sec, Some(100.0));
+        assert_eq!(estadisticas.writes_per_sec, Some(50.0));
+        assert_eq!(estadisticas.read_latency_ms, Some(2.0));
+        assert_eq!(estadisticas.write_latency_ms, Some(10.0));
+        assert_eq!(estadisticas.avg_queue_depth, Some(1.5));
+        assert_eq!(estadisticas.utilization_percent, Some(75.0));
+    }
+
+    #[test]
+    fn sin_operaciones_ni_lectura_anterior() {
+        let quieto = lectura((1000, 5000), (2000, 8000), 10000, 30000);
+        let estadisticas = estadisticas("vda".to_string(), quieto, Some((quieto, 1.0)));
+        assert_eq!((estadisticas.reads_per_sec, estadisticas.writes_per_sec), (Some(0.0), Some(0.0)));
+        //Sin operaciones no hay latencia que promediar
+        assert!(estadisticas.read_latency_ms.is_none() && estadisticas.write_latency_ms.is_none());
+        assert_eq!(estadisticas.utilization_percent, Some(0.0));
+        let primera = super::estadisticas("vda".to_string(), quieto, None);
+        assert!(primera.reads_per_sec.is_none() && primera.utilization_percent.is_none());
+        assert!(primera.avg_queue_depth.is_none());
+        let sin_tiempo = super::estadisticas("vda".to_string(), quieto, Some((quieto, 0.0)));
+        assert!(sin_tiempo.reads_per_sec.is_none());
+    }
+
+    //Un contador de 32 bits que dio la vuelta no da tasas absurdas, y io_ticks no pasa del 100%
+    #[test]
+    fn vuelta_del_contador_y_uso_topado() {
+        let anterior = lectura((u32::MAX as u64 - 10, 100), (50, 100), 10000, 500);
+        let actual = lectura((20, 120), (60, 150), 11100, 900);
+        let estadisticas = estadisticas("sda".to_string(), actual, Some((anterior, 1.0)));
+        assert_eq!(estadisticas.reads_per_sec, Some(0.0));
+        assert!(estadisticas.read_latency_ms.is_none());
+        assert_eq!(estadisticas.writes_per_sec, Some(10.0));
+        assert_eq!(estadisticas.write_latency_ms, Some(5.0));
+        assert_eq!(estadisticas.utilization_percent, Some(100.0));
+    }
+
+    #[test]
+    fn particiones_del_block_configurado() {
+        let raiz = arbol(&[
+            ("proc/diskstats", DISKSTATS),
+            ("sys/class/block/loop0/", ""),
+            ("sys/class/block/vda/", ""),
+            ("sys/class/block/nvme0n1/", ""),
+            ("sys/class/block/vda1/partition", "1\n"),
+        ]);
+        let (proc_prueba, bloques) = (raiz.path().join("proc"), raiz.path().join("sys/class/block"));
+        let mut colector = ColectorDiskstats::new(proc_prueba, bloques, Filtro::excluyendo(&["loop*"]));
+        let Ok(Seccion::Diskstats(dispositivos)) = colector.recolectar() else { panic!("sin diskstats") };
+        let nombres: Vec<_> = dispositivos.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(nombres, ["nvme0n1", "vda"]);
+    }
+}
@@ -0,0 +1,259 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+use sysinfo::System;
+use crate::datos::{DetalleMemoria, Memoria};
+use crate::unidades::{bytes, bytes_por_segundo, kibibytes};
+use super::{ColectorBloqueante, Seccion};
+
+pub struct ColectorMemoria {
+    system: System,
+    //En Linux el desglose se lee de <raiz>/meminfo y <raiz>/vmstat
+    raiz_proc: PathBuf,
+    //Última lectura de pswpin/pswpout, para las tasas de swap
+    anterior: Option<(Instant, u64, u64)>,
+    //Partes de la última muestra que no se pudieron leer
+    errores: Vec<String>,
+}
+
+impl ColectorMemoria {
+    pub fn new(raiz_proc: PathBuf) -> ColectorMemoria {
+        ColectorMemoria { system: System::new(), raiz_proc, anterior: None, errores: Vec::new() }
+    }
+
+    fn leer(&self, archivo: &str) -> Result<String, String> {
+        let ruta = self.raiz_proc.join(archivo);
+        fs::read_to_string(&ruta).map_err(|e| format!("{}: {}", ruta.display(), e))
+    }
+
+    //Páginas que entraron y salieron del swap desde el arranque; sin vmstat no hay tasas
+    fn leer_swap(&self) -> Option<(Instant, u64, u64)> {
+        let vmstat = self.leer("vmstat").ok()?;
+        let campo = |nombre: &str| {
+            vmstat.lines().find_map(|linea| linea.strip_prefix(nombre)?.strip_prefix(' ')?.trim().parse::<u64>().ok())
+        };
+        Some((Instant::now(), campo("pswpin")?, campo("pswpout")?))
+    }
+
+    fn detalle(&mut self, meminfo: &str) -> Result<DetalleMemoria, String> {
+        let campo = |nombre: &str| campo_meminfo(meminfo, nombre);
+        let kib = |nombre: &str| campo(nombre).map(kibibytes).ok_or(format!("falta {} en meminfo", nombre));
+        let swap = self.leer_swap();
+        let (swap_in_bytes_per_sec, swap_out_bytes_per_sec) = match (self.anterior, swap) {
+            (Some((antes, entradas_antes, salidas_antes)), Some((ahora, entradas, salidas))) => {
+                let segundos = ahora.duration_since(antes).as_secs_f64();
+                let tasa = |actual: u64, anterior: u64| {
+                    (segundos > 0.0)
+                        .then(|| bytes_por_segundo((actual.saturating_sub(anterior) * tamano_pagina()) as f64 / segundos))
+                };
+                (tasa(entradas, entradas_antes), tasa(salidas, salidas_antes))
+            }
+            _ => (None, None),
+        };
+        self.anterior = swap;
+        Ok(DetalleMemoria {
+            cached_bytes: kib("Cached")?,
+            buffers_bytes: kib("Buffers")?,
+            shared_bytes: kib("Shmem")?,
+            slab_bytes: kib("Slab")?,
+            dirty_bytes: kib("Dirty")?,
+            writeback_bytes: kib("Writeback")?,
+            //Sin soporte de hugepages en el kernel estos campos no aparecen
+            hugepages_total: campo("HugePages_Total").unwrap_or(0),
+            hugepages_free: campo("HugePages_Free").unwrap_or(0),
+            hugepage_size_bytes: kibibytes(campo("Hugepagesize").unwrap_or(0)),
+            swap_in_bytes_per_sec,
+            swap_out_bytes_per_sec,
+        })
+    }
+
+    //Fuera de Linux no hay meminfo; sysinfo ya la da en bytes
+    fn de_sysinfo(&mut self) -> Memoria {
+        self.system.refresh_memory();
+        let system = &self.system;
+        Memoria {
+            total_bytes: bytes(system.total_memory()),
+            used_bytes: bytes(system.used_memory()),
+            free_bytes: bytes(system.free_memory()),
+            available_bytes: Some(bytes(system.available_memory())),
+            total_swap_bytes: bytes(system.total_swap()),
+            used_swap_bytes: bytes(system.used_swap()),
+            breakdown: None,
+        }
+    }
+}
+
+//"Cached:          5136664 kB"; los HugePages_* no llevan unidad
+fn campo_meminfo(meminfo: &str, nombre: &str) -> Option<u64> {
+    meminfo.lines().find_map(|linea| {
+        let (clave, valor) = linea.split_once(':')?;
+        (clave == nombre).then(|| valor.split_whitespace().next()?.parse().ok())?
+    })
+}
+
+//Los totales, del mismo meminfo que el desglose; "usada" es lo que no está disponible, como en sysinfo
+fn memoria_de_meminfo(meminfo: &str) -> Result<Memoria, String> {
+    let kib = |nombre: &str| campo_meminfo(meminfo, nombre).ok_or(format!("falta {} en meminfo", nombre));
+    let total = kib("MemTotal")?;
+    let libre = kib("MemFree")?;
+    //MemAvailable existe desde el 3.14
+    let disponible = campo_meminfo(meminfo, "MemAvailable");
+    let swap_total = kib("SwapTotal")?;
+    let swap_libre = kib("SwapFree")?;
+    Ok(Memoria {
+        total_bytes: kibibytes(total),
+        used_bytes: kibibytes(total.saturating_sub(disponible.unwrap_or(libre))),
+        free_bytes: kibibytes(libre),
+        available_bytes: disponible.map(kibibytes),
+        total_swap_bytes: kibibytes(swap_total),
+        used_swap_bytes: kibibytes(swap_total.saturating_sub(swap_libre)),
+        breakdown: None,
+    })
+}
+
+//pswpin y pswpout cuentan páginas
+#[cfg(unix)]
+fn tamano_pagina() -> u64 {
+    //SAFETY: sysconf no tiene precondiciones
+    let tamano = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if tamano > 0 { tamano as u64 } else { 4096 }
+}
+
+#[cfg(not(unix))]
+fn tamano_pagina() -> u64 {
+    4096
+}
+
+impl Default for ColectorMemoria {
+    fn default() -> Self {
+        Self::new(PathBuf::from("/proc"))
+    }
+}
+
+impl ColectorBloqueante for ColectorMemoria {
+    fn nombre(&self) -> &'static str {
+        "memory"
+    }
+
+    fn preparar(&mut self) -> Result<(), String> {
+        if cfg!(target_os = "linux") {
+            self.anterior = self.leer_swap();
+        }
+        Ok(())
+    }
+
+    //En Linux todo sale de <raiz>/meminfo. Si falla el desglose la sección sale igual, sin él; si no se puede
+    //leer meminfo los totales vienen de sysinfo. En ambos casos queda el aviso en `errors`
+    fn recolectar(&mut self) -> Result<Seccion, String> {
+        if !cfg!(target_os = "linux") {
+            return Ok(Seccion::Memoria(self.de_sysinfo()));
+        }
+        let memoria = match self.leer("meminfo") {
+            Ok(meminfo) => {
+                let mut memoria = memoria_de_meminfo(&meminfo).unwrap_or_else(|e| {
+                    self.errores.push(format!("{}; se usan los totales de sysinfo", e));
+                    self.de_sysinfo()
+                });
+                memoria.breakdown = self.detalle(&meminfo).map_err(|e| self.errores.push(format!("desglose: {}", e))).ok();
+                memoria
+            }
+            Err(e) => {
+                self.errores.push(format!("{}; sin desglose y con los totales de sysinfo", e));
+                self.de_sysinfo()
+            }
+        };
+        Ok(Seccion::Memoria(memoria))
+    }
+
+    fn errores(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.errores)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uom::si::information::byte;
+    use super::*;
+    #[cfg(target_os = "linux")]
+    use crate::prueba::arbol;
+
+    const MEMINFO: &str = "MemTotal:        6147400 kB
+MemFree:          284636 kB
+MemAvailable:    5513252 kB
+Buffers:           61240 kB
+Cached:          5313692 kB
+SwapCached:            0 kB
+SwapTotal:       2097148 kB
+SwapFree:        1048576 kB
+Dirty:             25904 kB
+Writeback:             0 kB
+Shmem:              9048 kB
+Slab:             185056 kB
+HugePages_Total:       0
+HugePages_Free:        0
+Hugepagesize:       2048 kB
+";
+
+    fn recolectar(colector: &mut ColectorMemoria) -> Memoria {
+        match colector.recolectar() {
+            Ok(Seccion::Memoria(memoria)) => memoria,
+            Ok(_) => panic!("sección equivocada"),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    fn totales_de_meminfo() {
+        let memoria = memoria_de_meminfo(MEMINFO).unwrap();
+        assert_eq!(memoria.total_bytes.get::<byte>(), 6147400 * 1024);
+        assert_eq!(memoria.available_bytes.unwrap().get::<byte>(), 5513252 * 1024);
+        assert_eq!(memoria.used_bytes.get::<byte>(), (6147400 - 5513252) * 1024);
+        assert_eq!(memoria.free_bytes.get::<byte>(), 284636 * 1024);
+        assert_eq!(memoria.used_swap_bytes.get::<byte>(), (2097148 - 1048576) * 1024);
+        //Antes del 3.14 no hay MemAvailable
+        let viejo = memoria_de_meminfo(&MEMINFO.replace("MemAvailable:    5513252 kB\n", "")).unwrap();
+        assert!(viejo.available_bytes.is_none());
+        assert_eq!(viejo.used_bytes.get::<byte>(), (6147400 - 284636) * 1024);
+        assert!(memoria_de_meminfo("MemFree: 1 kB\n").is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn todo_del_meminfo_configurado() {
+        let raiz = arbol(&[("meminfo", MEMINFO)]);
+        let mut colector = ColectorMemoria::new(raiz.path().to_path_buf());
+        let memoria = recolectar(&mut colector);
+        assert_eq!(memoria.total_bytes.get::<byte>(), 6147400 * 1024);
+        let detalle = memoria.breakdown.unwrap();
+        assert_eq!(detalle.cached_bytes.get::<byte>(), 5313692 * 1024);
+        assert_eq!(detalle.hugepage_size_bytes.get::<byte>(), 2048 * 1024);
+        //Sin vmstat no hay tasas de swap, pero no es un error
+        assert!(detalle.swap_in_bytes_per_sec.is_none());
+        assert!(colector.errores().is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn sin_una_clave_del_desglose() {
+        let raiz = arbol(&[("meminfo", &MEMINFO.replace("Slab:             185056 kB\n", ""))]);
+        let mut colector = ColectorMemoria::new(raiz.path().to_path_buf());
+        let memoria = recolectar(&mut colector);
+        assert_eq!(memoria.total_bytes.get::<byte>(), 6147400 * 1024);
+        assert!(memoria.breakdown.is_none());
+        assert_eq!(colector.errores(), vec!["desglose: falta Slab en meminfo".to_string()]);
+        //Se toman una vez
+        assert!(colector.errores().is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn sin_meminfo() {
+        let raiz = arbol(&[]);
+        let mut colector = ColectorMemoria::new(raiz.path().to_path_buf());
+        let memoria = recolectar(&mut colector);
+        assert!(memoria.breakdown.is_none());
+        let errores = colector.errores();
+        assert_eq!(errores.len(), 1);
+        assert!(errores[0].contains("meminfo") && errores[0].contains("sysinfo"), "{}", errores[0]);
+    }
+}
@@ -0,0 +1,323 @@
+//Un colector por subsistema; cada uno guarda su propio estado entre muestras (estructuras de sysinfo,
+//contadores anteriores) y devuelve su sección de Datosuwu o un error propio
+use std::sync::{Arc, Mutex, TryLockError};
+use std::time::Instant;
+use futures::future::BoxFuture;
+use uom::si::information::byte;
+use crate::config::ConfigRecolector;
+use crate::eventos::Evento;
+use crate::datos::{Carga, Cgroup, ConsumoPropio, Cpu, Datosuwu, Disco, EstadisticasDisco, Memoria, Proceso, Red, Sensor, SistemaArchivos, Sockets, Usuario};
+use crate::unidades::{bytes_por_segundo, Bytes, BytesPorSegundo};
+
+mod carga;
+mod cgroups;
+mod cpu;
+mod disco;
+mod diskstats;
+mod memoria;
+mod procesos;
+mod propio;
+mod red;
+mod sistemas_archivos;
+mod sockets;
+mod temperaturas;
+
+pub use carga::ColectorCarga;
+pub use cgroups::ColectorCgroups;
+pub use cpu::ColectorCpu;
+pub use disco::ColectorDisco;
+pub use diskstats::ColectorDiskstats;
+pub use memoria::ColectorMemoria;
+pub use procesos::ColectorProcesos;
+pub use propio::ColectorPropio;
+pub use red::ColectorRed;
+pub use sistemas_archivos::ColectorSistemasArchivos;
+pub use sockets::ColectorSockets;
+pub use temperaturas::ColectorTemperaturas;
+
+//Lo que devuelve un colector: la sección de la muestra que le toca llenar
+pub enum Seccion {
+    Cpu(Cpu),
+    //En caja porque con la presión es mucho más grande que las demás secciones
+    Carga(Box<Carga>),
+    Memoria(Memoria),
+    Red(Red),
+    Sockets(Sockets),
+    Disco(Disco),
+    Diskstats(Vec<EstadisticasDisco>),
+    SistemasArchivos(Vec<SistemaArchivos>),
+    Temperaturas(Vec<Sensor>),
+    //Un top por cada orden configurado; None si ese orden no se pidió
+    Procesos {
+        por_cpu: Option<Vec<Proceso>>,
+        por_memoria: Option<Vec<Proceso>>,
+        por_disco: Option<Vec<Proceso>>,
+        por_usuario: Vec<Usuario>,
+    },
+    Cgroups(Vec<Cgroup>),
+    ConsumoPropio(ConsumoPropio),
+}
+
+impl Seccion {
+    pub fn aplicar(self, datos: &mut Datosuwu) {
+        match self {
+            Seccion::Cpu(cpu) => datos.cpu = Some(cpu),
+            Seccion::Carga(carga) => datos.load = Some(*carga),
+            Seccion::Memoria(memoria) => datos.memory = Some(memoria),
+            Seccion::Red(red) => datos.network = Some(red),
+            Seccion::Sockets(sockets) => datos.sockets = Some(sockets),
+            Seccion::Disco(disco) => datos.disk = Some(disco),
+            Seccion::Diskstats(estadisticas) => datos.disk_stats = Some(estadisticas),
+            Seccion::SistemasArchivos(sistemas) => datos.filesystems = Some(sistemas),
+            Seccion::Temperaturas(sensores) => datos.temperatures = Some(sensores),
+            Seccion::Procesos { por_cpu, por_memoria, por_disco, por_usuario } => {
+                datos.top_processes = por_cpu;
+                datos.top_processes_by_memory = por_memoria;
+                datos.top_processes_by_disk_io = por_disco;
+                datos.per_user = Some(por_usuario);
+            }
+            Seccion::Cgroups(cgroups) => datos.cgroups = Some(cgroups),
+            Seccion::ConsumoPropio(consumo) => datos.self_overhead = Some(consumo),
+        }
+    }
+}
+
+pub trait Collector: Send {
+    //Nombre del colector; es también la clave de su configuración y el nombre de su sección en el JSON
+    fn nombre(&self) -> &'static str;
+
+    //Lectura base para los colectores que calculan tasas o porcentajes entre dos lecturas
+    fn preparar(&mut self) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn recolectar(&mut self) -> BoxFuture<'_, Result<Seccion, String>>;
+
+    //Eventos que notó en la última recolección (como un sensor que llegó a su temperatura crítica);
+    //el Recolector los toma después de cada muestra
+    fn eventos(&mut self) -> Vec<Evento> {
+        Vec::new()
+    }
+
+    //Partes que faltaron en la última recolección aunque la sección sí salió (como el desglose de memoria);
+    //el Recolector las pasa a `errors` con el nombre del colector
+    fn errores(&mut self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+//Los colectores que leen /proc, /sys, llaman a statvfs o recorren /proc/*/fd bloquean el hilo mientras leen.
+//Se escriben como funciones normales y `todos` los envuelve en EnHilo, que corre cada lectura con
+//spawn_blocking: así el límite de tiempo de cada uno se cumple y uno colgado no frena a los demás
+pub trait ColectorBloqueante: Send + 'static {
+    fn nombre(&self) -> &'static str;
+
+    fn preparar(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn recolectar(&mut self) -> Result<Seccion, String>;
+
+    fn eventos(&mut self) -> Vec<Evento> {
+        Vec::new()
+    }
+
+    fn errores(&mut self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+pub struct EnHilo<C> {
+    nombre: &'static str,
+    //Compartido con la lectura en curso, que sigue en su hilo aunque se haya pasado de su límite
+    colector: Arc<Mutex<C>>,
+}
+
+impl<C: ColectorBloqueante> EnHilo<C> {
+    pub fn new(colector: C) -> EnHilo<C> {
+        EnHilo { nombre: colector.nombre(), colector: Arc::new(Mutex::new(colector)) }
+    }
+
+    fn en_hilo<T: Send + 'static>(
+        &self,
+        leer: impl FnOnce(&mut C) -> Result<T, String> + Send + 'static,
+    ) -> BoxFuture<'static, Result<T, String>> {
+        let colector = Arc::clone(&self.colector);
+        Box::pin(async move {
+            tokio::task::spawn_blocking(move || {
+                //Si la lectura anterior sigue colgada no se encola otra detrás de ella
+                let mut colector = match colector.try_lock() {
+                    Ok(colector) => colector,
+                    Err(TryLockError::Poisoned(envenenado)) => envenenado.into_inner(),
+                    Err(TryLockError::WouldBlock) => return Err("la lectura anterior todavía no termina".to_string()),
+                };
+                leer(&mut colector)
+            })
+            .await
+            .map_err(|e| format!("la lectura falló: {}", e))?
+        })
+    }
+}
+
+impl<C: ColectorBloqueante> Collector for EnHilo<C> {
+    fn nombre(&self) -> &'static str {
+        self.nombre
+    }
+
+    fn preparar(&mut self) -> BoxFuture<'_, Result<(), String>> {
+        self.en_hilo(C::preparar)
+    }
+
+    fn recolectar(&mut self) -> BoxFuture<'_, Result<Seccion, String>> {
+        self.en_hilo(C::recolectar)
+    }
+
+    //Con la lectura todavía en curso no hay eventos nuevos; quedan para la siguiente muestra
+    fn eventos(&mut self) -> Vec<Evento> {
+        match self.colector.try_lock() {
+            Ok(mut colector) => colector.eventos(),
+            Err(TryLockError::Poisoned(envenenado)) => envenenado.into_inner().eventos(),
+            Err(TryLockError::WouldBlock) => Vec::new(),
+        }
+    }
+
+    fn errores(&mut self) -> Vec<String> {
+        match self.colector.try_lock() {
+            Ok(mut colector) => colector.errores(),
+            Err(TryLockError::Poisoned(envenenado)) => envenenado.into_inner().errores(),
+            Err(TryLockError::WouldBlock) => Vec::new(),
+        }
+    }
+}
+
+//Todos los colectores disponibles, en el orden en que aparecen en la muestra
+pub fn todos(config: &ConfigRecolector) -> Vec<Box<dyn Collector>> {
+    //Los que leen /proc o /sys/fs/cgroup solo existen en Linux
+    let linux = cfg!(target_os = "linux");
+    let mut todos: Vec<Box<dyn Collector>> = vec![Box::new(EnHilo::new(ColectorCpu::new()))];
+    if linux {
+        todos.push(Box::new(EnHilo::new(ColectorCarga::new(config.raiz_proc.clone()))));
+    }
+    todos.push(Box::new(EnHilo::new(ColectorMemoria::new(config.raiz_proc.clone()))));
+    todos.push(Box::new(EnHilo::new(ColectorRed::new(config.raiz_red.clone(), config.filtro_interfaces.clone()))));
+    if linux {
+        todos.push(Box::new(EnHilo::new(ColectorSockets::new(config.raiz_proc.clone()))));
+    }
+    todos.push(Box::new(ColectorDisco::new(config.raiz_bloques.clone(), config.filtro_discos.clone())));
+    if linux {
+        todos.push(Box::new(EnHilo::new(ColectorDiskstats::new(
+            config.raiz_proc.clone(),
+            config.raiz_bloques.clone(),
+            config.filtro_discos.clone(),
+        ))));
+    }
+    todos.push(Box::new(EnHilo::new(ColectorSistemasArchivos::new())));
+    todos.push(Box::new(EnHilo::new(ColectorTemperaturas::new(config))));
+    todos.push(Box::new(EnHilo::new(ColectorProcesos::new(config))));
+    if linux {
+        todos.push(Box::new(EnHilo::new(ColectorCgroups::new(config.raiz_cgroup.clone()))));
+    }
+    todos.push(Box::new(ColectorPropio::new()));
+    todos
+}
+
+//Lectura de un par de contadores acumulados (desde el arranque) y el momento en que se tomó
+#[derive(Clone, Copy)]
+pub(crate) struct Contadores {
+    pub instante: Instant,
+    pub entrada: Bytes,
+    pub salida: Bytes,
+}
+
+impl Contadores {
+    //Tasas de entrada y salida desde la lectura anterior; sin lectura anterior no hay tasa
+    pub fn tasas(&self, anterior: Option<Contadores>) -> (Option<BytesPorSegundo>, Option<BytesPorSegundo>) {
+        let Some(anterior) = anterior else {
+            return (None, None);
+        };
+        let segundos = self.instante.duration_since(anterior.instante).as_secs_f64();
+        (tasa(self.entrada, anterior.entrada, segundos), tasa(self.salida, anterior.salida, segundos))
+    }
+}
+
+//Tasa de un contador entre dos lecturas; si el contador se reinició se toma como 0
+fn tasa(actual: Bytes, anterior: Bytes, segundos: f64) -> Option<BytesPorSegundo> {
+    if segundos <= 0.0 {
+        return None;
+    }
+    let diferencia = actual.get::<byte>().saturating_sub(anterior.get::<byte>());
+    Some(bytes_por_segundo(diferencia as f64 / segundos))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{mpsc, Barrier};
+    use std::time::Duration;
+    use tokio::sync::mpsc as canal;
+    use super::*;
+
+    //Como un statvfs sobre un NFS caído: no vuelve hasta que el test lo suelta
+    struct Colgado {
+        empezo: canal::UnboundedSender<()>,
+        soltar: mpsc::Receiver<()>,
+    }
+
+    impl ColectorBloqueante for Colgado {
+        fn nombre(&self) -> &'static str {
+            "colgado"
+        }
+
+        fn recolectar(&mut self) -> Result<Seccion, String> {
+            let _ = self.empezo.send(());
+            self.soltar.recv().map_err(|e| e.to_string())?;
+            Ok(Seccion::Temperaturas(Vec::new()))
+        }
+    }
+
+    //Solo termina cuando todos los del grupo están leyendo a la vez
+    struct EnGrupo(Arc<Barrier>);
+
+    impl ColectorBloqueante for EnGrupo {
+        fn nombre(&self) -> &'static str {
+            "en_grupo"
+        }
+
+        fn recolectar(&mut self) -> Result<Seccion, String> {
+            self.0.wait();
+            Ok(Seccion::Temperaturas(Vec::new()))
+        }
+    }
+
+    #[tokio::test]
+    async fn el_limite_corta_una_lectura_colgada() {
+        let (avisar, mut empezo) = canal::unbounded_channel();
+        let (soltar, colgado) = mpsc::channel();
+        let mut colector = EnHilo::new(Colgado { empezo: avisar, soltar: colgado });
+        //La lectura no vuelve mientras no se suelte, así que solo el límite puede terminarla
+        assert!(tokio::time::timeout(Duration::from_millis(50), colector.recolectar()).await.is_err());
+        empezo.recv().await.unwrap();
+        //Mientras la anterior siga colgada, la siguiente falla enseguida en lugar de esperar detrás
+        let error = colector.recolectar().await.err().unwrap();
+        assert!(error.contains("todavía no termina"), "{}", error);
+        //Suelta la colgada y la siguiente lectura
+        soltar.send(()).unwrap();
+        soltar.send(()).unwrap();
+        let resultado = loop {
+            match colector.recolectar().await {
+                Err(e) if e.contains("todavía no termina") => tokio::task::yield_now().await,
+                resultado => break resultado,
+            }
+        };
+        assert!(resultado.is_ok());
+    }
+
+    //Cada uno espera a los otros dos: si se leyeran uno detrás de otro, el primero no terminaría nunca
+    #[tokio::test]
+    async fn los_colectores_colgados_no_se_esperan_entre_si() {
+        let grupo = Arc::new(Barrier::new(3));
+        let mut colectores: Vec<_> = (0..3).map(|_| EnHilo::new(EnGrupo(Arc::clone(&grupo)))).collect();
+        let lecturas = futures::future::join_all(colectores.iter_mut().map(|c| c.recolectar()));
+        let resultados = tokio::time::timeout(Duration::from_secs(30), lecturas).await.expect("se leyeron en serie");
+        assert!(resultados.iter().all(Result::is_ok));
+    }
+}
@@ -0,0 +1,314 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Instant;
+use chrono::DateTime;
+use sysinfo::{Pid, Process, ProcessRefreshKind, System, ThreadKind, UpdateKind, Users};
+use crate::config::{ConfigRecolector, OrdenProcesos};
+use crate::datos::{rfc3339, Proceso, Usuario};
+use crate::unidades::{bytes, bytes_por_segundo};
+use super::cgroups::id_contenedor;
+use super::{ColectorBloqueante, Seccion};
+
+pub struct ColectorProcesos {
+    system: System,
+    //Para pasar el uid de cada proceso a nombre de usuario
+    users: Users,
+    //Cuántos procesos entran a cada top y qué tops se arman
+    top: usize,
+    listas: Vec<OrdenProcesos>,
+    //El propio recolector nunca entra al top (su consumo va en self_overhead)
+    propio: u32,
+    //Nombres en minúsculas y sin ".exe"
+    ignorados: Vec<String>,
+    //Para leer <raiz>/<pid>/cgroup y <raiz>/<pid>/fd (solo Linux)
+    raiz_proc: PathBuf,
+    //Momento del último refresco; la E/S de disco de sysinfo es desde entonces
+    ultimo_refresco: Option<Instant>,
+}
+
+//Lo que se necesita de cada proceso para armar los tops y las sumas por usuario, sin leer nada más de /proc
+struct Candidato {
+    pid: u32,
+    nombre: String,
+    usuario: Option<String>,
+    cpu: f32,
+    memoria: u64,
+    //Bytes leídos y escritos desde el refresco anterior
+    leidos: u64,
+    escritos: u64,
+}
+
+impl ColectorProcesos {
+    pub fn new(config: &ConfigRecolector) -> ColectorProcesos {
+        ColectorProcesos {
+            system: System::new(),
+            users: Users::new(),
+            top: config.top_procesos,
+            listas: config.listas_procesos.clone(),
+            propio: std::process::id(),
+            ignorados: config.procesos_ignorados.iter().map(|nombre| nombre_comparable(nombre)).collect(),
+            raiz_proc: config.raiz_proc.clone(),
+            ultimo_refresco: None,
+        }
+    }
+
+    //Devuelve los segundos desde el refresco anterior
+    fn refrescar(&mut self) -> Option<f64> {
+        //Lo de refresh_processes más el usuario y la línea de comandos, que no cambian en la vida del proceso
+        self.system.refresh_processes_specifics(
+            ProcessRefreshKind::new()
+                .with_cpu()
+                .with_memory()
+                .with_disk_usage()
+                .with_exe(UpdateKind::OnlyIfNotSet)
+                .with_cmd(UpdateKind::OnlyIfNotSet)
+                .with_user(UpdateKind::OnlyIfNotSet),
+        );
+        let ahora = Instant::now();
+        self.ultimo_refresco.replace(ahora).map(|antes| ahora.duration_since(antes).as_secs_f64())
+    }
+
+    //Los procesos que no entran a los tops: el propio recolector y los ignorados
+    fn excluido(&self, candidato: &Candidato) -> bool {
+        candidato.pid == self.propio || self.ignorados.contains(&nombre_comparable(&candidato.nombre))
+    }
+
+    //En Linux sysinfo también lista los hilos de cada proceso (como los tokio-rt-worker del recolector);
+    //su consumo ya está sumado en el proceso al que pertenecen, así que se dejan fuera
+    fn candidatos(&self) -> Vec<Candidato> {
+        self.system
+            .processes()
+            .values()
+            .filter(|p| p.thread_kind() != Some(ThreadKind::Userland))
+            .map(|p| {
+                let disco = p.disk_usage();
+                Candidato {
+                    pid: p.pid().as_u32(),
+                    nombre: p.name().to_string(),
+                    usuario: self.usuario(p),
+                    cpu: p.cpu_usage(),
+                    memoria: p.memory(),
+                    leidos: disco.read_bytes,
+                    escritos: disco.written_bytes,
+                }
+            })
+            .collect()
+    }
+
+    //La línea "0::<ruta>" de /proc/<pid>/cgroup es la de cgroup v2
+    fn cgroup(&self, pid: u32) -> Option<String> {
+        let texto = std::fs::read_to_string(self.raiz_proc.join(pid.to_string()).join("cgroup")).ok()?;
+        texto.lines().find_map(|linea| linea.strip_prefix("0::")).map(String::from)
+    }
+
+    //Entradas de /proc/<pid>/fd; solo se pueden leer las de procesos del mismo usuario (o como root)
+    fn descriptores(&self, pid: u32) -> Option<usize> {
+        std::fs::read_dir(self.raiz_proc.join(pid.to_string()).join("fd")).ok().map(|entradas| entradas.count())
+    }
+
+    fn usuario(&self, procesouvu: &Process) -> Option<String> {
+        procesouvu
+            .user_id()
+            .map(|uid| self.users.get_user_by_id(uid).map_or_else(|| uid.to_string(), |u| u.name().to_string()))
+    }
+
+    fn proceso(&self, procesouvu: &Process, segundos: Option<f64>) -> Proceso {
+        let pid = procesouvu.pid().as_u32();
+        let disco = procesouvu.disk_usage();
+        let tasa = |bytes_en_intervalo: u64| {
+            segundos.filter(|s| *s > 0.0).map(|s| bytes_por_segundo(bytes_en_intervalo as f64 / s))
+        };
+        let cgroup = self.cgroup(pid);
+        Proceso {
+            ppid: procesouvu.parent().map(|p| p.as_u32()),
+            user: self.usuario(procesouvu),
+            cmd: procesouvu.cmd().to_vec(),
+            exe: procesouvu.exe().map(|ruta| ruta.to_string_lossy().into_owned()),
+            start_time: DateTime::from_timestamp(procesouvu.start_time() as i64, 0).map(rfc3339),
+            status: Some(procesouvu.status().to_string()),
+            //sysinfo no cuenta el hilo principal entre las tareas
+            threads: procesouvu.tasks().map(|hilos| hilos.len() + 1),
+            open_fds: self.descriptores(pid),
+            disk_read_bytes: Some(bytes(disco.total_read_bytes)),
+            disk_written_bytes: Some(bytes(disco.total_written_bytes)),
+            disk_read_bytes_per_sec: tasa(disco.read_bytes),
+            disk_write_bytes_per_sec: tasa(disco.written_bytes),
+            container_id: cgroup.as_deref().and_then(id_contenedor),
+            cgroup,
+            ..Proceso::basico(Some(pid), procesouvu.name().to_string(), procesouvu.cpu_usage(), bytes(procesouvu.memory()))
+        }
+    }
+}
+
+//Los `top` candidatos según `orden`, del que más consume al que menos; los empates van por pid
+fn ordenar<'a>(candidatos: &[&'a Candidato], orden: OrdenProcesos, top: usize) -> Vec<&'a Candidato> {
+    let mut ordenados = candidatos.to_vec();
+    match orden {
+        //total_cmp también ordena los NaN, en lugar de entrar en pánico
+        OrdenProcesos::Cpu => ordenados.sort_by(|a, b| b.cpu.total_cmp(&a.cpu).then(a.pid.cmp(&b.pid))),
+        OrdenProcesos::Memory => ordenados.sort_by_key(|c| (Reverse(c.memoria), c.pid)),
+        //Lo leído y escrito desde la muestra anterior, no desde que arrancó cada proceso
+        OrdenProcesos::DiskIo => ordenados.sort_by_key(|c| (Reverse(c.leidos + c.escritos), c.pid)),
+    }
+    ordenados.truncate(top);
+    ordenados
+}
+
+//Suma por usuario de todos los procesos, del que más CPU usa al que menos
+fn por_usuario(candidatos: &[Candidato], segundos: Option<f64>) -> Vec<Usuario> {
+    //Procesos, CPU, memoria, bytes leídos y escritos en el intervalo
+    let mut sumas: HashMap<&str, (usize, f32, u64, u64, u64)> = HashMap::new();
+    for candidato in candidatos {
+        let suma = sumas.entry(candidato.usuario.as_deref().unwrap_or("unknown")).or_default();
+        suma.0 += 1;
+        suma.1 += candidato.cpu;
+        suma.2 += candidato.memoria;
+        suma.3 += candidato.leidos;
+        suma.4 += candidato.escritos;
+    }
+    let tasa = |bytes_en_intervalo: u64| {
+        segundos.filter(|s| *s > 0.0).map(|s| bytes_por_segundo(bytes_en_intervalo as f64 / s))
+    };
+    let mut usuarios: Vec<Usuario> = sumas
+        .into_iter()
+        .map(|(user, (processes, cpu, memoria, leidos, escritos))| Usuario {
+            user: user.to_string(),
+            processes,
+            cpu_usage_percent: cpu,
+            memory_bytes: bytes(memoria),
+            disk_read_bytes_per_sec: tasa(leidos),
+            disk_write_bytes_per_sec: tasa(escritos),
+        })
+        .collect();
+    usuarios.sort_by(|a, b| b.cpu_usage_percent.total_cmp(&a.cpu_usage_percent).then_with(|| a.user.cmp(&b.user)));
+    usuarios
+}
+
+//"Act4.exe" y "act4" son el mismo proceso para la lista de ignorados
+fn nombre_comparable(nombre: &str) -> String {
+    let nombre = nombre.to_lowercase();
+    match nombre.strip_suffix(".exe") {
+        Some(sin_extension) => sin_extension.to_string(),
+        None => nombre,
+    }
+}
+
+impl ColectorBloqueante for ColectorProcesos {
+    fn nombre(&self) -> &'static str {
+        "processes"
+    }
+
+    //El % de CPU y la E/S de disco de cada proceso son la diferencia entre dos refrescos
+    fn preparar(&mut self) -> Result<(), String> {
+        self.refrescar();
+        Ok(())
+    }
+
+    fn recolectar(&mut self) -> Result<Seccion, String> {
+        let segundos = self.refrescar();
+        self.users.refresh_list();
+        let todos = self.candidatos();
+        // Para obtener los procesos que más consumen
+        let candidatos: Vec<&Candidato> = todos.iter().filter(|c| !self.excluido(c)).collect();
+        //Los `top` procesos según `orden`, o None si ese top no se pidió
+        let top = |orden: OrdenProcesos| {
+            self.listas.contains(&orden).then(|| {
+                ordenar(&candidatos, orden, self.top)
+                    .into_iter()
+                    .filter_map(|c| self.system.process(Pid::from_u32(c.pid)))
+                    .map(|p| self.proceso(p, segundos))
+                    .collect()
+            })
+        };
+        let seccion = Seccion::Procesos {
+            por_cpu: top(OrdenProcesos::Cpu),
+            por_memoria: top(OrdenProcesos::Memory),
+            por_disco: top(OrdenProcesos::DiskIo),
+            //El recolector y los ignorados sí cuentan en su usuario
+            por_usuario: por_usuario(&todos, segundos),
+        };
+        Ok(seccion)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidato(pid: u32, nombre: &str, usuario: Option<&str>, cpu: f32, memoria: u64, leidos: u64, escritos: u64) -> Candidato {
+        Candidato { pid, nombre: nombre.to_string(), usuario: usuario.map(String::from), cpu, memoria, leidos, escritos }
+    }
+
+    fn pids(ordenados: &[&Candidato]) -> Vec<u32> {
+        ordenados.iter().map(|c| c.pid).collect()
+    }
+
+    fn tabla() -> Vec<Candidato> {
+        vec![
+            candidato(30, "firefox", Some("pau"), 40.0, 900, 0, 10),
+            candidato(10, "postgres", Some("postgres"), 5.0, 2000, 500, 500),
+            candidato(20, "cargo", Some("pau"), 40.0, 300, 100, 0),
+            candidato(40, "kworker", None, f32::NAN, 0, 0, 0),
+            candidato(50, "sh", Some("pau"), 0.0, 300, 0, 100),
+        ]
+    }
+
+    #[test]
+    fn tops_por_cpu_memoria_y_disco() {
+        let todos = tabla();
+        let candidatos: Vec<&Candidato> = todos.iter().collect();
+        //Los empates van por pid; el NaN queda antes que todo con total_cmp y no hace entrar en pánico
+        assert_eq!(pids(&ordenar(&candidatos, OrdenProcesos::Cpu, 5)), [40, 20, 30, 10, 50]);
+        assert_eq!(pids(&ordenar(&candidatos, OrdenProcesos::Memory, 5)), [10, 30, 20, 50, 40]);
+        //Lectura más escritura; 20 y 50 empatan con 100
+        assert_eq!(pids(&ordenar(&candidatos, OrdenProcesos::DiskIo, 5)), [10, 20, 50, 30, 40]);
+        assert_eq!(pids(&ordenar(&candidatos, OrdenProcesos::Memory, 2)), [10, 30]);
+        //Pedir más de los que hay devuelve todos
+        assert_eq!(ordenar(&candidatos, OrdenProcesos::Cpu, 50).len(), 5);
+        assert!(ordenar(&candidatos, OrdenProcesos::Cpu, 0).is_empty());
+        assert!(ordenar(&[], OrdenProcesos::DiskIo, 5).is_empty());
+    }
+
+    #[test]
+    fn el_recolector_y_los_ignorados_no_entran_a_los_tops() {
+        let config = ConfigRecolector {
+            procesos_ignorados: vec!["Firefox.EXE".to_string(), "sh".to_string()],
+            ..ConfigRecolector::default()
+        };
+        let mut colector = ColectorProcesos::new(&config);
+        colector.propio = 10;
+        let todos = tabla();
+        let candidatos: Vec<u32> = todos.iter().filter(|c| !colector.excluido(c)).map(|c| c.pid).collect();
+        assert_eq!(candidatos, [20, 40]);
+        //Sin distinguir mayúsculas ni ".exe", pero el nombre tiene que ser el mismo
+        assert_eq!(nombre_comparable("Act4.exe"), "act4");
+        assert_eq!(nombre_comparable("ACT4"), "act4");
+        assert_eq!(nombre_comparable("act4.exe.bak"), "act4.exe.bak");
+        assert!(!colector.excluido(&candidato(60, "shell", None, 0.0, 0, 0, 0)));
+        assert!(colector.excluido(&candidato(61, "FIREFOX", None, 0.0, 0, 0, 0)));
+    }
+
+    #[test]
+    fn sumas_por_usuario() {
+        let mut todos = tabla();
+        todos.retain(|c| !c.cpu.is_nan());
+        todos.push(candidato(60, "init", None, 1.0, 100, 0, 0));
+        let usuarios = por_usuario(&todos, Some(10.0));
+        let resumen: Vec<(&str, usize, f32, u64)> = usuarios
+            .iter()
+            .map(|u| (u.user.as_str(), u.processes, u.cpu_usage_percent, u.memory_bytes.get::<uom::si::information::byte>()))
+            .collect();
+        //Sin dueño conocido va en "unknown"
+        assert_eq!(resumen, [("pau", 3, 80.0, 1500), ("postgres", 1, 5.0, 2000), ("unknown", 1, 1.0, 100)]);
+        let tasas = |u: &Usuario| {
+            let bps = |t: Option<crate::unidades::BytesPorSegundo>| t.map(|t| t.get::<uom::si::information_rate::byte_per_second>());
+            (bps(u.disk_read_bytes_per_sec), bps(u.disk_write_bytes_per_sec))
+        };
+        assert_eq!(tasas(&usuarios[0]), (Some(10.0), Some(11.0)));
+        assert_eq!(tasas(&usuarios[1]), (Some(50.0), Some(50.0)));
+        //En la primera muestra no hay intervalo para las tasas
+        assert_eq!(tasas(&por_usuario(&todos, None)[0]), (None, None));
+        assert!(por_usuario(&[], Some(10.0)).is_empty());
+    }
+}
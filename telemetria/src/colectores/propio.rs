@@ -0,0 +1,74 @@
+//Lo que consume el propio proceso que recolecta (act4 o monitoreo), para saber cuánto pesa medir
+use futures::future::BoxFuture;
+use sysinfo::{Pid, ProcessRefreshKind, System};
+use crate::datos::ConsumoPropio;
+use crate::unidades::bytes;
+use super::{Collector, Seccion};
+
+pub struct ColectorPropio {
+    system: System,
+    pid: Pid,
+}
+
+impl ColectorPropio {
+    pub fn new() -> ColectorPropio {
+        ColectorPropio { system: System::new(), pid: Pid::from_u32(std::process::id()) }
+    }
+
+    fn refrescar(&mut self) -> bool {
+        self.system.refresh_process_specifics(self.pid, ProcessRefreshKind::new().with_cpu().with_memory())
+    }
+}
+
+impl Default for ColectorPropio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Collector for ColectorPropio {
+    fn nombre(&self) -> &'static str {
+        "self_overhead"
+    }
+
+    //El % de CPU es la diferencia entre dos refrescos, igual que en el top de procesos
+    fn preparar(&mut self) -> BoxFuture<'_, Result<(), String>> {
+        self.refrescar();
+        Box::pin(async { Ok(()) })
+    }
+
+    fn recolectar(&mut self) -> BoxFuture<'_, Result<Seccion, String>> {
+        let resultado = if self.refrescar() {
+            let proceso = self.system.process(self.pid);
+            Ok(Seccion::ConsumoPropio(ConsumoPropio {
+                pid: self.pid.as_u32(),
+                cpu_usage_percent: proceso.map_or(0.0, |p| p.cpu_usage()),
+                cpu_time_secs: tiempo_de_cpu(),
+                rss_bytes: bytes(proceso.map_or(0, |p| p.memory())),
+            }))
+        } else {
+            Err(format!("no se encontró el proceso propio (pid {})", self.pid))
+        };
+        Box::pin(async move { resultado })
+    }
+}
+
+//Tiempo de CPU acumulado (usuario + sistema) del proceso desde que arrancó
+#[cfg(unix)]
+fn tiempo_de_cpu() -> Option<f64> {
+    let mut uso = std::mem::MaybeUninit::<libc::rusage>::zeroed();
+    //SAFETY: getrusage solo escribe en la estructura que se le pasa
+    if unsafe { libc::getrusage(libc::RUSAGE_SELF, uso.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    //SAFETY: getrusage devolvió 0, así que llenó la estructura
+    let uso = unsafe { uso.assume_init() };
+    let segundos = |t: libc::timeval| t.tv_sec as f64 + t.tv_usec as f64 / 1_000_000.0;
+    Some(segundos(uso.ru_utime) + segundos(uso.ru_stime))
+}
+
+//sysinfo no da el tiempo de CPU acumulado; en otros sistemas solo se reporta el %
+#[cfg(not(unix))]
+fn tiempo_de_cpu() -> Option<f64> {
+    None
+}
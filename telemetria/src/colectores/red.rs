@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use sysinfo::Networks;
+use crate::config::{ConfigRecolector, Filtro};
+use crate::datos::{Interfaz, Red};
+use crate::unidades::bytes;
+use super::{ColectorBloqueante, Contadores, Seccion};
+
+pub struct ColectorRed {
+    networks: Networks,
+    raiz_red: PathBuf,
+    filtro: Filtro,
+    //Última lectura del total y de cada interfaz, para las tasas
+    anterior: Option<Contadores>,
+    anteriores: HashMap<String, Contadores>,
+}
+
+//Contadores de una interfaz que pasó el filtro
+struct Lectura {
+    nombre: String,
+    contadores: Contadores,
+    paquetes: (u64, u64),
+    errores: (u64, u64),
+    descartados: (Option<u64>, Option<u64>),
+}
+
+impl ColectorRed {
+    pub fn new(raiz_red: PathBuf, filtro: Filtro) -> ColectorRed {
+        ColectorRed {
+            networks: Networks::new_with_refreshed_list(),
+            raiz_red,
+            filtro,
+            anterior: None,
+            anteriores: HashMap::new(),
+        }
+    }
+
+    //Lee cada interfaz que pasa el filtro y suma sus totales acumulados
+    fn leer(&mut self) -> (Contadores, Vec<Lectura>) {
+        self.networks.refresh_list();
+        let instante = Instant::now();
+        let mut lecturas: Vec<Lectura> = self
+            .networks
+            .iter()
+            .filter(|(nombre, _)| self.filtro.permite(nombre))
+            .map(|(nombre, data)| Lectura {
+                nombre: nombre.clone(),
+                contadores: Contadores {
+                    instante,
+                    entrada: bytes(data.total_received()),
+                    salida: bytes(data.total_transmitted()),
+                },
+                paquetes: (data.total_packets_received(), data.total_packets_transmitted()),
+                errores: (data.total_errors_on_received(), data.total_errors_on_transmitted()),
+                descartados: (
+                    descartados(&self.raiz_red, nombre, "rx_dropped"),
+                    descartados(&self.raiz_red, nombre, "tx_dropped"),
+                ),
+            })
+            .collect();
+        lecturas.sort_by(|a, b| a.nombre.cmp(&b.nombre));
+        let total = Contadores {
+            instante,
+            entrada: lecturas.iter().map(|l| l.contadores.entrada).sum(),
+            salida: lecturas.iter().map(|l| l.contadores.salida).sum(),
+        };
+        (total, lecturas)
+    }
+}
+
+//sysinfo no da los paquetes descartados; en Linux están en <raiz_red>/<interfaz>/statistics
+fn descartados(raiz_red: &Path, interfaz: &str, contador: &str) -> Option<u64> {
+    let ruta = raiz_red.join(interfaz).join("statistics").join(contador);
+    std::fs::read_to_string(ruta).ok()?.trim().parse().ok()
+}
+
+impl Default for ColectorRed {
+    fn default() -> Self {
+        let config = ConfigRecolector::default();
+        Self::new(config.raiz_red, config.filtro_interfaces)
+    }
+}
+
+impl ColectorBloqueante for ColectorRed {
+    fn nombre(&self) -> &'static str {
+        "network"
+    }
+
+    fn preparar(&mut self) -> Result<(), String> {
+        let (total, lecturas) = self.leer();
+        self.anterior = Some(total);
+        self.anteriores = lecturas.into_iter().map(|l| (l.nombre, l.contadores)).collect();
+        Ok(())
+    }
+
+    fn recolectar(&mut self) -> Result<Seccion, String> {
+        let (actual, lecturas) = self.leer();
+        let (rx_bytes_per_sec, tx_bytes_per_sec) = actual.tasas(self.anterior.replace(actual));
+        let mut anteriores = HashMap::new();
+        let interfaces = lecturas
+            .into_iter()
+            .map(|l| {
+                let (rx_bytes_per_sec, tx_bytes_per_sec) = l.contadores.tasas(self.anteriores.get(&l.nombre).copied());
+                anteriores.insert(l.nombre.clone(), l.contadores);
+                Interfaz {
+                    name: l.nombre,
+                    received_bytes: l.contadores.entrada,
+                    transmitted_bytes: l.contadores.salida,
+                    rx_bytes_per_sec,
+                    tx_bytes_per_sec,
+                    rx_packets: l.paquetes.0,
+                    tx_packets: l.paquetes.1,
+                    rx_errors: l.errores.0,
+                    tx_errors: l.errores.1,
+                    rx_dropped: l.descartados.0,
+                    tx_dropped: l.descartados.1,
+                }
+            })
+            .collect();
+        //Las interfaces que desaparecieron se olvidan
+        self.anteriores = anteriores;
+        let red = Red {
+            received_bytes: actual.entrada,
+            transmitted_bytes: actual.salida,
+            rx_bytes_per_sec,
+            tx_bytes_per_sec,
+            interfaces,
+        };
+        Ok(Seccion::Red(red))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prueba::arbol;
+
+    #[test]
+    fn descartados_de_la_raiz_configurada() {
+        let dir = arbol(&[
+            ("eth0/statistics/rx_dropped", "12\n"),
+            ("eth0/statistics/tx_dropped", "basura\n"),
+        ]);
+        assert_eq!(descartados(dir.path(), "eth0", "rx_dropped"), Some(12));
+        assert_eq!(descartados(dir.path(), "eth0", "tx_dropped"), None);
+        assert_eq!(descartados(dir.path(), "wlan0", "rx_dropped"), None);
+    }
+}
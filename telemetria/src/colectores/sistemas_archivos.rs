@@ -0,0 +1,127 @@
+use sysinfo::Disks;
+use crate::datos::SistemaArchivos;
+use crate::unidades::bytes;
+use super::{ColectorBloqueante, Seccion};
+
+//Sistemas de archivos sin espacio propio que se pueda llenar, o cuyo espacio ya se reporta en otra parte
+const TIPOS_VIRTUALES: &[&str] = &[
+    //Del kernel, sin capacidad
+    "autofs", "binfmt_misc", "bpf", "cgroup", "cgroup2", "configfs", "debugfs", "devpts", "efivarfs", "fusectl",
+    "mqueue", "nsfs", "proc", "pstore", "rpc_pipefs", "securityfs", "selinuxfs", "sysfs", "tracefs",
+    //En memoria: lo que ocupan ya está en la sección de memoria (Shmem)
+    "devtmpfs", "hugetlbfs", "ramfs", "tmpfs",
+    //La capa de escritura de overlay está en otro sistema de archivos que ya aparece, y squashfs es una
+    //imagen de solo lectura (snaps, live CD) que siempre está llena
+    "overlay", "squashfs",
+];
+
+fn es_virtual(tipo: &str) -> bool {
+    TIPOS_VIRTUALES.contains(&tipo)
+}
+
+pub struct ColectorSistemasArchivos {
+    disks: Disks,
+}
+
+impl ColectorSistemasArchivos {
+    pub fn new() -> ColectorSistemasArchivos {
+        ColectorSistemasArchivos { disks: Disks::new() }
+    }
+}
+
+impl Default for ColectorSistemasArchivos {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ColectorBloqueante for ColectorSistemasArchivos {
+    fn nombre(&self) -> &'static str {
+        "filesystems"
+    }
+
+    fn recolectar(&mut self) -> Result<Seccion, String> {
+        //La lista se vuelve a leer en cada muestra por si se montó o desmontó algo
+        self.disks.refresh_list();
+        let sistemas = self
+            .disks
+            .iter()
+            .filter(|disk| !es_virtual(&disk.file_system().to_string_lossy()))
+            .map(|disk| {
+                let capacidad = capacidad(disk.mount_point()).unwrap_or(Capacidad {
+                    total: disk.total_space(),
+                    usado: disk.total_space().saturating_sub(disk.available_space()),
+                    disponible: disk.available_space(),
+                    inodos: None,
+                });
+                SistemaArchivos {
+                    device: disk.name().to_string_lossy().into_owned(),
+                    mount_point: disk.mount_point().to_string_lossy().into_owned(),
+                    fs_type: disk.file_system().to_string_lossy().into_owned(),
+                    total_bytes: bytes(capacidad.total),
+                    used_bytes: bytes(capacidad.usado),
+                    available_bytes: bytes(capacidad.disponible),
+                    inodes_total: capacidad.inodos.map(|(total, _)| total),
+                    inodes_used: capacidad.inodos.map(|(total, libres)| total.saturating_sub(libres)),
+                    inodes_free: capacidad.inodos.map(|(_, libres)| libres),
+                }
+            })
+            .collect();
+        Ok(Seccion::SistemasArchivos(sistemas))
+    }
+}
+
+//En bytes; usado + disponible puede ser menor que el total por los bloques reservados para root
+struct Capacidad {
+    total: u64,
+    usado: u64,
+    disponible: u64,
+    //(total, libres)
+    inodos: Option<(u64, u64)>,
+}
+
+//sysinfo no da los inodos ni el espacio usado real, así que en Unix se pregunta directo con statvfs
+#[cfg(unix)]
+#[allow(clippy::useless_conversion)] //u64::from solo es inútil donde los campos ya son u64
+fn capacidad(punto_montaje: &std::path::Path) -> Option<Capacidad> {
+    use std::os::unix::ffi::OsStrExt;
+    let ruta = std::ffi::CString::new(punto_montaje.as_os_str().as_bytes()).ok()?;
+    let mut datos = std::mem::MaybeUninit::<libc::statvfs>::zeroed();
+    //SAFETY: `ruta` termina en nulo y statvfs solo escribe en la estructura que se le pasa
+    if unsafe { libc::statvfs(ruta.as_ptr(), datos.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    //SAFETY: statvfs devolvió 0, así que llenó la estructura
+    let datos = unsafe { datos.assume_init() };
+    //Los campos son de 32 o 64 bits según la plataforma (en Linux de 64 son u64)
+    let bloque = u64::from(datos.f_frsize);
+    let (bloques, libres, disponibles) = (u64::from(datos.f_blocks), u64::from(datos.f_bfree), u64::from(datos.f_bavail));
+    let (inodos, inodos_libres) = (u64::from(datos.f_files), u64::from(datos.f_ffree));
+    Some(Capacidad {
+        total: bloques * bloque,
+        usado: bloques.saturating_sub(libres) * bloque,
+        disponible: disponibles * bloque,
+        //Algunos sistemas (como FAT o btrfs) no tienen un número fijo de inodos y reportan 0
+        inodos: (inodos > 0).then_some((inodos, inodos_libres)),
+    })
+}
+
+#[cfg(not(unix))]
+fn capacidad(_punto_montaje: &std::path::Path) -> Option<Capacidad> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solo_los_que_ocupan_un_disco() {
+        for tipo in ["ext4", "xfs", "btrfs", "zfs", "vfat", "ntfs3", "nfs4", "fuseblk"] {
+            assert!(!es_virtual(tipo), "{}", tipo);
+        }
+        for tipo in ["proc", "sysfs", "cgroup2", "tmpfs", "ramfs", "devtmpfs", "overlay", "squashfs"] {
+            assert!(es_virtual(tipo), "{}", tipo);
+        }
+    }
+}
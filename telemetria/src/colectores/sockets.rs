@@ -0,0 +1,348 @@
+//Sockets TCP y UDP de /proc/net/{tcp,tcp6,udp,udp6} (solo Linux): conexiones por estado, puertos en escucha
+//con el proceso dueño y las direcciones remotas con más conexiones
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::PathBuf;
+use crate::datos::{Par, PuertoEscucha, Sockets};
+use super::{ColectorBloqueante, Seccion};
+
+//Cuántas direcciones remotas entran a top_peers
+const TOP_PARES: usize = 10;
+
+//Estado de include/net/tcp_states.h
+const TCP_LISTEN: u8 = 0x0A;
+//Un socket UDP sin connect() queda en TCP_CLOSE
+const UDP_SIN_CONECTAR: u8 = 0x07;
+
+pub struct ColectorSockets {
+    raiz: PathBuf,
+}
+
+//Una línea de /proc/net/tcp* o udp*
+struct Socket {
+    local: (IpAddr, u16),
+    remota: (IpAddr, u16),
+    estado: u8,
+    inodo: u64,
+}
+
+impl ColectorSockets {
+    pub fn new(raiz: PathBuf) -> ColectorSockets {
+        ColectorSockets { raiz }
+    }
+
+    //Sin IPv6 en el kernel no existen tcp6 ni udp6, y eso no es un error
+    fn leer(&self, archivo: &str, opcional: bool) -> Result<Vec<Socket>, String> {
+        let ruta = self.raiz.join("net").join(archivo);
+        let texto = match fs::read_to_string(&ruta) {
+            Ok(texto) => texto,
+            Err(e) if opcional && e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("{}: {}", ruta.display(), e)),
+        };
+        //La primera línea son los encabezados
+        Ok(texto.lines().skip(1).filter_map(socket_de_linea).collect())
+    }
+
+    //Busca en /proc/<pid>/fd de qué proceso es cada inodo; deja de buscar cuando ya los encontró todos
+    fn duenos(&self, inodos: &HashSet<u64>) -> HashMap<u64, u32> {
+        let mut duenos = HashMap::new();
+        let Ok(entradas) = fs::read_dir(&self.raiz) else {
+            return duenos;
+        };
+        for entrada in entradas.flatten() {
+            let Some(pid) = entrada.file_name().to_str().and_then(|n| n.parse::<u32>().ok()) else {
+                continue;
+            };
+            //Sin permiso para leer los fd de otros usuarios simplemente no se encuentran
+            let Ok(descriptores) = fs::read_dir(entrada.path().join("fd")) else {
+                continue;
+            };
+            for descriptor in descriptores.flatten() {
+                let Ok(destino) = fs::read_link(descriptor.path()) else {
+                    continue;
+                };
+                //"socket:[12345]"
+                let inodo = destino
+                    .to_str()
+                    .and_then(|d| d.strip_prefix("socket:[")?.strip_suffix(']')?.parse::<u64>().ok());
+                if let Some(inodo) = inodo.filter(|i| inodos.contains(i)) {
+                    duenos.entry(inodo).or_insert(pid);
+                }
+            }
+            if duenos.len() == inodos.len() {
+                break;
+            }
+        }
+        duenos
+    }
+
+    fn nombre_proceso(&self, pid: u32) -> Option<String> {
+        let nombre = fs::read_to_string(self.raiz.join(pid.to_string()).join("comm")).ok()?;
+        Some(nombre.trim_end().to_string())
+    }
+
+    fn sockets(&self) -> Result<Sockets, String> {
+        let mut tcp = self.leer("tcp", false)?;
+        tcp.extend(self.leer("tcp6", true)?);
+        let mut udp = self.leer("udp", false)?;
+        udp.extend(self.leer("udp6", true)?);
+
+        let mut tcp_states = BTreeMap::new();
+        let mut pares: HashMap<IpAddr, u64> = HashMap::new();
+        for socket in &tcp {
+            *tcp_states.entry(nombre_estado(socket.estado).to_string()).or_insert(0) += 1;
+            if socket.estado != TCP_LISTEN && !socket.remota.0.is_unspecified() {
+                *pares.entry(socket.remota.0).or_insert(0) += 1;
+            }
+        }
+        let mut top_peers: Vec<Par> = pares
+            .into_iter()
+            .map(|(direccion, connections)| Par { address: direccion.to_string(), connections })
+            .collect();
+        top_peers.sort_by(|a, b| b.connections.cmp(&a.connections).then_with(|| a.address.cmp(&b.address)));
+        top_peers.truncate(TOP_PARES);
+
+        let escuchando: Vec<(&str, &Socket)> = tcp
+            .iter()
+            .filter(|s| s.estado == TCP_LISTEN)
+            .map(|s| ("tcp", s))
+            .chain(udp.iter().filter(|s| s.estado == UDP_SIN_CONECTAR && s.remota.1 == 0).map(|s| ("udp", s)))
+            .collect();
+        let duenos = self.duenos(&escuchando.iter().map(|(_, s)| s.inodo).collect());
+        let mut listening: Vec<PuertoEscucha> = Vec::new();
+        let mut vistos = HashSet::new();
+        for (protocolo, socket) in escuchando {
+            //Con SO_REUSEPORT varios sockets comparten dirección y puerto; basta con uno
+            if !vistos.insert((protocolo, socket.local)) {
+                continue;
+            }
+            let pid = duenos.get(&socket.inodo).copied();
+            listening.push(PuertoEscucha {
+                protocol: protocolo.to_string(),
+                address: socket.local.0.to_string(),
+                port: socket.local.1,
+                pid,
+                process: pid.and_then(|pid| self.nombre_proceso(pid)),
+            });
+        }
+        listening.sort_by(|a, b| (&a.protocol, a.port, &a.address).cmp(&(&b.protocol, b.port, &b.address)));
+
+        Ok(Sockets { tcp_states, udp_sockets: udp.len() as u64, listening, top_peers })
+    }
+}
+
+//"   1: 0100007F:0035 00000000:0000 0A 00000000:00000000 00:00000000 00000000   101        0 17855 1 ..."
+fn socket_de_linea(linea: &str) -> Option<Socket> {
+    let campos: Vec<&str> = linea.split_whitespace().collect();
+    Some(Socket {
+        local: direccion(campos.get(1)?)?,
+        remota: direccion(campos.get(2)?)?,
+        estado: u8::from_str_radix(campos.get(3)?, 16).ok()?,
+        inodo: campos.get(9)?.parse().ok()?,
+    })
+}
+
+//La IP son palabras de 32 bits en hexadecimal con el orden de bytes del equipo; el puerto va en hexadecimal normal
+fn direccion(texto: &str) -> Option<(IpAddr, u16)> {
+    let (ip, puerto) = texto.split_once(':')?;
+    let puerto = u16::from_str_radix(puerto, 16).ok()?;
+    let palabra = |i: usize| u32::from_str_radix(ip.get(i * 8..i * 8 + 8)?, 16).ok().map(u32::to_ne_bytes);
+    let ip = match ip.len() {
+        8 => IpAddr::V4(Ipv4Addr::from(palabra(0)?)),
+        32 => {
+            let mut bytes = [0u8; 16];
+            for i in 0..4 {
+                bytes[i * 4..i * 4 + 4].copy_from_slice(&palabra(i)?);
+            }
+            //Las conexiones IPv4 a un socket IPv6 llegan como ::ffff:a.b.c.d
+            IpAddr::V6(Ipv6Addr::from(bytes)).to_canonical()
+        }
+        _ => return None,
+    };
+    Some((ip, puerto))
+}
+
+fn nombre_estado(estado: u8) -> &'static str {
+    match estado {
+        0x01 => "ESTABLISHED",
+        0x02 => "SYN_SENT",
+        0x03 => "SYN_RECV",
+        0x04 => "FIN_WAIT1",
+        0x05 => "FIN_WAIT2",
+        0x06 => "TIME_WAIT",
+        0x07 => "CLOSE",
+        0x08 => "CLOSE_WAIT",
+        0x09 => "LAST_ACK",
+        0x0A => "LISTEN",
+        0x0B => "CLOSING",
+        0x0C => "NEW_SYN_RECV",
+        _ => "UNKNOWN",
+    }
+}
+
+impl ColectorBloqueante for ColectorSockets {
+    fn nombre(&self) -> &'static str {
+        "sockets"
+    }
+
+    fn recolectar(&mut self) -> Result<Seccion, String> {
+        self.sockets().map(Seccion::Sockets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prueba::arbol;
+
+    //Como las escribe el kernel: cada palabra de 32 bits de la IP con el orden de bytes del equipo
+    fn hex(ip: &str, puerto: u16) -> String {
+        let palabras: Vec<String> = match ip.parse::<IpAddr>().unwrap() {
+            IpAddr::V4(ip) => vec![format!("{:08X}", u32::from_ne_bytes(ip.octets()))],
+            IpAddr::V6(ip) => {
+                ip.octets().chunks(4).map(|p| format!("{:08X}", u32::from_ne_bytes(p.try_into().unwrap()))).collect()
+            }
+        };
+        format!("{}:{:04X}", palabras.concat(), puerto)
+    }
+
+    fn linea(local: (&str, u16), remota: (&str, u16), estado: u8, inodo: u64) -> String {
+        format!(
+            "   0: {} {} {:02X} 00000000:00000000 00:00000000 00000000     0        0 {} 1 0000000000000000 100 0 0 10 0\n",
+            hex(local.0, local.1),
+            hex(remota.0, remota.1),
+            estado,
+            inodo
+        )
+    }
+
+    const ENCABEZADO: &str = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n";
+
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn direcciones_como_las_escribe_el_kernel() {
+        let ip = |texto: &str| texto.parse::<IpAddr>().unwrap();
+        assert_eq!(direccion("0100007F:0035"), Some((ip("127.0.0.1"), 53)));
+        assert_eq!(direccion("00000000000000000000000001000000:0016"), Some((ip("::1"), 22)));
+        assert_eq!(direccion("B80D0120000000000000000001000000:01BB"), Some((ip("2001:db8::1"), 443)));
+        //IPv4 sobre un socket IPv6
+        assert_eq!(direccion("0000000000000000FFFF00000100007F:0050"), Some((ip("127.0.0.1"), 80)));
+    }
+
+    #[test]
+    fn direcciones_con_el_orden_de_bytes_del_equipo() {
+        for (ip, puerto) in [("192.0.2.1", 50000), ("::", 0), ("fe80::1:2:3", 8080), ("2001:db8::ff00:42:8329", 1)] {
+            assert_eq!(direccion(&hex(ip, puerto)), Some((ip.parse().unwrap(), puerto)));
+        }
+        assert_eq!(direccion(&hex("::ffff:10.0.0.2", 80)), Some(("10.0.0.2".parse().unwrap(), 80)));
+        assert_eq!(direccion("0100007F"), None);
+        assert_eq!(direccion("0100007F:XYZ"), None);
+        assert_eq!(direccion("0100007F00:0035"), None);
+    }
+
+    #[test]
+    fn lineas_de_proc_net() {
+        let socket = socket_de_linea(&linea(("10.0.0.2", 22), ("192.0.2.1", 50000), 0x01, 2001)).unwrap();
+        assert_eq!(socket.local, ("10.0.0.2".parse().unwrap(), 22));
+        assert_eq!(socket.remota, ("192.0.2.1".parse().unwrap(), 50000));
+        assert_eq!(socket.estado, 0x01);
+        assert_eq!(socket.inodo, 2001);
+        assert!(socket_de_linea(ENCABEZADO).is_none());
+        assert!(socket_de_linea("   0: 0100007F:0035 00000000:0000 0A").is_none());
+        assert_eq!(nombre_estado(0x01), "ESTABLISHED");
+        assert_eq!(nombre_estado(0x06), "TIME_WAIT");
+        assert_eq!(nombre_estado(TCP_LISTEN), "LISTEN");
+        assert_eq!(nombre_estado(0x0C), "NEW_SYN_RECV");
+        assert_eq!(nombre_estado(0x0D), "UNKNOWN");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn estados_puertos_en_escucha_y_pares() {
+        let mut tcp = ENCABEZADO.to_string();
+        tcp += &linea(("127.0.0.1", 53), ("0.0.0.0", 0), TCP_LISTEN, 1001);
+        tcp += &linea(("0.0.0.0", 22), ("0.0.0.0", 0), TCP_LISTEN, 1002);
+        //El mismo puerto con SO_REUSEPORT
+        tcp += &linea(("0.0.0.0", 22), ("0.0.0.0", 0), TCP_LISTEN, 1005);
+        tcp += &linea(("10.0.0.2", 22), ("192.0.2.1", 50000), 0x01, 2001);
+        tcp += &linea(("10.0.0.2", 22), ("192.0.2.1", 50001), 0x06, 0);
+        tcp += &linea(("10.0.0.2", 22), ("198.51.100.7", 443), 0x01, 2002);
+        for i in 1..=12 {
+            tcp += &linea(("10.0.0.2", 22), (&format!("10.0.0.{}", i + 100), 40000), 0x01, 3000 + i);
+        }
+        tcp += "línea ilegible\n";
+        let mut tcp6 = ENCABEZADO.to_string();
+        tcp6 += &linea(("::", 80), ("::", 0), TCP_LISTEN, 1003);
+        tcp6 += &linea(("::ffff:10.0.0.2", 80), ("::ffff:192.0.2.1", 40000), 0x01, 2003);
+        tcp6 += &linea(("2001:db8::2", 80), ("2001:db8::1", 443), 0x01, 2004);
+        tcp6 += &linea(("2001:db8::2", 80), ("2001:db8::1", 444), 0x01, 2005);
+        let mut udp = ENCABEZADO.to_string();
+        udp += &linea(("0.0.0.0", 68), ("0.0.0.0", 0), UDP_SIN_CONECTAR, 1004);
+        udp += &linea(("10.0.0.2", 41000), ("8.8.8.8", 53), 0x01, 2006);
+        //Sin udp6, como en un kernel sin IPv6
+        let dir = arbol(&[
+            ("net/tcp", &tcp),
+            ("net/tcp6", &tcp6),
+            ("net/udp", &udp),
+            ("100/comm", "sshd\n"),
+            ("100/fd/", ""),
+            ("200/fd/", ""),
+            ("self/fd/", ""),
+        ]);
+        let enlace = |destino: &str, descriptor: &str| std::os::unix::fs::symlink(destino, dir.path().join(descriptor)).unwrap();
+        enlace("/dev/null", "100/fd/0");
+        enlace("socket:[1002]", "100/fd/3");
+        enlace("socket:[1004]", "100/fd/4");
+        enlace("socket:[1001]", "200/fd/5");
+        enlace("socket:[1003]", "self/fd/6");
+
+        let sockets = ColectorSockets::new(dir.path().to_path_buf()).sockets().unwrap();
+        let estados: Vec<(&str, u64)> = sockets.tcp_states.iter().map(|(e, n)| (e.as_str(), *n)).collect();
+        assert_eq!(estados, [("ESTABLISHED", 17), ("LISTEN", 4), ("TIME_WAIT", 1)]);
+        assert_eq!(sockets.udp_sockets, 2);
+
+        let escuchando: Vec<String> = sockets
+            .listening
+            .iter()
+            .map(|p| format!("{} {} {} {:?} {:?}", p.protocol, p.address, p.port, p.pid, p.process))
+            .collect();
+        assert_eq!(
+            escuchando,
+            [
+                "tcp 0.0.0.0 22 Some(100) Some(\"sshd\")",
+                //El proceso no tiene comm legible
+                "tcp 127.0.0.1 53 Some(200) None",
+                //"self" no es un pid
+                "tcp :: 80 None None",
+                "udp 0.0.0.0 68 Some(100) Some(\"sshd\")",
+            ]
+        );
+
+        //Las conexiones IPv4 sobre IPv6 cuentan con las IPv4; los empates van por dirección y solo entran 10
+        let pares: Vec<(&str, u64)> = sockets.top_peers.iter().map(|p| (p.address.as_str(), p.connections)).collect();
+        assert_eq!(
+            pares,
+            [
+                ("192.0.2.1", 3),
+                ("2001:db8::1", 2),
+                ("10.0.0.101", 1),
+                ("10.0.0.102", 1),
+                ("10.0.0.103", 1),
+                ("10.0.0.104", 1),
+                ("10.0.0.105", 1),
+                ("10.0.0.106", 1),
+                ("10.0.0.107", 1),
+                ("10.0.0.108", 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn sin_tcp_es_un_error() {
+        let dir = arbol(&[("net/udp", ENCABEZADO)]);
+        let error = ColectorSockets::new(dir.path().to_path_buf()).sockets().unwrap_err();
+        assert!(error.contains("tcp"), "{}", error);
+    }
+}
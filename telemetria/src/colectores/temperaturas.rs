@@ -0,0 +1,257 @@
+//Sensores de temperatura: en Linux de <raiz>/hwmonN/temp*_input (la raíz es configurable para leer una de prueba),
+//y si ahí no hay ninguno, o en otros sistemas, de los Components de sysinfo
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use chrono::Utc;
+use sysinfo::Components;
+use crate::config::ConfigRecolector;
+use crate::datos::{rfc3339, Sensor};
+use crate::eventos::Evento;
+use super::{ColectorBloqueante, Seccion};
+
+pub struct ColectorTemperaturas {
+    components: Components,
+    raiz_hwmon: PathBuf,
+    //Sensores que ya estaban en su temperatura crítica en la lectura anterior
+    criticos: HashSet<String>,
+    eventos: Vec<Evento>,
+}
+
+impl ColectorTemperaturas {
+    pub fn new(config: &ConfigRecolector) -> ColectorTemperaturas {
+        ColectorTemperaturas {
+            //La lista se arma en la primera recolección, y solo si hwmon no tiene sensores
+            components: Components::new(),
+            raiz_hwmon: config.raiz_hwmon.clone(),
+            criticos: HashSet::new(),
+            eventos: Vec::new(),
+        }
+    }
+
+    fn de_sysinfo(&mut self) -> Vec<Sensor> {
+        if self.components.is_empty() {
+            self.components.refresh_list();
+        } else {
+            self.components.refresh();
+        }
+        self.components
+            .iter()
+            .map(|c| Sensor {
+                label: c.label().to_string(),
+                temperature_celsius: c.temperature(),
+                max_celsius: Some(c.max()),
+                critical_celsius: c.critical(),
+            })
+            .collect()
+    }
+
+    //Avisa de los sensores que pasaron de estar por debajo de su temperatura crítica a estar en ella o arriba
+    fn revisar_criticos(&mut self, sensores: &[Sensor]) {
+        let timestamp = rfc3339(Utc::now());
+        let mut criticos = HashSet::new();
+        for sensor in sensores {
+            let Some(critica) = sensor.critical_celsius.filter(|c| *c > 0.0) else {
+                continue;
+            };
+            if sensor.temperature_celsius < critica {
+                continue;
+            }
+            if !self.criticos.contains(&sensor.label) {
+                self.eventos.push(Evento::ThermalWarning {
+                    timestamp: timestamp.clone(),
+                    sensor: sensor.label.clone(),
+                    temperature_celsius: sensor.temperature_celsius,
+                    critical_celsius: critica,
+                });
+            }
+            criticos.insert(sensor.label.clone());
+        }
+        self.criticos = criticos;
+    }
+}
+
+impl Default for ColectorTemperaturas {
+    fn default() -> Self {
+        Self::new(&ConfigRecolector::default())
+    }
+}
+
+//Cada hwmonN tiene su `name` (coretemp, nvme, amdgpu...) y por cada sensor tempK_input en miligrados, con
+//tempK_label, tempK_max y tempK_crit opcionales. La etiqueta es "<name> <tempK_label>", como la arma sysinfo;
+//si hay varios chips con el mismo `name` (dos NVMe, dos GPU) se agrega el dispositivo, "<name>@<device> <tempK_label>",
+//para que sus sensores no se confundan en los avisos ni en las series de Prometheus e InfluxDB
+fn de_hwmon(raiz: &Path) -> Vec<Sensor> {
+    let Ok(entradas) = fs::read_dir(raiz) else {
+        return Vec::new();
+    };
+    let mut chips: Vec<PathBuf> = entradas.flatten().map(|e| e.path()).collect();
+    //hwmon10 después de hwmon9
+    chips.sort_by_key(|ruta| numero_de(ruta, "hwmon"));
+    let leer = |chip: &Path, archivo: &str| fs::read_to_string(chip.join(archivo)).ok().map(|t| t.trim().to_string());
+    let chips: Vec<(PathBuf, String)> = chips
+        .into_iter()
+        .map(|chip| {
+            let nombre = leer(&chip, "name").unwrap_or_else(|| nombre_de_archivo(&chip));
+            (chip, nombre)
+        })
+        .collect();
+    let mut sensores = Vec::new();
+    for (chip, nombre) in &chips {
+        let miligrados = |archivo: &str| leer(chip, archivo)?.parse::<f32>().ok().map(|m| m / 1000.0);
+        let repetido = chips.iter().filter(|(_, otro)| otro == nombre).count() > 1;
+        let nombre = if repetido { format!("{}@{}", nombre, dispositivo(chip)) } else { nombre.clone() };
+        let Ok(archivos) = fs::read_dir(chip) else {
+            continue;
+        };
+        let mut entradas: Vec<u32> = archivos
+            .flatten()
+            .filter_map(|a| {
+                let archivo = a.file_name().to_string_lossy().into_owned();
+                archivo.strip_prefix("temp")?.strip_suffix("_input")?.parse().ok()
+            })
+            .collect();
+        entradas.sort();
+        for k in entradas {
+            //Un sensor desconectado da error al leerlo
+            let Some(temperatura) = miligrados(&format!("temp{}_input", k)) else {
+                continue;
+            };
+            let etiqueta = leer(chip, &format!("temp{}_label", k)).unwrap_or_else(|| format!("temp{}", k));
+            sensores.push(Sensor {
+                label: format!("{} {}", nombre, etiqueta),
+                temperature_celsius: temperatura,
+                max_celsius: miligrados(&format!("temp{}_max", k)),
+                critical_celsius: miligrados(&format!("temp{}_crit", k)),
+            });
+        }
+    }
+    sensores
+}
+
+//A dónde apunta el enlace `device` del chip (como "0000:01:00.0" o "nvme0"), que no cambia entre arranques
+//como la numeración de hwmonN; sin el enlace, el nombre de la carpeta
+fn dispositivo(chip: &Path) -> String {
+    fs::read_link(chip.join("device")).map(|destino| nombre_de_archivo(&destino)).unwrap_or_else(|_| nombre_de_archivo(chip))
+}
+
+fn nombre_de_archivo(ruta: &Path) -> String {
+    ruta.file_name().unwrap_or_default().to_string_lossy().into_owned()
+}
+
+fn numero_de(ruta: &Path, prefijo: &str) -> u32 {
+    ruta.file_name()
+        .and_then(|n| n.to_str()?.strip_prefix(prefijo)?.parse().ok())
+        .unwrap_or(u32::MAX)
+}
+
+impl ColectorBloqueante for ColectorTemperaturas {
+    fn nombre(&self) -> &'static str {
+        "temperatures"
+    }
+
+    fn recolectar(&mut self) -> Result<Seccion, String> {
+        let mut sensores = if cfg!(target_os = "linux") { de_hwmon(&self.raiz_hwmon) } else { Vec::new() };
+        if sensores.is_empty() {
+            sensores = self.de_sysinfo();
+        }
+        self.revisar_criticos(&sensores);
+        Ok(Seccion::Temperaturas(sensores))
+    }
+
+    fn eventos(&mut self) -> Vec<Evento> {
+        std::mem::take(&mut self.eventos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prueba::{arbol, escribir};
+
+    fn colector(raiz: &Path) -> ColectorTemperaturas {
+        ColectorTemperaturas::new(&ConfigRecolector { raiz_hwmon: raiz.to_path_buf(), ..ConfigRecolector::default() })
+    }
+
+    fn etiquetas(sensores: &[Sensor]) -> Vec<&str> {
+        sensores.iter().map(|s| s.label.as_str()).collect()
+    }
+
+    #[test]
+    fn los_archivos_opcionales_pueden_faltar() {
+        let dir = arbol(&[
+            ("hwmon0/name", "coretemp\n"),
+            ("hwmon0/temp1_input", "45500\n"),
+            ("hwmon0/temp1_label", "Package id 0\n"),
+            ("hwmon0/temp1_max", "80000\n"),
+            ("hwmon0/temp1_crit", "100000\n"),
+            //Sin etiqueta, máxima ni crítica
+            ("hwmon0/temp2_input", "40000\n"),
+            //Desconectado
+            ("hwmon0/temp3_input", ""),
+            //Sin `name`
+            ("hwmon10/temp1_input", "30000\n"),
+        ]);
+        let sensores = de_hwmon(dir.path());
+        assert_eq!(etiquetas(&sensores), ["coretemp Package id 0", "coretemp temp2", "hwmon10 temp1"]);
+        assert_eq!(sensores[0].temperature_celsius, 45.5);
+        assert_eq!(sensores[0].max_celsius, Some(80.0));
+        assert_eq!(sensores[0].critical_celsius, Some(100.0));
+        assert_eq!(sensores[1].temperature_celsius, 40.0);
+        assert_eq!(sensores[1].max_celsius, None);
+        assert_eq!(sensores[1].critical_celsius, None);
+        assert!(de_hwmon(&dir.path().join("no_existe")).is_empty());
+    }
+
+    #[test]
+    fn los_chips_con_el_mismo_nombre_se_distinguen() {
+        let dir = arbol(&[
+            ("hwmon1/name", "nvme\n"),
+            ("hwmon1/temp1_input", "35000\n"),
+            ("hwmon1/temp1_label", "Composite\n"),
+            ("hwmon2/name", "nvme\n"),
+            ("hwmon2/temp1_input", "50000\n"),
+            ("hwmon2/temp1_label", "Composite\n"),
+            ("hwmon3/name", "coretemp\n"),
+            ("hwmon3/temp1_input", "45000\n"),
+        ]);
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("../../nvme1", dir.path().join("hwmon2/device")).unwrap();
+        let esperado = if cfg!(unix) { "nvme@nvme1 Composite" } else { "nvme@hwmon2 Composite" };
+        assert_eq!(etiquetas(&de_hwmon(dir.path())), ["nvme@hwmon1 Composite", esperado, "coretemp temp1"]);
+    }
+
+    #[test]
+    fn aviso_al_llegar_a_la_temperatura_critica() {
+        let dir = arbol(&[
+            ("hwmon0/name", "nvme\n"),
+            ("hwmon0/temp1_input", "60000\n"),
+            ("hwmon0/temp1_crit", "80000\n"),
+            ("hwmon1/name", "nvme\n"),
+            ("hwmon1/temp1_input", "90000\n"),
+            ("hwmon1/temp1_crit", "80000\n"),
+        ]);
+        let mut colector = colector(dir.path());
+        let avisos = |colector: &mut ColectorTemperaturas| -> Vec<String> {
+            colector.recolectar().unwrap();
+            colector
+                .eventos()
+                .into_iter()
+                .map(|e| match e {
+                    Evento::ThermalWarning { sensor, .. } => sensor,
+                    otro => panic!("evento inesperado: {:?}", otro),
+                })
+                .collect()
+        };
+        //Ya estaba en la crítica en la primera lectura; el otro chip con el mismo nombre no cuenta
+        assert_eq!(avisos(&mut colector), ["nvme@hwmon1 temp1"]);
+        //Sigue ahí: no se repite
+        assert!(avisos(&mut colector).is_empty());
+        //Baja y vuelve a llegar
+        escribir(dir.path(), "hwmon1/temp1_input", "70000\n");
+        assert!(avisos(&mut colector).is_empty());
+        escribir(dir.path(), "hwmon1/temp1_input", "80000\n");
+        escribir(dir.path(), "hwmon0/temp1_input", "85000\n");
+        assert_eq!(avisos(&mut colector), ["nvme@hwmon0 temp1", "nvme@hwmon1 temp1"]);
+    }
+}
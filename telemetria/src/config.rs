@@ -0,0 +1,258 @@
+//Configuración de la recolección: qué colectores corren y cómo se arma cada muestra
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, FixedOffset, Local, Utc};
+use serde::Deserialize;
+
+//Tiempo máximo que se espera a un colector antes de dejar su sección fuera de la muestra
+const LIMITE_POR_DEFECTO: Duration = Duration::from_secs(10);
+
+//Configuración de un colector
+#[derive(Clone, Debug)]
+pub struct ConfigColector {
+    pub habilitado: bool,
+    //Cada cuánto se ejecuta; None = en cada muestra
+    pub intervalo: Option<Duration>,
+    pub limite: Duration,
+}
+
+impl Default for ConfigColector {
+    fn default() -> Self {
+        ConfigColector {
+            habilitado: true,
+            intervalo: None,
+            limite: LIMITE_POR_DEFECTO,
+        }
+    }
+}
+
+//Criterio de cada top de procesos; cada uno va en su propio campo de la muestra
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OrdenProcesos {
+    Cpu,
+    Memory,
+    DiskIo,
+}
+
+impl FromStr for OrdenProcesos {
+    type Err = String;
+
+    fn from_str(texto: &str) -> Result<Self, Self::Err> {
+        match texto {
+            "cpu" => Ok(OrdenProcesos::Cpu),
+            "memory" => Ok(OrdenProcesos::Memory),
+            "disk_io" => Ok(OrdenProcesos::DiskIo),
+            otro => Err(format!("orden de procesos inválido: {} (cpu, memory o disk_io)", otro)),
+        }
+    }
+}
+
+//Zona horaria del timestamp: la del sistema, UTC o un desfase fijo como "-06:00"
+#[derive(Clone, Copy, Debug)]
+pub enum ZonaHoraria {
+    Local,
+    Utc,
+    Fija(FixedOffset),
+}
+
+impl FromStr for ZonaHoraria {
+    type Err = String;
+
+    fn from_str(texto: &str) -> Result<Self, Self::Err> {
+        match texto.to_ascii_lowercase().as_str() {
+            "local" => Ok(ZonaHoraria::Local),
+            "utc" | "z" => Ok(ZonaHoraria::Utc),
+            _ => FixedOffset::from_str(texto)
+                .map(ZonaHoraria::Fija)
+                .map_err(|_| format!("zona horaria inválida: {} (local, utc o un desfase como -06:00)", texto)),
+        }
+    }
+}
+
+//Filtro por nombre (de interfaz o de dispositivo) con comodines * y ?; si hay patrones de inclusión
+//solo pasa lo que coincide con alguno, y lo que coincide con uno de exclusión nunca pasa
+#[derive(Clone, Debug, Default)]
+pub struct Filtro {
+    pub incluir: Vec<String>,
+    pub excluir: Vec<String>,
+}
+
+impl Filtro {
+    pub fn excluyendo(patrones: &[&str]) -> Filtro {
+        Filtro { incluir: Vec::new(), excluir: patrones.iter().map(|p| p.to_string()).collect() }
+    }
+
+    pub fn permite(&self, nombre: &str) -> bool {
+        (self.incluir.is_empty() || self.incluir.iter().any(|p| coincide(p, nombre)))
+            && !self.excluir.iter().any(|p| coincide(p, nombre))
+    }
+}
+
+//Comodines de shell: * es cualquier cadena (también vacía) y ? un solo carácter
+fn coincide(patron: &str, nombre: &str) -> bool {
+    let patron: Vec<char> = patron.chars().collect();
+    let nombre: Vec<char> = nombre.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    //Última * vista y la posición del nombre desde la que se está probando
+    let mut estrella: Option<(usize, usize)> = None;
+    while n < nombre.len() {
+        match patron.get(p) {
+            Some('*') => {
+                estrella = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == nombre[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match estrella {
+                //La * se come un carácter más y se vuelve a probar
+                Some((pe, ne)) => {
+                    estrella = Some((pe, ne + 1));
+                    p = pe + 1;
+                    n = ne + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    patron[p..].iter().all(|&c| c == '*')
+}
+
+#[derive(Clone, Debug)]
+pub struct ConfigRecolector {
+    //Por nombre de colector (cpu, load, memory, network, sockets, disk, diskstats, filesystems, temperatures, processes, cgroups, self_overhead)
+    pub colectores: HashMap<String, ConfigColector>,
+    //Cuántos procesos entran a cada top y qué tops se arman
+    pub top_procesos: usize,
+    pub listas_procesos: Vec<OrdenProcesos>,
+    //Nombres de procesos que no entran al top, además del propio recolector (sin distinguir mayúsculas ni ".exe")
+    pub procesos_ignorados: Vec<String>,
+    //Qué interfaces de red y qué discos entran a su sección (y a sus totales)
+    pub filtro_interfaces: Filtro,
+    pub filtro_discos: Filtro,
+    //Formato de chrono (strftime) y zona del campo local_time de las muestras; timestamp siempre es RFC 3339 en UTC
+    pub formato_timestamp: String,
+    pub zona_horaria: ZonaHoraria,
+    //Etiqueta libre del equipo que se copia en cada muestra
+    pub host_label: Option<String>,
+    //De dónde leen los colectores de Linux que usan /proc
+    pub raiz_proc: PathBuf,
+    //Raíz de la jerarquía cgroup v2
+    pub raiz_cgroup: PathBuf,
+    //Dónde están los hwmonN con los sensores (solo Linux)
+    pub raiz_hwmon: PathBuf,
+    //Un directorio por dispositivo de bloque; disk y diskstats lo usan para saber cuáles son particiones (solo Linux)
+    pub raiz_bloques: PathBuf,
+    //Un directorio por interfaz de red; network lee de ahí los paquetes descartados (solo Linux)
+    pub raiz_red: PathBuf,
+}
+
+impl Default for ConfigRecolector {
+    fn default() -> Self {
+        ConfigRecolector {
+            colectores: HashMap::new(),
+            top_procesos: 5,
+            listas_procesos: vec![OrdenProcesos::Cpu, OrdenProcesos::Memory, OrdenProcesos::DiskIo],
+            procesos_ignorados: Vec::new(),
+            //El loopback y los veth de los contenedores duplican tráfico que ya pasa por otra interfaz
+            filtro_interfaces: Filtro::excluyendo(&["lo", "veth*"]),
+            //Dispositivos en memoria, no discos
+            filtro_discos: Filtro::excluyendo(&["loop*", "ram*", "zram*"]),
+            formato_timestamp: "%Y-%m-%d %H:%M:%S".to_string(),
+            zona_horaria: ZonaHoraria::Local,
+            host_label: None,
+            raiz_proc: PathBuf::from("/proc"),
+            raiz_cgroup: PathBuf::from("/sys/fs/cgroup"),
+            raiz_hwmon: PathBuf::from("/sys/class/hwmon"),
+            raiz_bloques: PathBuf::from("/sys/class/block"),
+            raiz_red: PathBuf::from("/sys/class/net"),
+        }
+    }
+}
+
+impl ConfigRecolector {
+    //Revisa lo que no se puede detectar al leer cada valor, como un formato de fecha inválido
+    pub fn validar(&self) -> Result<(), String> {
+        if StrftimeItems::new(&self.formato_timestamp).any(|item| matches!(item, Item::Error)) {
+            return Err(format!("formato de timestamp inválido: {}", self.formato_timestamp));
+        }
+        Ok(())
+    }
+
+    pub fn timestamp(&self, ahora: DateTime<Utc>) -> String {
+        match self.zona_horaria {
+            ZonaHoraria::Local => ahora.with_timezone(&Local).format(&self.formato_timestamp).to_string(),
+            ZonaHoraria::Utc => ahora.format(&self.formato_timestamp).to_string(),
+            ZonaHoraria::Fija(desfase) => ahora.with_timezone(&desfase).format(&self.formato_timestamp).to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filtro(incluir: &[&str], excluir: &[&str]) -> Filtro {
+        Filtro {
+            incluir: incluir.iter().map(|p| p.to_string()).collect(),
+            excluir: excluir.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn comodines() {
+        assert!(coincide("eth0", "eth0"));
+        assert!(!coincide("eth0", "eth01"));
+        //* al final, al principio y en medio, también vacía
+        assert!(coincide("veth*", "veth1a2b"));
+        assert!(coincide("veth*", "veth"));
+        assert!(!coincide("veth*", "eth0"));
+        assert!(coincide("*p0", "enp0"));
+        assert!(coincide("*p0", "p0"));
+        assert!(!coincide("*p0", "enp0s1"));
+        assert!(coincide("en*s1", "enp0s1"));
+        assert!(coincide("en*s1", "ens1"));
+        assert!(!coincide("en*s1", "enp0s2"));
+        //La * tiene que poder retroceder cuando el resto coincide más de una vez
+        assert!(coincide("*s1", "s1s1"));
+        assert!(coincide("a*b*c", "axxbyybzc"));
+        assert!(coincide("*", ""));
+        assert!(coincide("**", "sda"));
+        //? es exactamente un carácter
+        assert!(coincide("sd?", "sda"));
+        assert!(!coincide("sd?", "sda1"));
+        assert!(!coincide("sd?", "sd"));
+        assert!(coincide("nvme?n?", "nvme0n1"));
+        assert!(coincide("wlán?", "wlán0"));
+        //Un patrón vacío solo coincide con un nombre vacío
+        assert!(coincide("", ""));
+        assert!(!coincide("", "eth0"));
+    }
+
+    #[test]
+    fn inclusion_y_exclusion() {
+        //Sin patrones pasa todo
+        assert!(Filtro::default().permite("lo"));
+        let excluyendo = Filtro::excluyendo(&["lo", "veth*"]);
+        assert!(!excluyendo.permite("lo"));
+        assert!(!excluyendo.permite("veth0"));
+        assert!(excluyendo.permite("eth0"));
+        //Con inclusión solo pasa lo que coincide con alguno
+        let incluyendo = filtro(&["eth*", "wl*"], &[]);
+        assert!(incluyendo.permite("eth0"));
+        assert!(incluyendo.permite("wlan0"));
+        assert!(!incluyendo.permite("docker0"));
+        //La exclusión gana aunque también coincida con una inclusión
+        let ambos = filtro(&["eth*"], &["eth1", "*.100"]);
+        assert!(ambos.permite("eth0"));
+        assert!(!ambos.permite("eth1"));
+        assert!(!ambos.permite("eth0.100"));
+        //Un patrón de inclusión vacío no deja pasar nombres
+        assert!(!filtro(&[""], &[]).permite("eth0"));
+    }
+}
@@ -0,0 +1,543 @@
+//Estructura de cada línea de datosuwu.jsonl
+use std::collections::BTreeMap;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use crate::unidades::{self, Bytes, BytesPorSegundo, Frecuencia};
+
+//Versión del formato; las líneas sin este campo son del formato 1 (cadenas con format! y memoria en KiB etiquetada como MB).
+//Hasta el formato 3 `timestamp` era la hora local con el formato configurado, sin desfase
+pub const SCHEMA_VERSION: u32 = 4;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+//Aquí se define la estructura datosuwu
+pub struct Datosuwu {
+    pub schema_version: u32,
+    //RFC 3339 en UTC, como "2025-04-10T21:08:52.123Z"
+    pub timestamp: String,
+    //La misma hora con el formato y la zona configurados
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_time: Option<String>,
+    //Número de muestra; sigue contando entre ejecuciones mientras se escriba en el mismo archivo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u64>,
+    //Etiqueta del equipo, si se configuró
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host_label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<Equipo>,
+    //Versión de telemetria que tomó la muestra
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collector_version: Option<String>,
+    //Segundos reales entre esta muestra y la anterior, sobre los que se calculan las tasas
+    pub interval_secs: f64,
+    //Cada sección la llena un colector; falta si el colector está deshabilitado o no le tocaba en esta muestra
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<Cpu>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub load: Option<Carga>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<Memoria>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<Red>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sockets: Option<Sockets>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disk: Option<Disco>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disk_stats: Option<Vec<EstadisticasDisco>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filesystems: Option<Vec<SistemaArchivos>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperatures: Option<Vec<Sensor>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_processes: Option<Vec<Proceso>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_processes_by_memory: Option<Vec<Proceso>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_processes_by_disk_io: Option<Vec<Proceso>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub per_user: Option<Vec<Usuario>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cgroups: Option<Vec<Cgroup>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub self_overhead: Option<ConsumoPropio>,
+    //Colectores que fallaron en esta muestra; su sección falta
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<ErrorColector>,
+}
+
+//Identidad del equipo, para poder juntar archivos de varias máquinas; lo que el sistema no da falta
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Equipo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    //De /etc/machine-id (solo Linux); no cambia al reiniciar
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub machine_id: Option<String>,
+    //Cambia en cada arranque (solo Linux); dos muestras con distinto boot_id no se pueden restar
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boot_id: Option<String>,
+    //Como "Linux 22.04 Ubuntu" o "Windows 11 Pro"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub os: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kernel_version: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ErrorColector {
+    pub collector: String,
+    pub message: String,
+}
+
+//Formato de `timestamp` en muestras y eventos y de `start_time` en procesos: RFC 3339 en UTC con milisegundos,
+//"2025-04-10T21:08:52.123Z"
+pub fn rfc3339(momento: DateTime<Utc>) -> String {
+    momento.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+impl Datosuwu {
+    //Muestra sin secciones; los colectores las van llenando
+    pub fn vacia(timestamp: String, interval_secs: f64) -> Datosuwu {
+        Datosuwu {
+            schema_version: SCHEMA_VERSION,
+            timestamp,
+            local_time: None,
+            sequence: None,
+            host_label: None,
+            host: None,
+            collector_version: None,
+            interval_secs,
+            cpu: None,
+            load: None,
+            memory: None,
+            network: None,
+            sockets: None,
+            disk: None,
+            disk_stats: None,
+            filesystems: None,
+            temperatures: None,
+            top_processes: None,
+            top_processes_by_memory: None,
+            top_processes_by_disk_io: None,
+            per_user: None,
+            cgroups: None,
+            self_overhead: None,
+            errors: Vec::new(),
+        }
+    }
+
+    //Una muestra es parcial si algún colector habilitado falló
+    pub fn es_parcial(&self) -> bool {
+        !self.errors.is_empty()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Cpu {
+    pub total_usage_percent: f32,
+    #[serde(with = "unidades::en_megahercios")]
+    pub frequency_mhz: Frecuencia,
+    pub cores: Vec<Nucleo>,
+}
+
+//Uso de un núcleo de CPU
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Nucleo {
+    pub core: usize,
+    pub usage_percent: f32,
+}
+
+//Carga del sistema según /proc (solo Linux)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Carga {
+    pub load_1: f64,
+    pub load_5: f64,
+    pub load_15: f64,
+    //Acumulados desde el arranque y por segundo desde la muestra anterior
+    pub context_switches: u64,
+    pub interrupts: u64,
+    #[serde(default)]
+    pub context_switches_per_sec: Option<f64>,
+    #[serde(default)]
+    pub interrupts_per_sec: Option<f64>,
+    //Procesos listos para correr y bloqueados esperando E/S
+    pub procs_running: u64,
+    pub procs_blocked: u64,
+    //PSI; falta si el kernel no lo tiene
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pressure: Option<Presion>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Presion {
+    pub cpu: Option<PresionRecurso>,
+    pub memory: Option<PresionRecurso>,
+    pub io: Option<PresionRecurso>,
+}
+
+//some: al menos una tarea esperando el recurso; full: todas las tareas no ociosas esperando
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PresionRecurso {
+    pub some: PromediosPresion,
+    pub full: Option<PromediosPresion>,
+}
+
+//Porcentaje del tiempo con espera en los últimos 10, 60 y 300 segundos, y microsegundos acumulados
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PromediosPresion {
+    pub avg10: f64,
+    pub avg60: f64,
+    pub avg300: f64,
+    pub total_us: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Memoria {
+    #[serde(with = "unidades::en_bytes")]
+    pub total_bytes: Bytes,
+    #[serde(with = "unidades::en_bytes")]
+    pub used_bytes: Bytes,
+    //En Linux "libre" no cuenta la caché de páginas, que el kernel suelta cuando hace falta;
+    //lo que de verdad queda para los programas es `available_bytes`
+    #[serde(with = "unidades::en_bytes")]
+    pub free_bytes: Bytes,
+    #[serde(with = "unidades::en_bytes_opcional", default)]
+    pub available_bytes: Option<Bytes>,
+    #[serde(with = "unidades::en_bytes")]
+    pub total_swap_bytes: Bytes,
+    #[serde(with = "unidades::en_bytes")]
+    pub used_swap_bytes: Bytes,
+    //Solo en Linux
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub breakdown: Option<DetalleMemoria>,
+}
+
+//Desglose de /proc/meminfo y tasas de swap de /proc/vmstat
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DetalleMemoria {
+    //Caché de páginas (incluye la memoria compartida) y búferes de bloques
+    #[serde(with = "unidades::en_bytes")]
+    pub cached_bytes: Bytes,
+    #[serde(with = "unidades::en_bytes")]
+    pub buffers_bytes: Bytes,
+    //tmpfs y memoria compartida entre procesos
+    #[serde(with = "unidades::en_bytes")]
+    pub shared_bytes: Bytes,
+    //Estructuras del kernel
+    #[serde(with = "unidades::en_bytes")]
+    pub slab_bytes: Bytes,
+    //Páginas modificadas que aún no se escriben a disco, y las que se están escribiendo
+    #[serde(with = "unidades::en_bytes")]
+    pub dirty_bytes: Bytes,
+    #[serde(with = "unidades::en_bytes")]
+    pub writeback_bytes: Bytes,
+    pub hugepages_total: u64,
+    pub hugepages_free: u64,
+    #[serde(with = "unidades::en_bytes")]
+    pub hugepage_size_bytes: Bytes,
+    #[serde(with = "unidades::en_bytes_por_segundo", default)]
+    pub swap_in_bytes_per_sec: Option<BytesPorSegundo>,
+    #[serde(with = "unidades::en_bytes_por_segundo", default)]
+    pub swap_out_bytes_per_sec: Option<BytesPorSegundo>,
+}
+
+//Totales acumulados desde el arranque y tasas entre muestras
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Red {
+    #[serde(with = "unidades::en_bytes")]
+    pub received_bytes: Bytes,
+    #[serde(with = "unidades::en_bytes")]
+    pub transmitted_bytes: Bytes,
+    #[serde(with = "unidades::en_bytes_por_segundo", default)]
+    pub rx_bytes_per_sec: Option<BytesPorSegundo>,
+    #[serde(with = "unidades::en_bytes_por_segundo", default)]
+    pub tx_bytes_per_sec: Option<BytesPorSegundo>,
+    //Las interfaces que pasaron el filtro; los totales de arriba son su suma
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interfaces: Vec<Interfaz>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Interfaz {
+    pub name: String,
+    #[serde(with = "unidades::en_bytes")]
+    pub received_bytes: Bytes,
+    #[serde(with = "unidades::en_bytes")]
+    pub transmitted_bytes: Bytes,
+    #[serde(with = "unidades::en_bytes_por_segundo", default)]
+    pub rx_bytes_per_sec: Option<BytesPorSegundo>,
+    #[serde(with = "unidades::en_bytes_por_segundo", default)]
+    pub tx_bytes_per_sec: Option<BytesPorSegundo>,
+    pub rx_packets: u64,
+    pub tx_packets: u64,
+    pub rx_errors: u64,
+    pub tx_errors: u64,
+    //Solo en Linux (de /sys/class/net)
+    #[serde(default)]
+    pub rx_dropped: Option<u64>,
+    #[serde(default)]
+    pub tx_dropped: Option<u64>,
+}
+
+//Sockets TCP y UDP de /proc/net (solo Linux), IPv4 e IPv6 juntos
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Sockets {
+    //Con los nombres del kernel: ESTABLISHED, TIME_WAIT, SYN_RECV, LISTEN...
+    pub tcp_states: BTreeMap<String, u64>,
+    pub udp_sockets: u64,
+    //Puertos TCP en LISTEN y UDP sin conectar
+    pub listening: Vec<PuertoEscucha>,
+    //Direcciones remotas con más conexiones TCP, de mayor a menor
+    pub top_peers: Vec<Par>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PuertoEscucha {
+    //"tcp" o "udp"
+    pub protocol: String,
+    pub address: String,
+    pub port: u16,
+    //Falta si el socket es de un proceso de otro usuario y no se pudo leer su /proc/<pid>/fd
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub process: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Par {
+    pub address: String,
+    pub connections: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Disco {
+    #[serde(with = "unidades::en_bytes")]
+    pub read_bytes: Bytes,
+    #[serde(with = "unidades::en_bytes")]
+    pub written_bytes: Bytes,
+    #[serde(with = "unidades::en_bytes_por_segundo", default)]
+    pub read_bytes_per_sec: Option<BytesPorSegundo>,
+    #[serde(with = "unidades::en_bytes_por_segundo", default)]
+    pub write_bytes_per_sec: Option<BytesPorSegundo>,
+    //Los dispositivos que pasaron el filtro; los totales de arriba son su suma
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub devices: Vec<Dispositivo>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Dispositivo {
+    pub name: String,
+    #[serde(with = "unidades::en_bytes")]
+    pub read_bytes: Bytes,
+    #[serde(with = "unidades::en_bytes")]
+    pub written_bytes: Bytes,
+    #[serde(with = "unidades::en_bytes_por_segundo", default)]
+    pub read_bytes_per_sec: Option<BytesPorSegundo>,
+    #[serde(with = "unidades::en_bytes_por_segundo", default)]
+    pub write_bytes_per_sec: Option<BytesPorSegundo>,
+    //Operaciones de lectura y escritura completadas
+    pub reads: u64,
+    pub writes: u64,
+    //Milisegundos con operaciones en curso desde el arranque; solo en Linux (de /sys/class/block)
+    #[serde(default)]
+    pub busy_time_ms: Option<u64>,
+}
+
+//Rendimiento de un dispositivo entre dos lecturas de /proc/diskstats (solo Linux); en la primera muestra
+//en que aparece un dispositivo solo están los campos que no son tasas
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EstadisticasDisco {
+    pub name: String,
+    //Solicitudes en curso al momento de leer
+    pub in_flight: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reads_per_sec: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub writes_per_sec: Option<f64>,
+    //Tiempo promedio de cada operación completada en el intervalo, cola incluida; falta si no hubo operaciones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_latency_ms: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_latency_ms: Option<f64>,
+    //Solicitudes en curso en promedio durante el intervalo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avg_queue_depth: Option<f64>,
+    //Porcentaje del intervalo con al menos una solicitud en curso
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub utilization_percent: Option<f64>,
+}
+
+//Capacidad de un sistema de archivos montado; los inodos faltan donde el sistema no los reporta
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SistemaArchivos {
+    pub device: String,
+    pub mount_point: String,
+    pub fs_type: String,
+    #[serde(with = "unidades::en_bytes")]
+    pub total_bytes: Bytes,
+    #[serde(with = "unidades::en_bytes")]
+    pub used_bytes: Bytes,
+    //Lo que pueden usar los usuarios sin privilegios
+    #[serde(with = "unidades::en_bytes")]
+    pub available_bytes: Bytes,
+    #[serde(default)]
+    pub inodes_total: Option<u64>,
+    #[serde(default)]
+    pub inodes_used: Option<u64>,
+    #[serde(default)]
+    pub inodes_free: Option<u64>,
+}
+
+//Lectura de un sensor de temperatura; max y critical no siempre los reporta el sistema
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Sensor {
+    pub label: String,
+    pub temperature_celsius: f32,
+    pub max_celsius: Option<f32>,
+    pub critical_celsius: Option<f32>,
+}
+
+//Un proceso de alguno de los tops; el formato 1 solo guardaba nombre, CPU y memoria,
+//y los formatos 2 y 3 el pid. Lo demás falta cuando el sistema no lo da (o no hay permiso para leerlo)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Proceso {
+    pub pid: Option<u32>,
+    pub name: String,
+    pub cpu_usage_percent: f32,
+    #[serde(with = "unidades::en_bytes")]
+    pub memory_bytes: Bytes,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ppid: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    //Línea de comandos completa, un elemento por argumento
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cmd: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exe: Option<String>,
+    //RFC 3339 en UTC
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<String>,
+    //Como lo muestra sysinfo: "Run", "Sleep", "Zombie"...
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threads: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_fds: Option<usize>,
+    //Acumulados desde que arrancó el proceso y tasas desde la muestra anterior
+    #[serde(with = "unidades::en_bytes_opcional", default, skip_serializing_if = "Option::is_none")]
+    pub disk_read_bytes: Option<Bytes>,
+    #[serde(with = "unidades::en_bytes_opcional", default, skip_serializing_if = "Option::is_none")]
+    pub disk_written_bytes: Option<Bytes>,
+    #[serde(with = "unidades::en_bytes_por_segundo", default, skip_serializing_if = "Option::is_none")]
+    pub disk_read_bytes_per_sec: Option<BytesPorSegundo>,
+    #[serde(with = "unidades::en_bytes_por_segundo", default, skip_serializing_if = "Option::is_none")]
+    pub disk_write_bytes_per_sec: Option<BytesPorSegundo>,
+    //cgroup v2 del proceso y, si está en un contenedor, su ID (solo Linux)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cgroup: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container_id: Option<String>,
+}
+
+impl Proceso {
+    //Solo lo que tenían todos los formatos; el resto se llena después si se conoce
+    pub fn basico(pid: Option<u32>, name: String, cpu_usage_percent: f32, memory_bytes: Bytes) -> Proceso {
+        Proceso {
+            pid,
+            name,
+            cpu_usage_percent,
+            memory_bytes,
+            ppid: None,
+            user: None,
+            cmd: Vec::new(),
+            exe: None,
+            start_time: None,
+            status: None,
+            threads: None,
+            open_fds: None,
+            disk_read_bytes: None,
+            disk_written_bytes: None,
+            disk_read_bytes_per_sec: None,
+            disk_write_bytes_per_sec: None,
+            cgroup: None,
+            container_id: None,
+        }
+    }
+}
+
+//Lo que consumen juntos todos los procesos de un usuario (incluido el recolector, que en los tops no sale)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Usuario {
+    //Nombre, o el uid si no tiene; "unknown" si el sistema no da el dueño del proceso
+    pub user: String,
+    pub processes: usize,
+    //Suma de los % de cada proceso, así que puede pasar de 100 con varios núcleos
+    pub cpu_usage_percent: f32,
+    #[serde(with = "unidades::en_bytes")]
+    pub memory_bytes: Bytes,
+    //Desde la muestra anterior; faltan en la primera
+    #[serde(with = "unidades::en_bytes_por_segundo", default, skip_serializing_if = "Option::is_none")]
+    pub disk_read_bytes_per_sec: Option<BytesPorSegundo>,
+    #[serde(with = "unidades::en_bytes_por_segundo", default, skip_serializing_if = "Option::is_none")]
+    pub disk_write_bytes_per_sec: Option<BytesPorSegundo>,
+}
+
+//Un cgroup v2; lo que falta es porque su controlador no está habilitado en ese cgroup
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Cgroup {
+    //Relativo a la raíz de la jerarquía, "/" es la raíz
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container_id: Option<String>,
+    //Tiempo de CPU acumulado y % desde la muestra anterior (100% = un núcleo completo)
+    pub cpu_usage_usec: u64,
+    #[serde(default)]
+    pub cpu_usage_percent: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub throttling: Option<Estrangulamiento>,
+    #[serde(with = "unidades::en_bytes_opcional", default, skip_serializing_if = "Option::is_none")]
+    pub memory_current_bytes: Option<Bytes>,
+    //Falta si no hay límite (o si no hay controlador de memoria, cuando tampoco está memory_current_bytes)
+    #[serde(with = "unidades::en_bytes_opcional", default, skip_serializing_if = "Option::is_none")]
+    pub memory_max_bytes: Option<Bytes>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_events: Option<EventosMemoria>,
+    #[serde(with = "unidades::en_bytes_opcional", default, skip_serializing_if = "Option::is_none")]
+    pub io_read_bytes: Option<Bytes>,
+    #[serde(with = "unidades::en_bytes_opcional", default, skip_serializing_if = "Option::is_none")]
+    pub io_written_bytes: Option<Bytes>,
+}
+
+//Periodos de cuota de CPU transcurridos, en cuántos se agotó la cuota y cuánto tiempo se estuvo esperando
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Estrangulamiento {
+    pub nr_periods: u64,
+    pub nr_throttled: u64,
+    pub throttled_usec: u64,
+}
+
+//Contadores de memory.events: veces que se llegó a cada límite, y OOM con y sin proceso terminado
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EventosMemoria {
+    pub low: u64,
+    pub high: u64,
+    pub max: u64,
+    pub oom: u64,
+    pub oom_kill: u64,
+}
+
+//Consumo del propio recolector; no aparece en top_processes
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConsumoPropio {
+    pub pid: u32,
+    pub cpu_usage_percent: f32,
+    //Usuario + sistema desde que arrancó el proceso; no disponible fuera de Unix
+    #[serde(default)]
+    pub cpu_time_secs: Option<f64>,
+    #[serde(with = "unidades::en_bytes")]
+    pub rss_bytes: Bytes,
+}
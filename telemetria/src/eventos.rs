@@ -0,0 +1,77 @@
+//Eventos sueltos que no caben en una muestra periódica (procesos que arrancan y terminan entre dos muestras,
+//lo que avisa el kernel por su log, sensores que llegan a su temperatura crítica...);
+//van a su propio archivo JSONL, una línea por evento con el tipo en el campo "event"
+use serde::{Deserialize, Serialize};
+use crate::unidades::{self, Bytes};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Evento {
+    ProcessStarted {
+        timestamp: String,
+        pid: u32,
+        name: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        cmd: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ppid: Option<u32>,
+        //RFC 3339 en UTC
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        start_time: Option<String>,
+    },
+    ProcessExited {
+        timestamp: String,
+        pid: u32,
+        name: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        cmd: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ppid: Option<u32>,
+        //Desde que arrancó hasta que se notó que ya no estaba (se pasa por hasta un intervalo de revisión)
+        lifetime_secs: u64,
+        //La mayor memoria residente vista en las revisiones, no el pico exacto
+        #[serde(with = "unidades::en_bytes")]
+        peak_memory_bytes: Bytes,
+    },
+    //Los del log del kernel llevan el mensaje tal cual y su nivel de syslog ("err", "warning", "info"...);
+    //el pid y el proceso faltan si el mensaje no los dice
+    OomKill {
+        timestamp: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pid: Option<u32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        process: Option<String>,
+        message: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        priority: Option<String>,
+    },
+    Segfault {
+        timestamp: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pid: Option<u32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        process: Option<String>,
+        message: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        priority: Option<String>,
+    },
+    //Machine check (MCE) o error de memoria reportado por EDAC
+    HardwareError {
+        timestamp: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pid: Option<u32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        process: Option<String>,
+        message: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        priority: Option<String>,
+    },
+    //Un sensor llegó a su temperatura crítica (o ya estaba ahí en la primera lectura); no se repite
+    //hasta que baje de ella y vuelva a llegar
+    ThermalWarning {
+        timestamp: String,
+        sensor: String,
+        temperature_celsius: f32,
+        critical_celsius: f32,
+    },
+}
@@ -0,0 +1,362 @@
+//Lector de datosuwu.jsonl que entiende los formatos anteriores y corrige sus unidades
+//Formato 1: sin schema_version; núcleos, temperaturas y procesos como cadenas de format!
+//Formato 2: núcleos, temperaturas y procesos estructurados, más tasas en bytes/s
+//En ambos los campos de memoria `*_mb` en realidad estaban en KiB, la red y el disco sí en MiB
+//y la memoria de los procesos del formato 1 ("KB memoria") en realidad eran bytes
+//Formatos 1 a 3: `timestamp` era la hora local sin desfase; se pasa a `local_time` y, si tiene el formato
+//por defecto, se convierte a UTC con la zona de quien lee (la de quien escribió no quedó guardada)
+use std::fs::File;
+use std::io::{BufRead, Read, Seek, SeekFrom};
+use std::path::Path;
+use chrono::{Local, NaiveDateTime, Utc};
+use serde::Deserialize;
+use crate::datos::{rfc3339, Cpu, Datosuwu, Disco, Memoria, Nucleo, Proceso, Red, Sensor, SCHEMA_VERSION};
+use crate::rotacion;
+use crate::unidades::{bytes, bytes_por_segundo, kibibytes, megahercios};
+
+const BYTES_POR_MIB: f64 = 1024.0 * 1024.0;
+
+#[derive(Deserialize)]
+struct Legado {
+    timestamp: String,
+    cpu_total_usage: f32,
+    cpu_frequency_mhz: u64,
+    cpu_cores_usage: Vec<NucleoLegado>,
+    used_memory_mb: u64,
+    total_memory_mb: u64,
+    used_swap_mb: u64,
+    total_swap_mb: u64,
+    free_memory_mb: u64,
+    total_received_mb: f64,
+    total_transmitted_mb: f64,
+    disk_reads_mb: f64,
+    disk_writes_mb: f64,
+    #[serde(default)]
+    interval_secs: f64,
+    network_rx_bytes_per_sec: Option<f64>,
+    network_tx_bytes_per_sec: Option<f64>,
+    disk_read_bytes_per_sec: Option<f64>,
+    disk_write_bytes_per_sec: Option<f64>,
+    #[serde(default)]
+    component_temperatures: Vec<SensorLegado>,
+    #[serde(default)]
+    top_cpu_processes: Vec<ProcesoLegado>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NucleoLegado {
+    Texto(String),
+    Estructurado(Nucleo),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SensorLegado {
+    Texto(String),
+    Estructurado(Sensor),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ProcesoLegado {
+    Texto(String),
+    Estructurado(Box<Proceso>),
+}
+
+fn mib_a_bytes(mib: f64) -> crate::unidades::Bytes {
+    bytes((mib * BYTES_POR_MIB).round() as u64)
+}
+
+//"Core 0: 69.20%"
+fn nucleo_de_texto(texto: &str) -> Option<Nucleo> {
+    let (indice, uso) = texto.strip_prefix("Core ")?.split_once(": ")?;
+    Some(Nucleo {
+        core: indice.parse().ok()?,
+        usage_percent: uso.trim_end_matches('%').parse().ok()?,
+    })
+}
+
+//"CPU: 45.00°C"
+fn sensor_de_texto(texto: &str) -> Option<Sensor> {
+    let (label, temperatura) = texto.rsplit_once(": ")?;
+    Some(Sensor {
+        label: label.to_string(),
+        temperature_celsius: temperatura.trim_end_matches("°C").parse().ok()?,
+        max_celsius: None,
+        critical_celsius: None,
+    })
+}
+
+//"act4.exe: 50.00% CPU, 14778368 KB memoria"
+fn proceso_de_texto(texto: &str) -> Option<Proceso> {
+    let (izquierda, memoria) = texto.strip_suffix(" KB memoria")?.rsplit_once("% CPU, ")?;
+    let (nombre, cpu) = izquierda.rsplit_once(": ")?;
+    Some(Proceso::basico(None, nombre.to_string(), cpu.parse().ok()?, bytes(memoria.parse().ok()?)))
+}
+
+impl Legado {
+    fn corregir(self) -> Result<Datosuwu, String> {
+        let cores = self.cpu_cores_usage.into_iter().map(|n| match n {
+            NucleoLegado::Estructurado(n) => Ok(n),
+            NucleoLegado::Texto(t) => nucleo_de_texto(&t).ok_or(format!("núcleo ilegible: {}", t)),
+        }).collect::<Result<Vec<_>, _>>()?;
+        let temperatures = self.component_temperatures.into_iter().map(|s| match s {
+            SensorLegado::Estructurado(s) => Ok(s),
+            SensorLegado::Texto(t) => sensor_de_texto(&t).ok_or(format!("temperatura ilegible: {}", t)),
+        }).collect::<Result<Vec<_>, _>>()?;
+        let top_processes = self.top_cpu_processes.into_iter().map(|p| match p {
+            ProcesoLegado::Estructurado(p) => Ok(*p),
+            ProcesoLegado::Texto(t) => proceso_de_texto(&t).ok_or(format!("proceso ilegible: {}", t)),
+        }).collect::<Result<Vec<_>, _>>()?;
+        //Lo que los formatos anteriores no tenían se queda vacío
+        Ok(Datosuwu {
+            cpu: Some(Cpu {
+                total_usage_percent: self.cpu_total_usage,
+                frequency_mhz: megahercios(self.cpu_frequency_mhz),
+                cores,
+            }),
+            memory: Some(Memoria {
+                total_bytes: kibibytes(self.total_memory_mb),
+                used_bytes: kibibytes(self.used_memory_mb),
+                free_bytes: kibibytes(self.free_memory_mb),
+                available_bytes: None,
+                total_swap_bytes: kibibytes(self.total_swap_mb),
+                used_swap_bytes: kibibytes(self.used_swap_mb),
+                breakdown: None,
+            }),
+            network: Some(Red {
+                received_bytes: mib_a_bytes(self.total_received_mb),
+                transmitted_bytes: mib_a_bytes(self.total_transmitted_mb),
+                rx_bytes_per_sec: self.network_rx_bytes_per_sec.map(bytes_por_segundo),
+                tx_bytes_per_sec: self.network_tx_bytes_per_sec.map(bytes_por_segundo),
+                interfaces: Vec::new(),
+            }),
+            disk: Some(Disco {
+                read_bytes: mib_a_bytes(self.disk_reads_mb),
+                written_bytes: mib_a_bytes(self.disk_writes_mb),
+                read_bytes_per_sec: self.disk_read_bytes_per_sec.map(bytes_por_segundo),
+                write_bytes_per_sec: self.disk_write_bytes_per_sec.map(bytes_por_segundo),
+                devices: Vec::new(),
+            }),
+            temperatures: Some(temperatures),
+            top_processes: Some(top_processes),
+            ..Datosuwu::vacia(self.timestamp, self.interval_secs)
+        })
+    }
+}
+
+//Lee una línea de cualquier formato y la devuelve en el formato actual
+pub fn leer_linea(linea: &str) -> Result<Datosuwu, String> {
+    let valor: serde_json::Value = serde_json::from_str(linea).map_err(|e| e.to_string())?;
+    let version = valor.get("schema_version").and_then(|v| v.as_u64()).unwrap_or(1);
+    let mut registro: Datosuwu = if version >= 3 {
+        serde_json::from_value(valor).map_err(|e| e.to_string())?
+    } else {
+        let legado: Legado = serde_json::from_value(valor).map_err(|e| e.to_string())?;
+        legado.corregir()?
+    };
+    if version < 4 {
+        registro.schema_version = SCHEMA_VERSION;
+        registro.local_time = Some(registro.timestamp.clone());
+        if let Some(utc) = hora_local_a_utc(&registro.timestamp) {
+            registro.timestamp = utc;
+        }
+    }
+    Ok(registro)
+}
+
+//"2025-04-10 15:08:52" en la zona local a RFC 3339 en UTC; None si tiene otro formato (o no existe esa hora
+//local, como en el salto del horario de verano)
+fn hora_local_a_utc(texto: &str) -> Option<String> {
+    let local = NaiveDateTime::parse_from_str(texto, "%Y-%m-%d %H:%M:%S").ok()?;
+    let utc = local.and_local_timezone(Local).earliest()?.with_timezone(&Utc);
+    Some(rfc3339(utc))
+}
+
+//Lee un archivo completo (también .gz y .zst); las líneas vacías se ignoran y los errores indican el número de línea
+pub fn leer_archivo(ruta: &Path) -> Result<Vec<Datosuwu>, String> {
+    let lector = rotacion::abrir(ruta).map_err(|e| format!("{}: {}", ruta.display(), e))?;
+    let mut registros = Vec::new();
+    for (i, linea) in lector.lines().enumerate() {
+        let linea = linea.map_err(|e| format!("{}: {}", ruta.display(), e))?;
+        if linea.trim().is_empty() {
+            continue;
+        }
+        let registro = leer_linea(&linea).map_err(|e| format!("{}:{}: {}", ruta.display(), i + 1, e))?;
+        registros.push(registro);
+    }
+    Ok(registros)
+}
+
+//`sequence` de la última muestra de la serie de `ruta`, para continuar la numeración. Del archivo activo solo
+//se lee el final; si no existe o está vacío (recién rotado) se usa el último segmento rotado
+pub fn ultima_secuencia(ruta: &Path) -> Option<u64> {
+    //Más que cualquier línea de datosuwu.jsonl
+    const VENTANA: u64 = 1024 * 1024;
+    let secuencia = |linea: &str| {
+        serde_json::from_str::<serde_json::Value>(linea).ok()?.get("sequence")?.as_u64()
+    };
+    let mut final_activo = String::new();
+    if let Ok(mut archivo) = File::open(ruta) {
+        let largo = archivo.metadata().map(|m| m.len()).unwrap_or(0);
+        if archivo.seek(SeekFrom::Start(largo.saturating_sub(VENTANA))).is_ok() {
+            let mut bytes = Vec::new();
+            let _ = archivo.read_to_end(&mut bytes);
+            final_activo = String::from_utf8_lossy(&bytes).into_owned();
+        }
+    }
+    if let Some(linea) = final_activo.lines().rev().find(|l| !l.trim().is_empty()) {
+        return secuencia(linea);
+    }
+    let segmento = rotacion::segmentos_rotados(ruta).ok()?.pop()?;
+    let ultima = rotacion::abrir(&segmento).ok()?.lines().map_while(Result::ok).filter(|l| !l.trim().is_empty()).last()?;
+    secuencia(&ultima)
+}
+
+//Lee la serie completa de `ruta`: los segmentos rotados (comprimidos o no) y después el archivo activo
+pub fn leer_serie(ruta: &Path) -> Result<Vec<Datosuwu>, String> {
+    let segmentos = rotacion::segmentos(ruta).map_err(|e| format!("{}: {}", ruta.display(), e))?;
+    if segmentos.is_empty() {
+        return Err(format!("{}: no existe ni tiene segmentos rotados", ruta.display()));
+    }
+    let mut registros = Vec::new();
+    for segmento in segmentos {
+        registros.extend(leer_archivo(&segmento)?);
+    }
+    Ok(registros)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use crate::prueba::{arbol, escribir};
+
+    //La hora UTC que le corresponde a la hora local de la prueba en la zona de quien corre las pruebas
+    fn en_utc(local: &str) -> String {
+        let local = NaiveDateTime::parse_from_str(local, "%Y-%m-%d %H:%M:%S").unwrap();
+        rfc3339(local.and_local_timezone(Local).earliest().unwrap().with_timezone(&Utc))
+    }
+
+    fn corregida(linea: &str) -> Value {
+        serde_json::to_value(leer_linea(linea).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn formato_1() {
+        let linea = r#"{"timestamp":"2025-04-10 15:08:52","cpu_total_usage":77.5,"cpu_frequency_mhz":0,"cpu_cores_usage":["Core 0: 69.20%","Core 1: 71.15%"],"used_memory_mb":10490128,"total_memory_mb":14612608,"used_swap_mb":4676400,"total_swap_mb":9302380,"free_memory_mb":4122480,"total_received_mb":1.5,"total_transmitted_mb":2.0,"disk_reads_mb":3.0,"disk_writes_mb":0.5,"component_temperatures":["CPU: 45.00°C"],"top_cpu_processes":["act4.exe: 50.00% CPU, 14778368 KB memoria"]}"#;
+        assert_eq!(corregida(linea), json!({
+            "schema_version": 4,
+            "timestamp": en_utc("2025-04-10 15:08:52"),
+            "local_time": "2025-04-10 15:08:52",
+            "interval_secs": 0.0,
+            "cpu": {
+                "total_usage_percent": 77.5,
+                "frequency_mhz": 0,
+                "cores": [{"core": 0, "usage_percent": 69.2f32}, {"core": 1, "usage_percent": 71.15f32}],
+            },
+            "memory": {
+                "total_bytes": 14612608u64 * 1024,
+                "used_bytes": 10490128u64 * 1024,
+                "free_bytes": 4122480u64 * 1024,
+                "available_bytes": null,
+                "total_swap_bytes": 9302380u64 * 1024,
+                "used_swap_bytes": 4676400u64 * 1024,
+            },
+            "network": {
+                "received_bytes": 1572864,
+                "transmitted_bytes": 2097152,
+                "rx_bytes_per_sec": null,
+                "tx_bytes_per_sec": null,
+            },
+            "disk": {
+                "read_bytes": 3145728,
+                "written_bytes": 524288,
+                "read_bytes_per_sec": null,
+                "write_bytes_per_sec": null,
+            },
+            "temperatures": [{"label": "CPU", "temperature_celsius": 45.0, "max_celsius": null, "critical_celsius": null}],
+            //"KB memoria" en realidad eran bytes
+            "top_processes": [{"pid": null, "name": "act4.exe", "cpu_usage_percent": 50.0, "memory_bytes": 14778368}],
+        }));
+    }
+
+    #[test]
+    fn formato_2() {
+        let linea = r#"{"schema_version":2,"timestamp":"2025-04-10 15:13:52","interval_secs":300.0,"cpu_total_usage":10.0,"cpu_frequency_mhz":2400,"cpu_cores_usage":[{"core":0,"usage_percent":10.0}],"used_memory_mb":1024,"total_memory_mb":4096,"used_swap_mb":0,"total_swap_mb":2048,"free_memory_mb":3072,"total_received_mb":1.0,"total_transmitted_mb":1.0,"disk_reads_mb":1.0,"disk_writes_mb":1.0,"network_rx_bytes_per_sec":100.0,"network_tx_bytes_per_sec":50.0,"disk_read_bytes_per_sec":10.0,"disk_write_bytes_per_sec":20.0,"component_temperatures":[{"label":"coretemp Core 0","temperature_celsius":50.0,"max_celsius":80.0,"critical_celsius":100.0}],"top_cpu_processes":[{"pid":42,"name":"act4","cpu_usage_percent":5.0,"memory_bytes":2048}]}"#;
+        let registro = corregida(linea);
+        assert_eq!(registro["schema_version"], 4);
+        assert_eq!(registro["timestamp"], json!(en_utc("2025-04-10 15:13:52")));
+        assert_eq!(registro["local_time"], "2025-04-10 15:13:52");
+        assert_eq!(registro["interval_secs"], 300.0);
+        assert_eq!(registro["cpu"], json!({"total_usage_percent": 10.0, "frequency_mhz": 2400, "cores": [{"core": 0, "usage_percent": 10.0}]}));
+        assert_eq!(registro["memory"]["total_bytes"], 4096 * 1024);
+        assert_eq!(registro["memory"]["total_swap_bytes"], 2048 * 1024);
+        assert_eq!(registro["network"]["received_bytes"], 1048576);
+        assert_eq!(registro["network"]["rx_bytes_per_sec"], 100.0);
+        assert_eq!(registro["network"]["tx_bytes_per_sec"], 50.0);
+        assert_eq!(registro["disk"]["read_bytes_per_sec"], 10.0);
+        assert_eq!(registro["disk"]["write_bytes_per_sec"], 20.0);
+        assert_eq!(registro["temperatures"], json!([{"label": "coretemp Core 0", "temperature_celsius": 50.0, "max_celsius": 80.0, "critical_celsius": 100.0}]));
+        assert_eq!(registro["top_processes"], json!([{"pid": 42, "name": "act4", "cpu_usage_percent": 5.0, "memory_bytes": 2048}]));
+    }
+
+    #[test]
+    fn formato_3() {
+        let linea = r#"{"schema_version":3,"timestamp":"2025-04-10 15:18:52","sequence":7,"interval_secs":300.0,"memory":{"total_bytes":4096,"used_bytes":1024,"free_bytes":3072,"available_bytes":3500,"total_swap_bytes":0,"used_swap_bytes":0}}"#;
+        assert_eq!(corregida(linea), json!({
+            "schema_version": 4,
+            "timestamp": en_utc("2025-04-10 15:18:52"),
+            "local_time": "2025-04-10 15:18:52",
+            "sequence": 7,
+            "interval_secs": 300.0,
+            "memory": {"total_bytes": 4096, "used_bytes": 1024, "free_bytes": 3072, "available_bytes": 3500, "total_swap_bytes": 0, "used_swap_bytes": 0},
+        }));
+    }
+
+    #[test]
+    fn el_formato_4_no_se_toca() {
+        let linea = r#"{"schema_version":4,"timestamp":"2025-04-10T21:18:52.000Z","local_time":"10/04/2025 15:18","sequence":8,"interval_secs":300.0}"#;
+        assert_eq!(corregida(linea), serde_json::from_str::<Value>(linea).unwrap());
+    }
+
+    #[test]
+    fn hora_local_con_otro_formato() {
+        assert_eq!(hora_local_a_utc("2025-04-10 15:18:52"), Some(en_utc("2025-04-10 15:18:52")));
+        assert_eq!(hora_local_a_utc("10/04/2025 15:18"), None);
+        assert_eq!(hora_local_a_utc("2025-04-10T21:18:52.000Z"), None);
+        //Se queda como estaba, pero igual se guarda como hora local
+        let registro = leer_linea(r#"{"schema_version":3,"timestamp":"10/04/2025 15:18","interval_secs":0.0}"#).unwrap();
+        assert_eq!(registro.timestamp, "10/04/2025 15:18");
+        assert_eq!(registro.local_time.as_deref(), Some("10/04/2025 15:18"));
+    }
+
+    #[test]
+    fn lineas_ilegibles() {
+        assert!(leer_linea("no es json").is_err());
+        let error = leer_linea(r#"{"timestamp":"2025-04-10 15:08:52","cpu_total_usage":1.0,"cpu_frequency_mhz":0,"cpu_cores_usage":["núcleo 0"],"used_memory_mb":0,"total_memory_mb":0,"used_swap_mb":0,"total_swap_mb":0,"free_memory_mb":0,"total_received_mb":0.0,"total_transmitted_mb":0.0,"disk_reads_mb":0.0,"disk_writes_mb":0.0}"#).unwrap_err();
+        assert_eq!(error, "núcleo ilegible: núcleo 0");
+    }
+
+    #[test]
+    fn ultima_secuencia_del_archivo_activo() {
+        let dir = arbol(&[("datosuwu.jsonl", "{\"sequence\":1}\n{\"sequence\":2}\n\n")]);
+        assert_eq!(ultima_secuencia(&dir.path().join("datosuwu.jsonl")), Some(2));
+        //Sin secuencia (formatos anteriores) no hay de dónde seguir
+        escribir(dir.path(), "datosuwu.jsonl", "{\"sequence\":1}\n{\"timestamp\":\"2025-04-10 15:08:52\"}\n");
+        assert_eq!(ultima_secuencia(&dir.path().join("datosuwu.jsonl")), None);
+    }
+
+    #[test]
+    fn ultima_secuencia_recien_rotado() {
+        let dir = arbol(&[
+            ("datosuwu.jsonl", ""),
+            ("datosuwu.20250410-150000.jsonl", "{\"sequence\":3}\n"),
+            ("datosuwu.20250410-160000.jsonl", "{\"sequence\":4}\n{\"sequence\":5}\n"),
+        ]);
+        assert_eq!(ultima_secuencia(&dir.path().join("datosuwu.jsonl")), Some(5));
+        let vacio = arbol(&[]);
+        assert_eq!(ultima_secuencia(&vacio.path().join("datosuwu.jsonl")), None);
+    }
+}
@@ -0,0 +1,27 @@
+//Biblioteca compartida por act4, monitoreo y defense_system:
+//los tipos de la muestra Datosuwu, su recolección, la escritura y lectura de datosuwu.jsonl, los eventos
+//(procesos que arrancan y terminan...), el formato Metrics por UDP, la exposición para Prometheus y los
+//puntos para InfluxDB y Graphite
+pub mod colectores;
+pub mod config;
+pub mod datos;
+pub mod eventos;
+pub mod lector;
+pub mod metrics;
+pub mod prometheus;
+pub mod puntos;
+pub mod recolector;
+pub mod rotacion;
+pub mod unidades;
+pub mod vigilantes;
+#[cfg(test)]
+mod prueba;
+
+pub use datos::Datosuwu;
+pub use eventos::Evento;
+pub use metrics::Metrics;
+pub use colectores::Collector;
+pub use config::{ConfigColector, ConfigRecolector, Filtro, OrdenProcesos, ZonaHoraria};
+pub use recolector::{Recolector, VENTANA_MEDICION};
+pub use rotacion::{Compresion, EscritorRotativo, Periodo, PoliticaRotacion};
+pub use vigilantes::{VigilanteKernel, VigilanteProcesos};
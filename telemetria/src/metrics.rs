@@ -0,0 +1,106 @@
+//Formato que defense_system recibe por UDP (act4 lo manda con un destino defense_system): porcentajes enteros
+//y la red como "N MB/s", más conexiones TCP cuando la muestra trae la sección sockets
+use serde::{Deserialize, Serialize};
+use uom::si::information::byte;
+use uom::si::information_rate::megabyte_per_second;
+use crate::datos::Datosuwu;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Metrics {
+    pub cpu: u32,
+    pub network: String,
+    pub memory: u32,
+    //Faltan si la muestra no tiene la sección sockets (o quien manda es de una versión anterior)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tcp_established: Option<u32>,
+    //Conexiones a medio abrir (SYN_RECV y NEW_SYN_RECV)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tcp_syn_recv: Option<u32>,
+    //Conexiones de la dirección remota que más tiene
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_peer_connections: Option<u32>,
+}
+
+impl Metrics {
+    //Tasa de red en MB/s, si `network` tiene la forma "N MB/s"
+    pub fn network_mb_por_segundo(&self) -> Option<u32> {
+        self.network.strip_suffix(" MB/s")?.parse().ok()
+    }
+}
+
+//Las secciones que falten en la muestra se mandan como 0
+impl From<&Datosuwu> for Metrics {
+    fn from(datos: &Datosuwu) -> Metrics {
+        let memoria = match &datos.memory {
+            Some(m) if m.total_bytes.get::<byte>() > 0 => {
+                (m.used_bytes.get::<byte>() as f64 * 100.0 / m.total_bytes.get::<byte>() as f64).round() as u32
+            }
+            _ => 0,
+        };
+        //Recibido + enviado; sin muestra anterior no hay tasa
+        let red = match datos.network.as_ref().map(|r| (r.rx_bytes_per_sec, r.tx_bytes_per_sec)) {
+            Some((Some(rx), Some(tx))) => (rx + tx).get::<megabyte_per_second>().round() as u32,
+            _ => 0,
+        };
+        let estado = |nombres: &[&str]| {
+            datos.sockets.as_ref().map(|s| nombres.iter().filter_map(|n| s.tcp_states.get(*n)).sum::<u64>() as u32)
+        };
+        Metrics {
+            tcp_established: estado(&["ESTABLISHED"]),
+            tcp_syn_recv: estado(&["SYN_RECV", "NEW_SYN_RECV"]),
+            top_peer_connections: datos
+                .sockets
+                .as_ref()
+                .map(|s| s.top_peers.first().map_or(0, |par| par.connections as u32)),
+            cpu: datos.cpu.as_ref().map(|c| c.total_usage_percent.round() as u32).unwrap_or(0),
+            network: format!("{} MB/s", red),
+            memory: memoria,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use crate::datos::{Par, Sockets};
+    use super::*;
+
+    #[test]
+    fn conexiones_de_la_seccion_sockets() {
+        let mut datos = Datosuwu::vacia("2025-04-10T15:53:53.000Z".to_string(), 1.0);
+        let metrics = Metrics::from(&datos);
+        assert_eq!((metrics.cpu, metrics.memory, metrics.network.as_str()), (0, 0, "0 MB/s"));
+        assert!(metrics.tcp_established.is_none() && metrics.tcp_syn_recv.is_none());
+        //Sin sockets los campos ni se mandan
+        assert_eq!(serde_json::to_string(&metrics).unwrap(), r#"{"cpu":0,"network":"0 MB/s","memory":0}"#);
+
+        let estados = [("ESTABLISHED", 40), ("SYN_RECV", 150), ("NEW_SYN_RECV", 60), ("LISTEN", 5)];
+        datos.sockets = Some(Sockets {
+            tcp_states: BTreeMap::from(estados.map(|(estado, n)| (estado.to_string(), n))),
+            udp_sockets: 0,
+            listening: Vec::new(),
+            top_peers: vec![Par { address: "10.0.0.9".to_string(), connections: 180 }],
+        });
+        let metrics = Metrics::from(&datos);
+        assert_eq!(metrics.tcp_established, Some(40));
+        assert_eq!(metrics.tcp_syn_recv, Some(210));
+        assert_eq!(metrics.top_peer_connections, Some(180));
+        let recibidas: Metrics = serde_json::from_str(&serde_json::to_string(&metrics).unwrap()).unwrap();
+        assert_eq!(recibidas.tcp_syn_recv, Some(210));
+    }
+
+    #[test]
+    fn red_en_mb_por_segundo() {
+        let metrics = Metrics {
+            cpu: 0,
+            network: "85 MB/s".to_string(),
+            memory: 0,
+            tcp_established: None,
+            tcp_syn_recv: None,
+            top_peer_connections: None,
+        };
+        assert_eq!(metrics.network_mb_por_segundo(), Some(85));
+        let metrics = Metrics { network: "85 KB/s".to_string(), ..metrics };
+        assert_eq!(metrics.network_mb_por_segundo(), None);
+    }
+}
@@ -0,0 +1,748 @@
+//Exposición de una muestra en el formato de texto de Prometheus (0.0.4), la que act4 sirve en /metrics.
+//Los nombres llevan el prefijo act4_ y las unidades base de Prometheus: bytes, segundos, hercios y los
+//porcentajes como proporción (1 = 100%). Lo acumulado es counter y termina en _total; lo demás es gauge
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+use chrono::DateTime;
+use uom::si::frequency::hertz;
+use uom::si::information::byte;
+use uom::si::information_rate::byte_per_second;
+use crate::datos::{
+    Carga, Cgroup, ConsumoPropio, Cpu, Datosuwu, DetalleMemoria, Disco, Dispositivo, EstadisticasDisco, Interfaz,
+    Memoria, Nucleo, Par, Proceso, PromediosPresion, PuertoEscucha, Red, Sensor, SistemaArchivos, Usuario,
+};
+use crate::unidades::{Bytes, BytesPorSegundo};
+
+//Encabezado Content-Type de la respuesta
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+const PREFIJO: &str = "act4_";
+
+#[derive(Clone, Copy)]
+enum Tipo {
+    Gauge,
+    Counter,
+}
+
+type Etiquetas = Vec<(&'static str, String)>;
+
+//Nombre (sin prefijo), tipo, ayuda y cómo sacar el valor de cada elemento; None si ese elemento no lo tiene
+type Columna<T> = (&'static str, Tipo, &'static str, fn(&T) -> Option<f64>);
+
+#[derive(Default)]
+struct Exposicion {
+    texto: String,
+}
+
+impl Exposicion {
+    //HELP, TYPE y una línea por muestra; las muestras sin valor se omiten y, si no queda ninguna, la familia también
+    fn familia(&mut self, nombre: &str, tipo: Tipo, ayuda: &str, muestras: impl IntoIterator<Item = (Etiquetas, Option<f64>)>) {
+        let muestras: Vec<(Etiquetas, f64)> =
+            muestras.into_iter().filter_map(|(etiquetas, valor)| Some((etiquetas, valor?))).collect();
+        if muestras.is_empty() {
+            return;
+        }
+        let tipo = match tipo {
+            Tipo::Gauge => "gauge",
+            Tipo::Counter => "counter",
+        };
+        let _ = writeln!(self.texto, "# HELP {}{} {}", PREFIJO, nombre, ayuda);
+        let _ = writeln!(self.texto, "# TYPE {}{} {}", PREFIJO, nombre, tipo);
+        for (etiquetas, valor) in muestras {
+            let _ = write!(self.texto, "{}{}", PREFIJO, nombre);
+            if !etiquetas.is_empty() {
+                let etiquetas: Vec<String> =
+                    etiquetas.iter().map(|(nombre, valor)| format!("{}=\"{}\"", nombre, escapar(valor))).collect();
+                let _ = write!(self.texto, "{{{}}}", etiquetas.join(","));
+            }
+            let _ = writeln!(self.texto, " {}", numero(valor));
+        }
+    }
+
+    //Una familia por columna, con una muestra por elemento
+    fn columnas<T>(&mut self, elementos: &[T], etiquetas: impl Fn(&T) -> Etiquetas, columnas: &[Columna<T>]) {
+        for (nombre, tipo, ayuda, valor) in columnas {
+            self.familia(nombre, *tipo, ayuda, elementos.iter().map(|e| (etiquetas(e), valor(e))));
+        }
+    }
+}
+
+//Dentro de las comillas de una etiqueta solo se escapan \, " y el salto de línea
+fn escapar(valor: &str) -> String {
+    valor.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn numero(valor: f64) -> String {
+    if valor.is_nan() {
+        "NaN".to_string()
+    } else if valor.is_infinite() {
+        if valor > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        valor.to_string()
+    }
+}
+
+fn sin_etiquetas<T>(_: &T) -> Etiquetas {
+    Vec::new()
+}
+
+fn b(valor: Bytes) -> f64 {
+    valor.get::<byte>() as f64
+}
+
+fn bps(valor: BytesPorSegundo) -> f64 {
+    valor.get::<byte_per_second>()
+}
+
+fn proporcion(porcentaje: f64) -> f64 {
+    porcentaje / 100.0
+}
+
+//RFC 3339 a segundos desde 1970
+fn segundos_unix(fecha: &str) -> Option<f64> {
+    DateTime::parse_from_rfc3339(fecha).ok().map(|f| f.timestamp_millis() as f64 / 1000.0)
+}
+
+//Etiquetas con valor opcional; las que faltan no se ponen
+fn etiquetas(pares: &[(&'static str, Option<String>)]) -> Etiquetas {
+    pares.iter().filter_map(|(nombre, valor)| Some((*nombre, valor.clone()?))).collect()
+}
+
+//El texto completo de /metrics para una muestra
+pub fn exposicion(datos: &Datosuwu) -> String {
+    let mut e = Exposicion::default();
+    muestra(&mut e, datos);
+    if let Some(cpu) = &datos.cpu {
+        procesador(&mut e, cpu);
+    }
+    if let Some(carga) = &datos.load {
+        carga_sistema(&mut e, carga);
+    }
+    if let Some(memoria) = &datos.memory {
+        memoria_sistema(&mut e, memoria);
+    }
+    if let Some(red) = &datos.network {
+        red_sistema(&mut e, red);
+    }
+    if let Some(sockets) = &datos.sockets {
+        e.familia(
+            "tcp_sockets",
+            Tipo::Gauge,
+            "Sockets TCP por estado (IPv4 e IPv6).",
+            sockets.tcp_states.iter().map(|(estado, n)| (vec![("state", estado.clone())], Some(*n as f64))),
+        );
+        e.familia("udp_sockets", Tipo::Gauge, "Sockets UDP (IPv4 e IPv6).", [(Vec::new(), Some(sockets.udp_sockets as f64))]);
+        e.columnas(
+            &sockets.listening,
+            |p: &PuertoEscucha| {
+                etiquetas(&[
+                    ("protocol", Some(p.protocol.clone())),
+                    ("address", Some(p.address.clone())),
+                    ("port", Some(p.port.to_string())),
+                    ("pid", p.pid.map(|pid| pid.to_string())),
+                    ("process", p.process.clone()),
+                ])
+            },
+            &[("listening_socket", Tipo::Gauge, "Puerto TCP en LISTEN o UDP sin conectar, con el proceso dueño; siempre 1.", |_| Some(1.0))],
+        );
+        e.columnas(
+            &sockets.top_peers,
+            |p: &Par| vec![("address", p.address.clone())],
+            &[("tcp_peer_connections", Tipo::Gauge, "Conexiones TCP de las direcciones remotas con más conexiones.", |p| Some(p.connections as f64))],
+        );
+    }
+    if let Some(disco) = &datos.disk {
+        disco_sistema(&mut e, disco);
+    }
+    if let Some(estadisticas) = &datos.disk_stats {
+        estadisticas_disco(&mut e, estadisticas);
+    }
+    if let Some(sistemas) = &datos.filesystems {
+        sistemas_archivos(&mut e, sistemas);
+    }
+    if let Some(sensores) = &datos.temperatures {
+        e.columnas(
+            sensores,
+            |s: &Sensor| vec![("sensor", s.label.clone())],
+            &[
+                ("temperature_celsius", Tipo::Gauge, "Temperatura del sensor.", |s| Some(s.temperature_celsius as f64)),
+                ("temperature_max_celsius", Tipo::Gauge, "Temperatura máxima del sensor.", |s| s.max_celsius.map(f64::from)),
+                ("temperature_critical_celsius", Tipo::Gauge, "Temperatura crítica del sensor.", |s| s.critical_celsius.map(f64::from)),
+            ],
+        );
+    }
+    procesos(&mut e, datos);
+    if let Some(usuarios) = &datos.per_user {
+        e.columnas(
+            usuarios,
+            |u: &Usuario| vec![("user", u.user.clone())],
+            &[
+                ("user_processes", Tipo::Gauge, "Procesos del usuario.", |u| Some(u.processes as f64)),
+                ("user_cpu_usage_ratio", Tipo::Gauge, "Suma del uso de CPU de los procesos del usuario (1 = un núcleo).", |u| {
+                    Some(proporcion(u.cpu_usage_percent as f64))
+                }),
+                ("user_memory_bytes", Tipo::Gauge, "Memoria de los procesos del usuario.", |u| Some(b(u.memory_bytes))),
+                ("user_disk_read_bytes_per_second", Tipo::Gauge, "Lectura de disco de los procesos del usuario.", |u| {
+                    u.disk_read_bytes_per_sec.map(bps)
+                }),
+                ("user_disk_write_bytes_per_second", Tipo::Gauge, "Escritura a disco de los procesos del usuario.", |u| {
+                    u.disk_write_bytes_per_sec.map(bps)
+                }),
+            ],
+        );
+    }
+    if let Some(cgroups) = &datos.cgroups {
+        grupos(&mut e, cgroups);
+    }
+    if let Some(propio) = &datos.self_overhead {
+        e.columnas(
+            std::slice::from_ref(propio),
+            |p: &ConsumoPropio| vec![("pid", p.pid.to_string())],
+            &[
+                ("self_cpu_usage_ratio", Tipo::Gauge, "Uso de CPU del recolector (1 = un núcleo).", |p| {
+                    Some(proporcion(p.cpu_usage_percent as f64))
+                }),
+                ("self_cpu_seconds_total", Tipo::Counter, "Tiempo de CPU de usuario y sistema del recolector.", |p| p.cpu_time_secs),
+                ("self_resident_memory_bytes", Tipo::Gauge, "Memoria residente del recolector.", |p| Some(b(p.rss_bytes))),
+            ],
+        );
+    }
+    e.texto
+}
+
+//Lo que identifica a la muestra y al equipo; local_time no se exporta porque es el mismo momento que timestamp
+fn muestra(e: &mut Exposicion, datos: &Datosuwu) {
+    let equipo = datos.host.as_ref();
+    let campo = |f: fn(&crate::datos::Equipo) -> &Option<String>| equipo.and_then(|e| f(e).clone());
+    e.familia(
+        "host_info",
+        Tipo::Gauge,
+        "Identidad del equipo; siempre 1.",
+        [(
+            etiquetas(&[
+                ("host_label", datos.host_label.clone()),
+                ("hostname", campo(|e| &e.hostname)),
+                ("machine_id", campo(|e| &e.machine_id)),
+                ("boot_id", campo(|e| &e.boot_id)),
+                ("os", campo(|e| &e.os)),
+                ("kernel_version", campo(|e| &e.kernel_version)),
+            ]),
+            Some(1.0),
+        )],
+    );
+    e.familia(
+        "build_info",
+        Tipo::Gauge,
+        "Versión del recolector y del formato de la muestra; siempre 1.",
+        [(
+            etiquetas(&[
+                ("version", datos.collector_version.clone()),
+                ("schema_version", Some(datos.schema_version.to_string())),
+            ]),
+            Some(1.0),
+        )],
+    );
+    e.familia("sample_timestamp_seconds", Tipo::Gauge, "Momento de la última muestra.", [(Vec::new(), segundos_unix(&datos.timestamp))]);
+    e.familia("sample_sequence", Tipo::Gauge, "Número de la última muestra.", [(Vec::new(), datos.sequence.map(|s| s as f64))]);
+    e.familia("sample_interval_seconds", Tipo::Gauge, "Segundos entre la última muestra y la anterior.", [(Vec::new(), Some(datos.interval_secs))]);
+    let mut errores: BTreeMap<&str, u64> = BTreeMap::new();
+    for error in &datos.errors {
+        *errores.entry(error.collector.as_str()).or_insert(0) += 1;
+    }
+    e.familia(
+        "collector_errors",
+        Tipo::Gauge,
+        "Errores del colector en la última muestra.",
+        errores.into_iter().map(|(colector, n)| (vec![("collector", colector.to_string())], Some(n as f64))),
+    );
+}
+
+fn procesador(e: &mut Exposicion, cpu: &Cpu) {
+    e.columnas(
+        std::slice::from_ref(cpu),
+        sin_etiquetas,
+        &[
+            ("cpu_usage_ratio", Tipo::Gauge, "Uso total de CPU.", |c| Some(proporcion(c.total_usage_percent as f64))),
+            ("cpu_frequency_hertz", Tipo::Gauge, "Frecuencia de la CPU.", |c| Some(c.frequency_mhz.get::<hertz>() as f64)),
+        ],
+    );
+    e.columnas(
+        &cpu.cores,
+        |n: &Nucleo| vec![("core", n.core.to_string())],
+        &[("cpu_core_usage_ratio", Tipo::Gauge, "Uso de CPU por núcleo.", |n| Some(proporcion(n.usage_percent as f64)))],
+    );
+}
+
+fn carga_sistema(e: &mut Exposicion, carga: &Carga) {
+    e.columnas(
+        std::slice::from_ref(carga),
+        sin_etiquetas,
+        &[
+            ("load1", Tipo::Gauge, "Carga promedio de 1 minuto.", |c| Some(c.load_1)),
+            ("load5", Tipo::Gauge, "Carga promedio de 5 minutos.", |c| Some(c.load_5)),
+            ("load15", Tipo::Gauge, "Carga promedio de 15 minutos.", |c| Some(c.load_15)),
+            ("context_switches_total", Tipo::Counter, "Cambios de contexto desde el arranque.", |c| Some(c.context_switches as f64)),
+            ("interrupts_total", Tipo::Counter, "Interrupciones desde el arranque.", |c| Some(c.interrupts as f64)),
+            ("context_switches_per_second", Tipo::Gauge, "Cambios de contexto por segundo desde la muestra anterior.", |c| {
+                c.context_switches_per_sec
+            }),
+            ("interrupts_per_second", Tipo::Gauge, "Interrupciones por segundo desde la muestra anterior.", |c| c.interrupts_per_sec),
+            ("procs_running", Tipo::Gauge, "Procesos listos para correr.", |c| Some(c.procs_running as f64)),
+            ("procs_blocked", Tipo::Gauge, "Procesos bloqueados esperando E/S.", |c| Some(c.procs_blocked as f64)),
+        ],
+    );
+    let Some(presion) = &carga.pressure else {
+        return;
+    };
+    //(recurso, tipo, promedios) de cada línea de PSI que hay
+    let mut lineas: Vec<(&str, &str, &PromediosPresion)> = Vec::new();
+    for (recurso, datos) in [("cpu", &presion.cpu), ("memory", &presion.memory), ("io", &presion.io)] {
+        if let Some(datos) = datos {
+            lineas.push((recurso, "some", &datos.some));
+            if let Some(full) = &datos.full {
+                lineas.push((recurso, "full", full));
+            }
+        }
+    }
+    let etiquetas = |recurso: &str, tipo: &str| vec![("resource", recurso.to_string()), ("kind", tipo.to_string())];
+    e.familia(
+        "pressure_ratio",
+        Tipo::Gauge,
+        "Proporción del tiempo con tareas esperando el recurso (PSI) en la ventana indicada.",
+        lineas.iter().flat_map(|(recurso, tipo, p)| {
+            [("10", p.avg10), ("60", p.avg60), ("300", p.avg300)].map(|(ventana, promedio)| {
+                let mut etiquetas = etiquetas(recurso, tipo);
+                etiquetas.push(("window", ventana.to_string()));
+                (etiquetas, Some(proporcion(promedio)))
+            })
+        }),
+    );
+    e.familia(
+        "pressure_stalled_seconds_total",
+        Tipo::Counter,
+        "Tiempo acumulado con tareas esperando el recurso (PSI).",
+        lineas.iter().map(|(recurso, tipo, p)| (etiquetas(recurso, tipo), Some(p.total_us as f64 / 1e6))),
+    );
+}
+
+fn memoria_sistema(e: &mut Exposicion, memoria: &Memoria) {
+    e.columnas(
+        std::slice::from_ref(memoria),
+        sin_etiquetas,
+        &[
+            ("memory_total_bytes", Tipo::Gauge, "Memoria total.", |m| Some(b(m.total_bytes))),
+            ("memory_used_bytes", Tipo::Gauge, "Memoria usada.", |m| Some(b(m.used_bytes))),
+            ("memory_free_bytes", Tipo::Gauge, "Memoria libre, sin contar la caché de páginas.", |m| Some(b(m.free_bytes))),
+            ("memory_available_bytes", Tipo::Gauge, "Memoria disponible para los programas.", |m| m.available_bytes.map(b)),
+            ("swap_total_bytes", Tipo::Gauge, "Swap total.", |m| Some(b(m.total_swap_bytes))),
+            ("swap_used_bytes", Tipo::Gauge, "Swap usada.", |m| Some(b(m.used_swap_bytes))),
+        ],
+    );
+    let Some(detalle) = &memoria.breakdown else {
+        return;
+    };
+    e.columnas(
+        std::slice::from_ref(detalle),
+        sin_etiquetas,
+        &[
+            ("memory_cached_bytes", Tipo::Gauge, "Caché de páginas, incluida la memoria compartida.", |d: &DetalleMemoria| {
+                Some(b(d.cached_bytes))
+            }),
+            ("memory_buffers_bytes", Tipo::Gauge, "Búferes de bloques.", |d| Some(b(d.buffers_bytes))),
+            ("memory_shared_bytes", Tipo::Gauge, "tmpfs y memoria compartida entre procesos.", |d| Some(b(d.shared_bytes))),
+            ("memory_slab_bytes", Tipo::Gauge, "Estructuras del kernel.", |d| Some(b(d.slab_bytes))),
+            ("memory_dirty_bytes", Tipo::Gauge, "Páginas modificadas que aún no se escriben a disco.", |d| Some(b(d.dirty_bytes))),
+            ("memory_writeback_bytes", Tipo::Gauge, "Páginas que se están escribiendo a disco.", |d| Some(b(d.writeback_bytes))),
+            ("hugepages_total", Tipo::Gauge, "Huge pages reservadas.", |d| Some(d.hugepages_total as f64)),
+            ("hugepages_free", Tipo::Gauge, "Huge pages libres.", |d| Some(d.hugepages_free as f64)),
+            ("hugepage_size_bytes", Tipo::Gauge, "Tamaño de cada huge page.", |d| Some(b(d.hugepage_size_bytes))),
+            ("swap_in_bytes_per_second", Tipo::Gauge, "Lectura desde swap desde la muestra anterior.", |d| {
+                d.swap_in_bytes_per_sec.map(bps)
+            }),
+            ("swap_out_bytes_per_second", Tipo::Gauge, "Escritura a swap desde la muestra anterior.", |d| {
+                d.swap_out_bytes_per_sec.map(bps)
+            }),
+        ],
+    );
+}
+
+fn red_sistema(e: &mut Exposicion, red: &Red) {
+    e.columnas(
+        std::slice::from_ref(red),
+        sin_etiquetas,
+        &[
+            ("network_received_bytes_total", Tipo::Counter, "Bytes recibidos por las interfaces que pasaron el filtro.", |r| {
+                Some(b(r.received_bytes))
+            }),
+            ("network_transmitted_bytes_total", Tipo::Counter, "Bytes enviados por las interfaces que pasaron el filtro.", |r| {
+                Some(b(r.transmitted_bytes))
+            }),
+            ("network_receive_bytes_per_second", Tipo::Gauge, "Recepción desde la muestra anterior.", |r| r.rx_bytes_per_sec.map(bps)),
+            ("network_transmit_bytes_per_second", Tipo::Gauge, "Envío desde la muestra anterior.", |r| r.tx_bytes_per_sec.map(bps)),
+        ],
+    );
+    e.columnas(
+        &red.interfaces,
+        |i: &Interfaz| vec![("interface", i.name.clone())],
+        &[
+            ("network_interface_received_bytes_total", Tipo::Counter, "Bytes recibidos por la interfaz.", |i| Some(b(i.received_bytes))),
+            ("network_interface_transmitted_bytes_total", Tipo::Counter, "Bytes enviados por la interfaz.", |i| {
+                Some(b(i.transmitted_bytes))
+            }),
+            ("network_interface_receive_bytes_per_second", Tipo::Gauge, "Recepción de la interfaz desde la muestra anterior.", |i| {
+                i.rx_bytes_per_sec.map(bps)
+            }),
+            ("network_interface_transmit_bytes_per_second", Tipo::Gauge, "Envío de la interfaz desde la muestra anterior.", |i| {
+                i.tx_bytes_per_sec.map(bps)
+            }),
+            ("network_interface_received_packets_total", Tipo::Counter, "Paquetes recibidos por la interfaz.", |i| Some(i.rx_packets as f64)),
+            ("network_interface_transmitted_packets_total", Tipo::Counter, "Paquetes enviados por la interfaz.", |i| {
+                Some(i.tx_packets as f64)
+            }),
+            ("network_interface_receive_errors_total", Tipo::Counter, "Errores de recepción de la interfaz.", |i| Some(i.rx_errors as f64)),
+            ("network_interface_transmit_errors_total", Tipo::Counter, "Errores de envío de la interfaz.", |i| Some(i.tx_errors as f64)),
+            ("network_interface_receive_dropped_total", Tipo::Counter, "Paquetes descartados al recibir.", |i| i.rx_dropped.map(|n| n as f64)),
+            ("network_interface_transmit_dropped_total", Tipo::Counter, "Paquetes descartados al enviar.", |i| i.tx_dropped.map(|n| n as f64)),
+        ],
+    );
+}
+
+fn disco_sistema(e: &mut Exposicion, disco: &Disco) {
+    e.columnas(
+        std::slice::from_ref(disco),
+        sin_etiquetas,
+        &[
+            ("disk_read_bytes_total", Tipo::Counter, "Bytes leídos de los dispositivos que pasaron el filtro.", |d| Some(b(d.read_bytes))),
+            ("disk_written_bytes_total", Tipo::Counter, "Bytes escritos en los dispositivos que pasaron el filtro.", |d| {
+                Some(b(d.written_bytes))
+            }),
+            ("disk_read_bytes_per_second", Tipo::Gauge, "Lectura desde la muestra anterior.", |d| d.read_bytes_per_sec.map(bps)),
+            ("disk_write_bytes_per_second", Tipo::Gauge, "Escritura desde la muestra anterior.", |d| d.write_bytes_per_sec.map(bps)),
+        ],
+    );
+    e.columnas(
+        &disco.devices,
+        |d: &Dispositivo| vec![("device", d.name.clone())],
+        &[
+            ("disk_device_read_bytes_total", Tipo::Counter, "Bytes leídos del dispositivo.", |d| Some(b(d.read_bytes))),
+            ("disk_device_written_bytes_total", Tipo::Counter, "Bytes escritos en el dispositivo.", |d| Some(b(d.written_bytes))),
+            ("disk_device_read_bytes_per_second", Tipo::Gauge, "Lectura del dispositivo desde la muestra anterior.", |d| {
+                d.read_bytes_per_sec.map(bps)
+            }),
+            ("disk_device_write_bytes_per_second", Tipo::Gauge, "Escritura del dispositivo desde la muestra anterior.", |d| {
+                d.write_bytes_per_sec.map(bps)
+            }),
+            ("disk_device_reads_completed_total", Tipo::Counter, "Lecturas completadas.", |d| Some(d.reads as f64)),
+            ("disk_device_writes_completed_total", Tipo::Counter, "Escrituras completadas.", |d| Some(d.writes as f64)),
+            ("disk_device_io_time_seconds_total", Tipo::Counter, "Tiempo con operaciones en curso.", |d| {
+                d.busy_time_ms.map(|ms| ms as f64 / 1000.0)
+            }),
+        ],
+    );
+}
+
+fn estadisticas_disco(e: &mut Exposicion, estadisticas: &[EstadisticasDisco]) {
+    e.columnas(
+        estadisticas,
+        |d: &EstadisticasDisco| vec![("device", d.name.clone())],
+        &[
+            ("disk_device_in_flight_requests", Tipo::Gauge, "Solicitudes en curso.", |d| Some(d.in_flight as f64)),
+            ("disk_device_reads_per_second", Tipo::Gauge, "Lecturas por segundo desde la muestra anterior.", |d| d.reads_per_sec),
+            ("disk_device_writes_per_second", Tipo::Gauge, "Escrituras por segundo desde la muestra anterior.", |d| d.writes_per_sec),
+            ("disk_device_read_latency_seconds", Tipo::Gauge, "Tiempo promedio de cada lectura, con la cola.", |d| {
+                d.read_latency_ms.map(|ms| ms / 1000.0)
+            }),
+            ("disk_device_write_latency_seconds", Tipo::Gauge, "Tiempo promedio de cada escritura, con la cola.", |d| {
+                d.write_latency_ms.map(|ms| ms / 1000.0)
+            }),
+            ("disk_device_queue_depth", Tipo::Gauge, "Solicitudes en curso en promedio desde la muestra anterior.", |d| d.avg_queue_depth),
+            ("disk_device_utilization_ratio", Tipo::Gauge, "Proporción del tiempo con al menos una solicitud en curso.", |d| {
+                d.utilization_percent.map(proporcion)
+            }),
+        ],
+    );
+}
+
+fn sistemas_archivos(e: &mut Exposicion, sistemas: &[SistemaArchivos]) {
+    e.columnas(
+        sistemas,
+        |s: &SistemaArchivos| {
+            vec![
+                ("device", s.device.clone()),
+                ("mountpoint", s.mount_point.clone()),
+                ("fstype", s.fs_type.clone()),
+            ]
+        },
+        &[
+            ("filesystem_size_bytes", Tipo::Gauge, "Tamaño del sistema de archivos.", |s| Some(b(s.total_bytes))),
+            ("filesystem_used_bytes", Tipo::Gauge, "Espacio usado.", |s| Some(b(s.used_bytes))),
+            ("filesystem_available_bytes", Tipo::Gauge, "Espacio que pueden usar los usuarios sin privilegios.", |s| {
+                Some(b(s.available_bytes))
+            }),
+            ("filesystem_inodes", Tipo::Gauge, "Inodos en total.", |s| s.inodes_total.map(|n| n as f64)),
+            ("filesystem_inodes_used", Tipo::Gauge, "Inodos usados.", |s| s.inodes_used.map(|n| n as f64)),
+            ("filesystem_inodes_free", Tipo::Gauge, "Inodos libres.", |s| s.inodes_free.map(|n| n as f64)),
+        ],
+    );
+}
+
+fn etiquetas_proceso(p: &Proceso) -> Etiquetas {
+    etiquetas(&[("pid", p.pid.map(|pid| pid.to_string())), ("name", Some(p.name.clone()))])
+}
+
+//Un proceso puede estar en varios tops; sus valores se exportan una vez, y su lugar en cada top en process_rank.
+//La ruta del ejecutable y la línea de comandos no van en process_info: cambian con los argumentos de cada
+//ejecución y cada combinación sería una serie nueva en Prometheus
+fn procesos(e: &mut Exposicion, datos: &Datosuwu) {
+    let tops = [
+        ("cpu", &datos.top_processes),
+        ("memory", &datos.top_processes_by_memory),
+        ("disk_io", &datos.top_processes_by_disk_io),
+    ];
+    let mut vistos = HashSet::new();
+    let mut unicos: Vec<Proceso> = Vec::new();
+    for (_, top) in tops {
+        for proceso in top.iter().flatten() {
+            if vistos.insert((proceso.pid, proceso.name.clone())) {
+                unicos.push(proceso.clone());
+            }
+        }
+    }
+    e.familia(
+        "process_rank",
+        Tipo::Gauge,
+        "Lugar del proceso en el top indicado (1 = el que más consume).",
+        tops.iter().flat_map(|(orden, top)| {
+            top.iter().flatten().enumerate().map(|(i, proceso)| {
+                let mut etiquetas = etiquetas_proceso(proceso);
+                etiquetas.push(("by", orden.to_string()));
+                (etiquetas, Some(i as f64 + 1.0))
+            })
+        }),
+    );
+    e.columnas(
+        &unicos,
+        |p: &Proceso| {
+            let mut todas = etiquetas_proceso(p);
+            todas.extend(etiquetas(&[
+                ("ppid", p.ppid.map(|ppid| ppid.to_string())),
+                ("user", p.user.clone()),
+                ("status", p.status.clone()),
+                ("cgroup", p.cgroup.clone()),
+                ("container_id", p.container_id.clone()),
+            ]));
+            todas
+        },
+        &[("process_info", Tipo::Gauge, "Datos descriptivos del proceso; siempre 1.", |_| Some(1.0))],
+    );
+    e.columnas(
+        &unicos,
+        etiquetas_proceso,
+        &[
+            ("process_cpu_usage_ratio", Tipo::Gauge, "Uso de CPU del proceso (1 = un núcleo).", |p| {
+                Some(proporcion(p.cpu_usage_percent as f64))
+            }),
+            ("process_memory_bytes", Tipo::Gauge, "Memoria del proceso.", |p| Some(b(p.memory_bytes))),
+            ("process_start_time_seconds", Tipo::Gauge, "Momento en que arrancó el proceso.", |p| {
+                p.start_time.as_deref().and_then(segundos_unix)
+            }),
+            ("process_threads", Tipo::Gauge, "Hilos del proceso.", |p| p.threads.map(|n| n as f64)),
+            ("process_open_fds", Tipo::Gauge, "Descriptores de archivo abiertos.", |p| p.open_fds.map(|n| n as f64)),
+            ("process_disk_read_bytes_total", Tipo::Counter, "Bytes leídos de disco desde que arrancó el proceso.", |p| {
+                p.disk_read_bytes.map(b)
+            }),
+            ("process_disk_written_bytes_total", Tipo::Counter, "Bytes escritos a disco desde que arrancó el proceso.", |p| {
+                p.disk_written_bytes.map(b)
+            }),
+            ("process_disk_read_bytes_per_second", Tipo::Gauge, "Lectura de disco desde la muestra anterior.", |p| {
+                p.disk_read_bytes_per_sec.map(bps)
+            }),
+            ("process_disk_write_bytes_per_second", Tipo::Gauge, "Escritura a disco desde la muestra anterior.", |p| {
+                p.disk_write_bytes_per_sec.map(bps)
+            }),
+        ],
+    );
+}
+
+fn grupos(e: &mut Exposicion, cgroups: &[Cgroup]) {
+    let etiquetas_cgroup =
+        |c: &Cgroup| etiquetas(&[("cgroup", Some(c.path.clone())), ("container_id", c.container_id.clone())]);
+    e.columnas(
+        cgroups,
+        etiquetas_cgroup,
+        &[
+            ("cgroup_cpu_usage_seconds_total", Tipo::Counter, "Tiempo de CPU del cgroup.", |c| Some(c.cpu_usage_usec as f64 / 1e6)),
+            ("cgroup_cpu_usage_ratio", Tipo::Gauge, "Uso de CPU del cgroup desde la muestra anterior (1 = un núcleo).", |c| {
+                c.cpu_usage_percent.map(proporcion)
+            }),
+            ("cgroup_cpu_periods_total", Tipo::Counter, "Periodos de cuota de CPU transcurridos.", |c| {
+                c.throttling.as_ref().map(|t| t.nr_periods as f64)
+            }),
+            ("cgroup_cpu_throttled_periods_total", Tipo::Counter, "Periodos en los que se agotó la cuota de CPU.", |c| {
+                c.throttling.as_ref().map(|t| t.nr_throttled as f64)
+            }),
+            ("cgroup_cpu_throttled_seconds_total", Tipo::Counter, "Tiempo esperando por haber agotado la cuota de CPU.", |c| {
+                c.throttling.as_ref().map(|t| t.throttled_usec as f64 / 1e6)
+            }),
+            ("cgroup_memory_current_bytes", Tipo::Gauge, "Memoria del cgroup.", |c| c.memory_current_bytes.map(b)),
+            ("cgroup_memory_max_bytes", Tipo::Gauge, "Límite de memoria del cgroup.", |c| c.memory_max_bytes.map(b)),
+            ("cgroup_io_read_bytes_total", Tipo::Counter, "Bytes leídos por el cgroup.", |c| c.io_read_bytes.map(b)),
+            ("cgroup_io_written_bytes_total", Tipo::Counter, "Bytes escritos por el cgroup.", |c| c.io_written_bytes.map(b)),
+        ],
+    );
+    e.familia(
+        "cgroup_memory_events_total",
+        Tipo::Counter,
+        "Veces que el cgroup llegó a cada límite de memoria (low, high, max) y OOM con y sin proceso terminado.",
+        cgroups.iter().flat_map(|c| {
+            let eventos = c.memory_events.as_ref().map(|m| {
+                [("low", m.low), ("high", m.high), ("max", m.max), ("oom", m.oom), ("oom_kill", m.oom_kill)]
+            });
+            eventos.into_iter().flatten().map(move |(evento, n)| {
+                let mut etiquetas = etiquetas_cgroup(c);
+                etiquetas.push(("event", evento.to_string()));
+                (etiquetas, Some(n as f64))
+            })
+        }),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::unidades::{bytes, megahercios};
+
+    fn muestra_completa() -> Datosuwu {
+        let mut datos = Datosuwu::vacia("2024-05-01T12:00:00.000Z".to_string(), 10.0);
+        datos.sequence = Some(7);
+        datos.host_label = Some("lab".to_string());
+        datos.collector_version = Some("0.1.0".to_string());
+        datos.errors.push(crate::datos::ErrorColector { collector: "sockets".to_string(), message: "sin permiso".to_string() });
+        datos.cpu = Some(Cpu {
+            total_usage_percent: 12.5,
+            frequency_mhz: megahercios(2400),
+            cores: vec![Nucleo { core: 0, usage_percent: 50.0 }],
+        });
+        datos.load = Some(Carga {
+            load_1: 0.5,
+            load_5: 0.25,
+            load_15: 0.125,
+            context_switches: 1000,
+            interrupts: 500,
+            context_switches_per_sec: None,
+            interrupts_per_sec: None,
+            procs_running: 2,
+            procs_blocked: 0,
+            pressure: None,
+        });
+        datos.temperatures = Some(vec![Sensor {
+            label: "coretemp \"Core 0\"\\x\ny".to_string(),
+            temperature_celsius: 45.5,
+            max_celsius: Some(80.0),
+            critical_celsius: None,
+        }]);
+        let mut proceso = Proceso::basico(Some(42), "act4".to_string(), 50.0, bytes(2048));
+        proceso.user = Some("root".to_string());
+        proceso.status = Some("Run".to_string());
+        proceso.exe = Some("/usr/bin/act4".to_string());
+        proceso.cmd = vec!["act4".to_string(), "--token=secreto".to_string()];
+        let otro = Proceso::basico(None, "sh".to_string(), 25.0, bytes(1024));
+        datos.top_processes = Some(vec![proceso.clone(), otro]);
+        datos.top_processes_by_memory = Some(vec![proceso]);
+        datos
+    }
+
+    #[test]
+    fn exposicion_de_una_muestra() {
+        let esperado = r##"# HELP act4_host_info Identidad del equipo; siempre 1.
+# TYPE act4_host_info gauge
+act4_host_info{host_label="lab"} 1
+# HELP act4_build_info Versión del recolector y del formato de la muestra; siempre 1.
+# TYPE act4_build_info gauge
+act4_build_info{version="0.1.0",schema_version="4"} 1
+# HELP act4_sample_timestamp_seconds Momento de la última muestra.
+# TYPE act4_sample_timestamp_seconds gauge
+act4_sample_timestamp_seconds 1714564800
+# HELP act4_sample_sequence Número de la última muestra.
+# TYPE act4_sample_sequence gauge
+act4_sample_sequence 7
+# HELP act4_sample_interval_seconds Segundos entre la última muestra y la anterior.
+# TYPE act4_sample_interval_seconds gauge
+act4_sample_interval_seconds 10
+# HELP act4_collector_errors Errores del colector en la última muestra.
+# TYPE act4_collector_errors gauge
+act4_collector_errors{collector="sockets"} 1
+# HELP act4_cpu_usage_ratio Uso total de CPU.
+# TYPE act4_cpu_usage_ratio gauge
+act4_cpu_usage_ratio 0.125
+# HELP act4_cpu_frequency_hertz Frecuencia de la CPU.
+# TYPE act4_cpu_frequency_hertz gauge
+act4_cpu_frequency_hertz 2400000000
+# HELP act4_cpu_core_usage_ratio Uso de CPU por núcleo.
+# TYPE act4_cpu_core_usage_ratio gauge
+act4_cpu_core_usage_ratio{core="0"} 0.5
+# HELP act4_load1 Carga promedio de 1 minuto.
+# TYPE act4_load1 gauge
+act4_load1 0.5
+# HELP act4_load5 Carga promedio de 5 minutos.
+# TYPE act4_load5 gauge
+act4_load5 0.25
+# HELP act4_load15 Carga promedio de 15 minutos.
+# TYPE act4_load15 gauge
+act4_load15 0.125
+# HELP act4_context_switches_total Cambios de contexto desde el arranque.
+# TYPE act4_context_switches_total counter
+act4_context_switches_total 1000
+# HELP act4_interrupts_total Interrupciones desde el arranque.
+# TYPE act4_interrupts_total counter
+act4_interrupts_total 500
+# HELP act4_procs_running Procesos listos para correr.
+# TYPE act4_procs_running gauge
+act4_procs_running 2
+# HELP act4_procs_blocked Procesos bloqueados esperando E/S.
+# TYPE act4_procs_blocked gauge
+act4_procs_blocked 0
+# HELP act4_temperature_celsius Temperatura del sensor.
+# TYPE act4_temperature_celsius gauge
+act4_temperature_celsius{sensor="coretemp \"Core 0\"\\x\ny"} 45.5
+# HELP act4_temperature_max_celsius Temperatura máxima del sensor.
+# TYPE act4_temperature_max_celsius gauge
+act4_temperature_max_celsius{sensor="coretemp \"Core 0\"\\x\ny"} 80
+# HELP act4_process_rank Lugar del proceso en el top indicado (1 = el que más consume).
+# TYPE act4_process_rank gauge
+act4_process_rank{pid="42",name="act4",by="cpu"} 1
+act4_process_rank{name="sh",by="cpu"} 2
+act4_process_rank{pid="42",name="act4",by="memory"} 1
+# HELP act4_process_info Datos descriptivos del proceso; siempre 1.
+# TYPE act4_process_info gauge
+act4_process_info{pid="42",name="act4",user="root",status="Run"} 1
+act4_process_info{name="sh"} 1
+# HELP act4_process_cpu_usage_ratio Uso de CPU del proceso (1 = un núcleo).
+# TYPE act4_process_cpu_usage_ratio gauge
+act4_process_cpu_usage_ratio{pid="42",name="act4"} 0.5
+act4_process_cpu_usage_ratio{name="sh"} 0.25
+# HELP act4_process_memory_bytes Memoria del proceso.
+# TYPE act4_process_memory_bytes gauge
+act4_process_memory_bytes{pid="42",name="act4"} 2048
+act4_process_memory_bytes{name="sh"} 1024
+"##;
+        assert_eq!(exposicion(&muestra_completa()), esperado);
+    }
+
+    #[test]
+    fn las_secciones_que_faltan_no_se_exportan() {
+        let texto = exposicion(&Datosuwu::vacia("no es una fecha".to_string(), 0.0));
+        let familias: Vec<&str> = texto.lines().filter_map(|l| l.strip_prefix("# TYPE ")).collect();
+        assert_eq!(familias, ["act4_host_info gauge", "act4_build_info gauge", "act4_sample_interval_seconds gauge"]);
+        assert!(texto.contains("act4_host_info 1\n"));
+    }
+
+    #[test]
+    fn numeros_especiales() {
+        assert_eq!(numero(f64::NAN), "NaN");
+        assert_eq!(numero(f64::INFINITY), "+Inf");
+        assert_eq!(numero(f64::NEG_INFINITY), "-Inf");
+        assert_eq!(numero(1e21), "1000000000000000000000");
+        assert_eq!(numero(-0.5), "-0.5");
+    }
+}
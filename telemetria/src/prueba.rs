@@ -0,0 +1,25 @@
+//Árboles de archivos para los tests (un /proc, /sys o /dev/kmsg de mentira). El directorio se borra al soltarlo,
+//aunque el test falle a la mitad
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+//Cada par es la ruta relativa de un archivo y su contenido; una ruta que termina en "/" es una carpeta vacía
+pub fn arbol(archivos: &[(&str, &str)]) -> TempDir {
+    let directorio = tempfile::Builder::new().prefix("act4-").tempdir().unwrap();
+    for (ruta, texto) in archivos {
+        escribir(directorio.path(), ruta, texto);
+    }
+    directorio
+}
+
+//Crea las carpetas intermedias que falten
+pub fn escribir(raiz: &Path, ruta: &str, texto: &str) {
+    let ruta = raiz.join(ruta);
+    if ruta.as_os_str().to_string_lossy().ends_with('/') {
+        fs::create_dir_all(ruta).unwrap();
+        return;
+    }
+    fs::create_dir_all(ruta.parent().unwrap()).unwrap();
+    fs::write(ruta, texto).unwrap();
+}
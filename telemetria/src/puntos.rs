@@ -0,0 +1,520 @@
+//Una muestra como puntos con etiquetas y campos, para mandarla a InfluxDB (line protocol) o a Graphite (plaintext).
+//Cada sección es una medición con los nombres de campo del JSON; lo que identifica a un elemento (núcleo,
+//interfaz, dispositivo...) va en las etiquetas, y todos los puntos llevan host y host_label
+use chrono::DateTime;
+use uom::si::frequency::megahertz;
+use uom::si::information::byte;
+use uom::si::information_rate::byte_per_second;
+use crate::datos::{Datosuwu, PromediosPresion};
+use crate::unidades::{Bytes, BytesPorSegundo};
+
+pub struct Punto {
+    pub medicion: &'static str,
+    //Todas las etiquetas de la medición, siempre en el mismo orden; None si este punto no la tiene
+    pub etiquetas: Vec<(&'static str, Option<String>)>,
+    pub campos: Vec<(&'static str, Valor)>,
+}
+
+pub enum Valor {
+    Entero(u64),
+    Real(f64),
+    Texto(String),
+}
+
+fn entero(n: impl TryInto<u64>) -> Option<Valor> {
+    n.try_into().ok().map(Valor::Entero)
+}
+
+//NaN e infinito no caben en ninguno de los dos formatos
+fn real(n: impl Into<f64>) -> Option<Valor> {
+    Some(n.into()).filter(|n: &f64| n.is_finite()).map(Valor::Real)
+}
+
+fn bytes(valor: Bytes) -> Option<Valor> {
+    entero(valor.get::<byte>())
+}
+
+fn tasa(valor: Option<BytesPorSegundo>) -> Option<Valor> {
+    real(valor?.get::<byte_per_second>())
+}
+
+fn texto(valor: &str) -> Option<Valor> {
+    Some(Valor::Texto(valor.to_string()))
+}
+
+//Arma los puntos; los campos sin valor no se agregan y un punto sin campos se descarta
+struct Puntos {
+    puntos: Vec<Punto>,
+    //host y host_label, que van en todos los puntos
+    comunes: Vec<(&'static str, Option<String>)>,
+}
+
+impl Puntos {
+    fn punto(&mut self, medicion: &'static str, etiquetas: &[(&'static str, Option<String>)], campos: Vec<(&'static str, Option<Valor>)>) {
+        let campos: Vec<_> = campos.into_iter().filter_map(|(nombre, valor)| Some((nombre, valor?))).collect();
+        if campos.is_empty() {
+            return;
+        }
+        let mut todas = self.comunes.clone();
+        todas.extend(etiquetas.iter().map(|(nombre, valor)| (*nombre, valor.clone().filter(|v| !v.is_empty()))));
+        self.puntos.push(Punto { medicion, etiquetas: todas, campos });
+    }
+}
+
+pub fn puntos(datos: &Datosuwu) -> Vec<Punto> {
+    let hostname = datos.host.as_ref().and_then(|h| h.hostname.clone());
+    let mut p = Puntos { puntos: Vec::new(), comunes: vec![("host", hostname), ("host_label", datos.host_label.clone())] };
+    p.punto(
+        "sample",
+        &[],
+        vec![
+            ("sequence", datos.sequence.and_then(entero)),
+            ("interval_secs", real(datos.interval_secs)),
+            ("schema_version", entero(datos.schema_version)),
+        ],
+    );
+    for error in &datos.errors {
+        p.punto("collector_error", &[("collector", Some(error.collector.clone()))], vec![("message", texto(&error.message))]);
+    }
+    if let Some(cpu) = &datos.cpu {
+        p.punto(
+            "cpu",
+            &[],
+            vec![
+                ("total_usage_percent", real(cpu.total_usage_percent)),
+                ("frequency_mhz", entero(cpu.frequency_mhz.get::<megahertz>())),
+            ],
+        );
+        for nucleo in &cpu.cores {
+            p.punto("cpu_core", &[("core", Some(nucleo.core.to_string()))], vec![("usage_percent", real(nucleo.usage_percent))]);
+        }
+    }
+    if let Some(carga) = &datos.load {
+        p.punto(
+            "load",
+            &[],
+            vec![
+                ("load_1", real(carga.load_1)),
+                ("load_5", real(carga.load_5)),
+                ("load_15", real(carga.load_15)),
+                ("context_switches", entero(carga.context_switches)),
+                ("interrupts", entero(carga.interrupts)),
+                ("context_switches_per_sec", carga.context_switches_per_sec.and_then(real)),
+                ("interrupts_per_sec", carga.interrupts_per_sec.and_then(real)),
+                ("procs_running", entero(carga.procs_running)),
+                ("procs_blocked", entero(carga.procs_blocked)),
+            ],
+        );
+        let presion = carga.pressure.as_ref();
+        let recursos = presion.map(|p| [("cpu", &p.cpu), ("memory", &p.memory), ("io", &p.io)]);
+        for (recurso, datos) in recursos.into_iter().flatten() {
+            let Some(datos) = datos else {
+                continue;
+            };
+            for (tipo, promedios) in [("some", Some(&datos.some)), ("full", datos.full.as_ref())] {
+                let Some(PromediosPresion { avg10, avg60, avg300, total_us }) = promedios else {
+                    continue;
+                };
+                p.punto(
+                    "pressure",
+                    &[("resource", Some(recurso.to_string())), ("kind", Some(tipo.to_string()))],
+                    vec![("avg10", real(*avg10)), ("avg60", real(*avg60)), ("avg300", real(*avg300)), ("total_us", entero(*total_us))],
+                );
+            }
+        }
+    }
+    if let Some(memoria) = &datos.memory {
+        let detalle = memoria.breakdown.as_ref();
+        p.punto(
+            "memory",
+            &[],
+            vec![
+                ("total_bytes", bytes(memoria.total_bytes)),
+                ("used_bytes", bytes(memoria.used_bytes)),
+                ("free_bytes", bytes(memoria.free_bytes)),
+                ("available_bytes", memoria.available_bytes.and_then(bytes)),
+                ("total_swap_bytes", bytes(memoria.total_swap_bytes)),
+                ("used_swap_bytes", bytes(memoria.used_swap_bytes)),
+                ("cached_bytes", detalle.and_then(|d| bytes(d.cached_bytes))),
+                ("buffers_bytes", detalle.and_then(|d| bytes(d.buffers_bytes))),
+                ("shared_bytes", detalle.and_then(|d| bytes(d.shared_bytes))),
+                ("slab_bytes", detalle.and_then(|d| bytes(d.slab_bytes))),
+                ("dirty_bytes", detalle.and_then(|d| bytes(d.dirty_bytes))),
+                ("writeback_bytes", detalle.and_then(|d| bytes(d.writeback_bytes))),
+                ("hugepages_total", detalle.and_then(|d| entero(d.hugepages_total))),
+                ("hugepages_free", detalle.and_then(|d| entero(d.hugepages_free))),
+                ("hugepage_size_bytes", detalle.and_then(|d| bytes(d.hugepage_size_bytes))),
+                ("swap_in_bytes_per_sec", detalle.and_then(|d| tasa(d.swap_in_bytes_per_sec))),
+                ("swap_out_bytes_per_sec", detalle.and_then(|d| tasa(d.swap_out_bytes_per_sec))),
+            ],
+        );
+    }
+    if let Some(red) = &datos.network {
+        p.punto(
+            "network",
+            &[],
+            vec![
+                ("received_bytes", bytes(red.received_bytes)),
+                ("transmitted_bytes", bytes(red.transmitted_bytes)),
+                ("rx_bytes_per_sec", tasa(red.rx_bytes_per_sec)),
+                ("tx_bytes_per_sec", tasa(red.tx_bytes_per_sec)),
+            ],
+        );
+        for interfaz in &red.interfaces {
+            p.punto(
+                "network_interface",
+                &[("interface", Some(interfaz.name.clone()))],
+                vec![
+                    ("received_bytes", bytes(interfaz.received_bytes)),
+                    ("transmitted_bytes", bytes(interfaz.transmitted_bytes)),
+                    ("rx_bytes_per_sec", tasa(interfaz.rx_bytes_per_sec)),
+                    ("tx_bytes_per_sec", tasa(interfaz.tx_bytes_per_sec)),
+                    ("rx_packets", entero(interfaz.rx_packets)),
+                    ("tx_packets", entero(interfaz.tx_packets)),
+                    ("rx_errors", entero(interfaz.rx_errors)),
+                    ("tx_errors", entero(interfaz.tx_errors)),
+                    ("rx_dropped", interfaz.rx_dropped.and_then(entero)),
+                    ("tx_dropped", interfaz.tx_dropped.and_then(entero)),
+                ],
+            );
+        }
+    }
+    if let Some(sockets) = &datos.sockets {
+        p.punto("sockets", &[], vec![("udp_sockets", entero(sockets.udp_sockets))]);
+        for (estado, n) in &sockets.tcp_states {
+            p.punto("tcp_state", &[("state", Some(estado.clone()))], vec![("sockets", entero(*n))]);
+        }
+        for puerto in &sockets.listening {
+            p.punto(
+                "listening_socket",
+                &[
+                    ("protocol", Some(puerto.protocol.clone())),
+                    ("address", Some(puerto.address.clone())),
+                    ("port", Some(puerto.port.to_string())),
+                    ("process", puerto.process.clone()),
+                ],
+                vec![("pid", puerto.pid.and_then(entero)), ("listening", entero(1u64))],
+            );
+        }
+        for par in &sockets.top_peers {
+            p.punto("tcp_peer", &[("address", Some(par.address.clone()))], vec![("connections", entero(par.connections))]);
+        }
+    }
+    if let Some(disco) = &datos.disk {
+        p.punto(
+            "disk",
+            &[],
+            vec![
+                ("read_bytes", bytes(disco.read_bytes)),
+                ("written_bytes", bytes(disco.written_bytes)),
+                ("read_bytes_per_sec", tasa(disco.read_bytes_per_sec)),
+                ("write_bytes_per_sec", tasa(disco.write_bytes_per_sec)),
+            ],
+        );
+        for dispositivo in &disco.devices {
+            p.punto(
+                "disk_device",
+                &[("device", Some(dispositivo.name.clone()))],
+                vec![
+                    ("read_bytes", bytes(dispositivo.read_bytes)),
+                    ("written_bytes", bytes(dispositivo.written_bytes)),
+                    ("read_bytes_per_sec", tasa(dispositivo.read_bytes_per_sec)),
+                    ("write_bytes_per_sec", tasa(dispositivo.write_bytes_per_sec)),
+                    ("reads", entero(dispositivo.reads)),
+                    ("writes", entero(dispositivo.writes)),
+                    ("busy_time_ms", dispositivo.busy_time_ms.and_then(entero)),
+                ],
+            );
+        }
+    }
+    for estadisticas in datos.disk_stats.iter().flatten() {
+        p.punto(
+            "disk_stats",
+            &[("device", Some(estadisticas.name.clone()))],
+            vec![
+                ("in_flight", entero(estadisticas.in_flight)),
+                ("reads_per_sec", estadisticas.reads_per_sec.and_then(real)),
+                ("writes_per_sec", estadisticas.writes_per_sec.and_then(real)),
+                ("read_latency_ms", estadisticas.read_latency_ms.and_then(real)),
+                ("write_latency_ms", estadisticas.write_latency_ms.and_then(real)),
+                ("avg_queue_depth", estadisticas.avg_queue_depth.and_then(real)),
+                ("utilization_percent", estadisticas.utilization_percent.and_then(real)),
+            ],
+        );
+    }
+    for sistema in datos.filesystems.iter().flatten() {
+        p.punto(
+            "filesystem",
+            &[
+                ("device", Some(sistema.device.clone())),
+                ("mount_point", Some(sistema.mount_point.clone())),
+                ("fs_type", Some(sistema.fs_type.clone())),
+            ],
+            vec![
+                ("total_bytes", bytes(sistema.total_bytes)),
+                ("used_bytes", bytes(sistema.used_bytes)),
+                ("available_bytes", bytes(sistema.available_bytes)),
+                ("inodes_total", sistema.inodes_total.and_then(entero)),
+                ("inodes_used", sistema.inodes_used.and_then(entero)),
+                ("inodes_free", sistema.inodes_free.and_then(entero)),
+            ],
+        );
+    }
+    for sensor in datos.temperatures.iter().flatten() {
+        p.punto(
+            "temperature",
+            &[("sensor", Some(sensor.label.clone()))],
+            vec![
+                ("temperature_celsius", real(sensor.temperature_celsius)),
+                ("max_celsius", sensor.max_celsius.and_then(real)),
+                ("critical_celsius", sensor.critical_celsius.and_then(real)),
+            ],
+        );
+    }
+    //El lugar en el top va en las etiquetas para que dos procesos con el mismo nombre no se pisen
+    let tops = [("cpu", &datos.top_processes), ("memory", &datos.top_processes_by_memory), ("disk_io", &datos.top_processes_by_disk_io)];
+    for (orden, top) in tops {
+        for (i, proceso) in top.iter().flatten().enumerate() {
+            p.punto(
+                "process",
+                &[
+                    ("by", Some(orden.to_string())),
+                    ("rank", Some((i + 1).to_string())),
+                    ("name", Some(proceso.name.clone())),
+                    ("user", proceso.user.clone()),
+                    ("container_id", proceso.container_id.clone()),
+                ],
+                vec![
+                    ("pid", proceso.pid.and_then(entero)),
+                    ("ppid", proceso.ppid.and_then(entero)),
+                    ("cpu_usage_percent", real(proceso.cpu_usage_percent)),
+                    ("memory_bytes", bytes(proceso.memory_bytes)),
+                    ("threads", proceso.threads.and_then(entero)),
+                    ("open_fds", proceso.open_fds.and_then(entero)),
+                    ("disk_read_bytes", proceso.disk_read_bytes.and_then(bytes)),
+                    ("disk_written_bytes", proceso.disk_written_bytes.and_then(bytes)),
+                    ("disk_read_bytes_per_sec", tasa(proceso.disk_read_bytes_per_sec)),
+                    ("disk_write_bytes_per_sec", tasa(proceso.disk_write_bytes_per_sec)),
+                    ("start_time", proceso.start_time.as_deref().and_then(texto)),
+                    ("status", proceso.status.as_deref().and_then(texto)),
+                    ("exe", proceso.exe.as_deref().and_then(texto)),
+                    ("cmd", (!proceso.cmd.is_empty()).then(|| Valor::Texto(proceso.cmd.join(" ")))),
+                    ("cgroup", proceso.cgroup.as_deref().and_then(texto)),
+                ],
+            );
+        }
+    }
+    for usuario in datos.per_user.iter().flatten() {
+        p.punto(
+            "user",
+            &[("user", Some(usuario.user.clone()))],
+            vec![
+                ("processes", entero(usuario.processes)),
+                ("cpu_usage_percent", real(usuario.cpu_usage_percent)),
+                ("memory_bytes", bytes(usuario.memory_bytes)),
+                ("disk_read_bytes_per_sec", tasa(usuario.disk_read_bytes_per_sec)),
+                ("disk_write_bytes_per_sec", tasa(usuario.disk_write_bytes_per_sec)),
+            ],
+        );
+    }
+    for cgroup in datos.cgroups.iter().flatten() {
+        let estrangulamiento = cgroup.throttling.as_ref();
+        let eventos = cgroup.memory_events.as_ref();
+        p.punto(
+            "cgroup",
+            &[("path", Some(cgroup.path.clone())), ("container_id", cgroup.container_id.clone())],
+            vec![
+                ("cpu_usage_usec", entero(cgroup.cpu_usage_usec)),
+                ("cpu_usage_percent", cgroup.cpu_usage_percent.and_then(real)),
+                ("nr_periods", estrangulamiento.and_then(|t| entero(t.nr_periods))),
+                ("nr_throttled", estrangulamiento.and_then(|t| entero(t.nr_throttled))),
+                ("throttled_usec", estrangulamiento.and_then(|t| entero(t.throttled_usec))),
+                ("memory_current_bytes", cgroup.memory_current_bytes.and_then(bytes)),
+                ("memory_max_bytes", cgroup.memory_max_bytes.and_then(bytes)),
+                ("memory_events_low", eventos.and_then(|e| entero(e.low))),
+                ("memory_events_high", eventos.and_then(|e| entero(e.high))),
+                ("memory_events_max", eventos.and_then(|e| entero(e.max))),
+                ("memory_events_oom", eventos.and_then(|e| entero(e.oom))),
+                ("memory_events_oom_kill", eventos.and_then(|e| entero(e.oom_kill))),
+                ("io_read_bytes", cgroup.io_read_bytes.and_then(bytes)),
+                ("io_written_bytes", cgroup.io_written_bytes.and_then(bytes)),
+            ],
+        );
+    }
+    if let Some(propio) = &datos.self_overhead {
+        p.punto(
+            "self_overhead",
+            &[],
+            vec![
+                ("pid", entero(propio.pid)),
+                ("cpu_usage_percent", real(propio.cpu_usage_percent)),
+                ("cpu_time_secs", propio.cpu_time_secs.and_then(real)),
+                ("rss_bytes", bytes(propio.rss_bytes)),
+            ],
+        );
+    }
+    p.puntos
+}
+
+//Nanosegundos desde 1970 del timestamp de la muestra
+fn nanosegundos(datos: &Datosuwu) -> Option<i64> {
+    DateTime::parse_from_rfc3339(&datos.timestamp).ok()?.timestamp_nanos_opt()
+}
+
+//En la medición se escapan comas y espacios; en etiquetas, además el signo igual
+fn escapar_influx(texto: &str, igual: bool) -> String {
+    let mut escapado = String::with_capacity(texto.len());
+    for c in texto.chars() {
+        match c {
+            ',' | ' ' | '\\' => escapado.push('\\'),
+            '=' if igual => escapado.push('\\'),
+            //Un salto de línea terminaría la línea; no se puede escapar y queda como un espacio (escapado)
+            '\n' | '\r' => {
+                escapado.push_str("\\ ");
+                continue;
+            }
+            _ => {}
+        }
+        escapado.push(c);
+    }
+    escapado
+}
+
+impl Punto {
+    //"cpu_core,host=vm,core=0 usage_percent=3.2 1744319332123000000"
+    pub fn influx(&self, nanosegundos: Option<i64>) -> String {
+        let mut linea = escapar_influx(self.medicion, false);
+        //Una etiqueta vacía no es válida en el line protocol; la que falta simplemente no va
+        for (nombre, valor) in self.etiquetas.iter().filter_map(|(nombre, valor)| Some((nombre, valor.as_ref()?))) {
+            linea.push(',');
+            linea.push_str(&escapar_influx(nombre, true));
+            linea.push('=');
+            linea.push_str(&escapar_influx(valor, true));
+        }
+        let campos: Vec<String> = self
+            .campos
+            .iter()
+            .map(|(nombre, valor)| {
+                let valor = match valor {
+                    Valor::Entero(n) => format!("{}i", n),
+                    Valor::Real(n) => n.to_string(),
+                    Valor::Texto(t) => format!("\"{}\"", t.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', " ")),
+                };
+                format!("{}={}", escapar_influx(nombre, true), valor)
+            })
+            .collect();
+        linea.push(' ');
+        linea.push_str(&campos.join(","));
+        if let Some(ns) = nanosegundos {
+            linea.push_str(&format!(" {}", ns));
+        }
+        linea
+    }
+
+    //"act4.vm.cpu_core.0.usage_percent 3.2 1744319332", una línea por campo numérico. La ruta es el prefijo, el
+    //equipo (host_label si está configurado, si no host), la medición y los valores de las demás etiquetas en orden.
+    //Una etiqueta que falta queda como "none", para que cada nodo signifique lo mismo en todas las rutas de la medición
+    pub fn graphite(&self, prefijo: &str, segundos: Option<i64>) -> Vec<String> {
+        let etiqueta =
+            |nombre: &str| self.etiquetas.iter().find(|(n, _)| *n == nombre).and_then(|(_, v)| v.as_deref());
+        let mut ruta: Vec<String> = prefijo.split('.').filter(|n| !n.is_empty()).map(nodo_graphite).collect();
+        ruta.push(nodo_graphite(etiqueta("host_label").or(etiqueta("host")).unwrap_or("unknown")));
+        ruta.push(self.medicion.to_string());
+        ruta.extend(
+            self.etiquetas
+                .iter()
+                .filter(|(nombre, _)| *nombre != "host" && *nombre != "host_label")
+                .map(|(_, valor)| valor.as_deref().map(nodo_graphite).unwrap_or_else(|| "none".to_string())),
+        );
+        let momento = segundos.map(|s| s.to_string()).unwrap_or_else(|| "-1".to_string());
+        self.campos
+            .iter()
+            .filter_map(|(nombre, valor)| {
+                let valor = match valor {
+                    Valor::Entero(n) => n.to_string(),
+                    Valor::Real(n) => n.to_string(),
+                    Valor::Texto(_) => return None,
+                };
+                Some(format!("{}.{} {} {}", ruta.join("."), nombre, valor, momento))
+            })
+            .collect()
+    }
+}
+
+//Cada nodo de la ruta solo puede tener letras, números, _ y -; "/" (la raíz de un sistema de archivos) queda como "root"
+fn nodo_graphite(texto: &str) -> String {
+    let nodo: String = texto
+        .trim_matches('/')
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .collect();
+    if nodo.is_empty() { "root".to_string() } else { nodo }
+}
+
+//Las líneas del line protocol de una muestra, separadas por saltos de línea
+pub fn influx(datos: &Datosuwu) -> String {
+    let nanosegundos = nanosegundos(datos);
+    puntos(datos).iter().map(|p| p.influx(nanosegundos)).collect::<Vec<_>>().join("\n")
+}
+
+//Las líneas de Graphite de una muestra (solo los campos numéricos), separadas por saltos de línea
+pub fn graphite(datos: &Datosuwu, prefijo: &str) -> String {
+    let segundos = nanosegundos(datos).map(|ns| ns.div_euclid(1_000_000_000));
+    puntos(datos).iter().flat_map(|p| p.graphite(prefijo, segundos)).collect::<Vec<_>>().join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //Un proceso del top sin usuario (vacío) y otro con todas sus etiquetas
+    fn procesos() -> Vec<Punto> {
+        let mut p = Puntos { puntos: Vec::new(), comunes: vec![("host", Some("vm".to_string())), ("host_label", None)] };
+        for usuario in ["", "www"] {
+            p.punto(
+                "process",
+                &[
+                    ("by", Some("cpu".to_string())),
+                    ("rank", Some("1".to_string())),
+                    ("name", Some("nginx".to_string())),
+                    ("user", Some(usuario.to_string())),
+                    ("container_id", Some("abc".to_string())),
+                ],
+                vec![("pid", entero(10u64)), ("exe", texto("/usr/sbin/nginx"))],
+            );
+        }
+        p.puntos
+    }
+
+    #[test]
+    fn graphite_marca_las_etiquetas_que_faltan() {
+        let puntos = procesos();
+        assert_eq!(puntos[0].graphite("act4", Some(1_700_000_000)), ["act4.vm.process.cpu.1.nginx.none.abc.pid 10 1700000000"]);
+        assert_eq!(puntos[1].graphite("act4", Some(1_700_000_000)), ["act4.vm.process.cpu.1.nginx.www.abc.pid 10 1700000000"]);
+        //Sin timestamp Graphite usa la hora de llegada
+        assert_eq!(puntos[0].graphite("a.b.", None), ["a.b.vm.process.cpu.1.nginx.none.abc.pid 10 -1"]);
+    }
+
+    #[test]
+    fn influx_omite_las_etiquetas_que_faltan() {
+        let puntos = procesos();
+        assert_eq!(
+            puntos[0].influx(Some(1_700_000_000_000_000_000)),
+            r#"process,host=vm,by=cpu,rank=1,name=nginx,container_id=abc pid=10i,exe="/usr/sbin/nginx" 1700000000000000000"#
+        );
+        assert_eq!(
+            puntos[1].influx(None),
+            r#"process,host=vm,by=cpu,rank=1,name=nginx,user=www,container_id=abc pid=10i,exe="/usr/sbin/nginx""#
+        );
+    }
+
+    #[test]
+    fn escapes_del_line_protocol() {
+        assert_eq!(escapar_influx("disk io,total", false), r"disk\ io\,total");
+        //El igual solo se escapa en etiquetas y nombres de campo
+        assert_eq!(escapar_influx("a=b", false), "a=b");
+        assert_eq!(escapar_influx("a=b c", true), r"a\=b\ c");
+        assert_eq!(escapar_influx(r"C:\tmp", true), r"C:\\tmp");
+        assert_eq!(escapar_influx("dos\nlíneas\r", true), r"dos\ líneas\ ");
+    }
+}
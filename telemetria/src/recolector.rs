@@ -0,0 +1,189 @@
+//Recolección de una muestra Datosuwu: ejecuta los colectores habilitados a los que les toca según su intervalo
+use std::time::{Duration, Instant};
+use chrono::Utc;
+use sysinfo::System;
+use futures::future::join_all;
+use crate::colectores::{self, Collector, Seccion};
+use crate::config::{ConfigColector, ConfigRecolector};
+use crate::datos::{rfc3339, Datosuwu, Equipo, ErrorColector};
+use crate::eventos::Evento;
+
+//Ventana entre la muestra base y la primera muestra real; sysinfo necesita dos lecturas para calcular el % de CPU
+pub const VENTANA_MEDICION: Duration = Duration::from_secs(1);
+//Margen para que un colector con intervalo de 60 s no se salte una muestra por llegar a los 59.9 s
+const HOLGURA: Duration = Duration::from_millis(500);
+
+struct Planificado {
+    colector: Box<dyn Collector>,
+    config: ConfigColector,
+    ultimo: Option<Instant>,
+}
+
+impl Planificado {
+    fn toca(&self, ahora: Instant) -> bool {
+        match (self.ultimo, self.config.intervalo) {
+            (Some(ultimo), Some(intervalo)) => ahora.duration_since(ultimo) + HOLGURA >= intervalo,
+            _ => true,
+        }
+    }
+
+    //Ejecuta el colector con su límite de tiempo; un colector lento o con error no detiene a los demás
+    async fn ejecutar(&mut self, ahora: Instant) -> Result<Seccion, String> {
+        self.ultimo = Some(ahora);
+        match tokio::time::timeout(self.config.limite, self.colector.recolectar()).await {
+            Ok(resultado) => resultado,
+            Err(_) => Err(format!("no respondió en {:?}", self.config.limite)),
+        }
+    }
+}
+
+pub struct Recolector {
+    colectores: Vec<Planificado>,
+    config: ConfigRecolector,
+    ultima_muestra: Option<Instant>,
+    //Errores de la lectura base; se reportan en la siguiente muestra
+    pendientes: Vec<ErrorColector>,
+    //Eventos de los colectores en la última muestra
+    eventos: Vec<Evento>,
+    //Colectores a los que les tocó la última muestra, hayan salido bien o no
+    ejecutados: Vec<&'static str>,
+    //Se lee una vez; nada de esto cambia sin reiniciar el equipo (o el recolector)
+    equipo: Equipo,
+    //Número de la siguiente muestra
+    secuencia: u64,
+}
+
+impl Recolector {
+    pub fn new() -> Recolector {
+        let config = ConfigRecolector::default();
+        Recolector::armar(colectores::todos(&config), config)
+    }
+
+    //Los colectores que no aparecen en `config.colectores` usan la configuración por defecto (habilitados, en cada muestra)
+    pub fn con_config(config: ConfigRecolector) -> Result<Recolector, String> {
+        config.validar()?;
+        let todos = colectores::todos(&config);
+        if let Some(desconocido) = config.colectores.keys().find(|nombre| !todos.iter().any(|c| c.nombre() == nombre.as_str())) {
+            let nombres: Vec<_> = todos.iter().map(|c| c.nombre()).collect();
+            return Err(format!("colector desconocido: {} (disponibles: {})", desconocido, nombres.join(", ")));
+        }
+        Ok(Recolector::armar(todos, config))
+    }
+
+    fn armar(todos: Vec<Box<dyn Collector>>, config: ConfigRecolector) -> Recolector {
+        let colectores = todos
+            .into_iter()
+            .map(|colector| {
+                let config = config.colectores.get(colector.nombre()).cloned().unwrap_or_default();
+                Planificado { colector, config, ultimo: None }
+            })
+            .filter(|p| p.config.habilitado)
+            .collect();
+        let equipo = equipo(&config);
+        Recolector {
+            colectores,
+            config,
+            ultima_muestra: None,
+            pendientes: Vec::new(),
+            eventos: Vec::new(),
+            ejecutados: Vec::new(),
+            equipo,
+            secuencia: 0,
+        }
+    }
+
+    //Para que `sequence` siga después de la última muestra que ya está en el archivo
+    pub fn continuar_secuencia(&mut self, ultima: u64) {
+        self.secuencia = ultima + 1;
+    }
+
+    //Toma la lectura base de cada colector; las tasas de la siguiente muestra se calculan contra ella
+    pub async fn preparar(&mut self) {
+        for p in &mut self.colectores {
+            let error = match tokio::time::timeout(p.config.limite, p.colector.preparar()).await {
+                Ok(Ok(())) => continue,
+                Ok(Err(e)) => e,
+                Err(_) => format!("no respondió en {:?}", p.config.limite),
+            };
+            self.pendientes.push(ErrorColector {
+                collector: p.colector.nombre().to_string(),
+                message: format!("lectura base: {}", error),
+            });
+        }
+        self.ultima_muestra = Some(Instant::now());
+    }
+
+    pub async fn obtener_datos(&mut self) -> Datosuwu {
+        let ahora = Instant::now();
+        let interval_secs = self
+            .ultima_muestra
+            .replace(ahora)
+            .map(|anterior| ahora.duration_since(anterior).as_secs_f64())
+            .unwrap_or(0.0);
+        let momento = Utc::now();
+        let mut datos = Datosuwu::vacia(rfc3339(momento), interval_secs);
+        datos.local_time = Some(self.config.timestamp(momento));
+        datos.sequence = Some(self.secuencia);
+        self.secuencia += 1;
+        datos.host_label = self.config.host_label.clone();
+        datos.host = Some(self.equipo.clone());
+        datos.collector_version = Some(env!("CARGO_PKG_VERSION").to_string());
+        datos.errors.append(&mut self.pendientes);
+        //Los colectores a los que les toca se ejecutan a la vez
+        let resultados = join_all(
+            self.colectores
+                .iter_mut()
+                .filter(|p| p.toca(ahora))
+                .map(|p| async move { (p.colector.nombre(), p.ejecutar(ahora).await) }),
+        )
+        .await;
+        self.ejecutados = resultados.iter().map(|(nombre, _)| *nombre).collect();
+        for (nombre, resultado) in resultados {
+            match resultado {
+                Ok(seccion) => seccion.aplicar(&mut datos),
+                Err(message) => datos.errors.push(ErrorColector { collector: nombre.to_string(), message }),
+            }
+        }
+        //Lo que faltó en una sección que sí salió
+        for p in &mut self.colectores {
+            let collector = p.colector.nombre();
+            for message in p.colector.errores() {
+                datos.errors.push(ErrorColector { collector: collector.to_string(), message });
+            }
+        }
+        //Los eventos que nadie tomó de la muestra anterior se descartan, así no se acumulan (en monitoreo, por ejemplo)
+        self.eventos = self.colectores.iter_mut().flat_map(|p| p.colector.eventos()).collect();
+        datos
+    }
+
+    //Para saber qué secciones vacías de la última muestra son de un colector que falló y cuáles de uno al que
+    //no le tocaba
+    pub fn ejecutados(&self) -> &[&'static str] {
+        &self.ejecutados
+    }
+
+    //Eventos de la última muestra; después de tomarlos quedan vacíos
+    pub fn tomar_eventos(&mut self) -> Vec<Evento> {
+        std::mem::take(&mut self.eventos)
+    }
+}
+
+//machine-id y boot_id solo existen en Linux (boot_id se lee del /proc configurado)
+fn equipo(config: &ConfigRecolector) -> Equipo {
+    let leer = |ruta: &std::path::Path| {
+        std::fs::read_to_string(ruta).ok().map(|t| t.trim().to_string()).filter(|t| !t.is_empty())
+    };
+    Equipo {
+        hostname: System::host_name(),
+        machine_id: leer("/etc/machine-id".as_ref()).or_else(|| leer("/var/lib/dbus/machine-id".as_ref())),
+        boot_id: leer(&config.raiz_proc.join("sys/kernel/random/boot_id")),
+        os: System::long_os_version(),
+        kernel_version: System::kernel_version(),
+    }
+}
+
+impl Default for Recolector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,452 @@
+//Rotación y retención de datosuwu.jsonl
+//El archivo activo es siempre el de la ruta configurada (datosuwu.jsonl); al rotar se renombra como
+//datosuwu.<AAAAmmdd-HHMMSS>.jsonl con la fecha de su última escritura, y se comprime si así se configuró
+//(datosuwu.<AAAAmmdd-HHMMSS>.jsonl.gz o .zst). Así el orden alfabético de los segmentos es el cronológico.
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+use chrono::{DateTime, Local};
+use serde::Deserialize;
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Periodo {
+    Hourly,
+    Daily,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Compresion {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl FromStr for Periodo {
+    type Err = String;
+
+    fn from_str(texto: &str) -> Result<Self, Self::Err> {
+        match texto {
+            "hourly" => Ok(Periodo::Hourly),
+            "daily" => Ok(Periodo::Daily),
+            otro => Err(format!("periodo de rotación inválido: {} (hourly o daily)", otro)),
+        }
+    }
+}
+
+impl FromStr for Compresion {
+    type Err = String;
+
+    fn from_str(texto: &str) -> Result<Self, Self::Err> {
+        match texto {
+            "none" => Ok(Compresion::None),
+            "gzip" => Ok(Compresion::Gzip),
+            "zstd" => Ok(Compresion::Zstd),
+            otro => Err(format!("compresión inválida: {} (none, gzip o zstd)", otro)),
+        }
+    }
+}
+
+impl Compresion {
+    fn extension(self) -> &'static str {
+        match self {
+            Compresion::None => "",
+            Compresion::Gzip => ".gz",
+            Compresion::Zstd => ".zst",
+        }
+    }
+}
+
+//Sin límites no se rota nunca (el comportamiento de siempre)
+#[derive(Clone, Debug, Default)]
+pub struct PoliticaRotacion {
+    //Rota antes de que el archivo activo pase de este tamaño
+    pub max_bytes: Option<u64>,
+    //Rota cuando la última escritura fue en otra hora o en otro día (hora local)
+    pub periodo: Option<Periodo>,
+    pub compresion: Compresion,
+    //Segmentos rotados que se conservan; los más viejos se borran
+    pub max_archivos: Option<usize>,
+    pub max_antiguedad: Option<Duration>,
+}
+
+pub struct EscritorRotativo {
+    ruta: PathBuf,
+    politica: PoliticaRotacion,
+}
+
+impl EscritorRotativo {
+    pub fn new(ruta: PathBuf, politica: PoliticaRotacion) -> EscritorRotativo {
+        EscritorRotativo { ruta, politica }
+    }
+
+    pub fn ruta(&self) -> &Path {
+        &self.ruta
+    }
+
+    //Agrega una línea al archivo activo, rotando antes si toca. Si la rotación falla la línea se escribe
+    //de todos modos en el archivo activo y el error de rotación se devuelve como aviso
+    pub fn escribir_linea(&mut self, linea: &str) -> io::Result<Option<String>> {
+        let aviso = match self.rotar_si_toca(linea.len() as u64 + 1) {
+            Ok(()) => None,
+            Err(e) => Some(format!("no se pudo rotar {}: {}", self.ruta.display(), e)),
+        };
+        let mut archivo = OpenOptions::new().append(true).create(true).open(&self.ruta)?;
+        writeln!(archivo, "{}", linea)?;
+        Ok(aviso)
+    }
+
+    fn rotar_si_toca(&self, por_escribir: u64) -> io::Result<()> {
+        let metadatos = match fs::metadata(&self.ruta) {
+            Ok(m) => m,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        if metadatos.len() == 0 {
+            return Ok(());
+        }
+        let ultima_escritura: DateTime<Local> = metadatos.modified()?.into();
+        let por_tamano = self.politica.max_bytes.is_some_and(|max| metadatos.len() + por_escribir > max);
+        let por_periodo = match self.politica.periodo {
+            Some(Periodo::Daily) => ultima_escritura.format("%Y%m%d").to_string() != Local::now().format("%Y%m%d").to_string(),
+            Some(Periodo::Hourly) => ultima_escritura.format("%Y%m%d%H").to_string() != Local::now().format("%Y%m%d%H").to_string(),
+            None => false,
+        };
+        if por_tamano || por_periodo {
+            self.rotar(ultima_escritura)?;
+            self.aplicar_retencion()?;
+        }
+        Ok(())
+    }
+
+    fn rotar(&self, ultima_escritura: DateTime<Local>) -> io::Result<()> {
+        let (directorio, base, extension) = partes(&self.ruta);
+        let sello = ultima_escritura.format("%Y%m%d-%H%M%S").to_string();
+        //Si ya hay segmentos del mismo segundo se agrega un contador mayor que el de todos ellos. No alcanza con el
+        //primer nombre libre: la retención pudo borrar los primeros y el nuevo quedaría ordenado como el más viejo
+        let contador =
+            rotados(&self.ruta)?.into_iter().filter(|(fecha, _, _)| *fecha == sello).map(|(_, n, _)| n + 1).max();
+        let nombre = match contador {
+            Some(n) => nombre_segmento(&base, &format!("{}-{}", sello, n), &extension),
+            None => nombre_segmento(&base, &sello, &extension),
+        };
+        let rotado = directorio.join(&nombre);
+        fs::rename(&self.ruta, &rotado)?;
+        if self.politica.compresion != Compresion::None {
+            comprimir(&rotado, self.politica.compresion)?;
+        }
+        Ok(())
+    }
+
+    fn aplicar_retencion(&self) -> io::Result<()> {
+        let mut rotados = segmentos_rotados(&self.ruta)?;
+        if let Some(max) = self.politica.max_antiguedad {
+            let limite = SystemTime::now() - max;
+            rotados.retain(|ruta| {
+                let viejo = fs::metadata(ruta).and_then(|m| m.modified()).is_ok_and(|t| t < limite);
+                !(viejo && fs::remove_file(ruta).is_ok())
+            });
+        }
+        if let Some(max) = self.politica.max_archivos {
+            let sobrantes = rotados.len().saturating_sub(max);
+            for ruta in &rotados[..sobrantes] {
+                fs::remove_file(ruta)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+//("dir", "datosuwu", "jsonl") de "dir/datosuwu.jsonl"
+fn partes(ruta: &Path) -> (PathBuf, String, String) {
+    let directorio = match ruta.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let base = ruta.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let extension = ruta.extension().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    (directorio, base, extension)
+}
+
+fn nombre_segmento(base: &str, sello: &str, extension: &str) -> String {
+    if extension.is_empty() {
+        format!("{}.{}", base, sello)
+    } else {
+        format!("{}.{}.{}", base, sello, extension)
+    }
+}
+
+//Escribe la versión comprimida junto al segmento y borra el original; si algo falla el original se queda
+fn comprimir(ruta: &Path, compresion: Compresion) -> io::Result<()> {
+    let destino = PathBuf::from(format!("{}{}", ruta.display(), compresion.extension()));
+    let temporal = PathBuf::from(format!("{}.tmp", destino.display()));
+    let mut origen = File::open(ruta)?;
+    let salida = File::create(&temporal)?;
+    let resultado = match compresion {
+        Compresion::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(salida, flate2::Compression::default());
+            io::copy(&mut origen, &mut encoder).and_then(|_| encoder.finish()).map(|_| ())
+        }
+        Compresion::Zstd => zstd::stream::copy_encode(&mut origen, salida, 0),
+        Compresion::None => Ok(()),
+    };
+    if let Err(e) = resultado {
+        let _ = fs::remove_file(&temporal);
+        return Err(e);
+    }
+    fs::rename(&temporal, &destino)?;
+    fs::remove_file(ruta)
+}
+
+//Segmentos rotados de `ruta`, del más viejo al más nuevo (sin el archivo activo)
+pub fn segmentos_rotados(ruta: &Path) -> io::Result<Vec<PathBuf>> {
+    Ok(rotados(ruta)?.into_iter().map(|(_, _, ruta)| ruta).collect())
+}
+
+//Fecha, contador y ruta de cada segmento rotado, en orden
+fn rotados(ruta: &Path) -> io::Result<Vec<(String, u32, PathBuf)>> {
+    let (directorio, base, extension) = partes(ruta);
+    let prefijo = format!("{}.", base);
+    let sufijo = if extension.is_empty() { String::new() } else { format!(".{}", extension) };
+    let mut segmentos = Vec::new();
+    for entrada in fs::read_dir(&directorio)? {
+        let nombre = entrada?.file_name().to_string_lossy().into_owned();
+        let sin_compresion = nombre
+            .strip_suffix(".gz")
+            .or_else(|| nombre.strip_suffix(".zst"))
+            .unwrap_or(&nombre);
+        let sello = sin_compresion.strip_prefix(&prefijo).and_then(|resto| resto.strip_suffix(&sufijo));
+        //AAAAmmdd-HHMMSS, con "-N" si hubo más de una rotación en el mismo segundo
+        let Some(sello) = sello.filter(|s| s.len() >= 15 && s.chars().all(|c| c.is_ascii_digit() || c == '-')) else {
+            continue;
+        };
+        let (fecha, contador) = sello.split_at(15);
+        let contador: u32 = contador.strip_prefix('-').and_then(|n| n.parse().ok()).unwrap_or(0);
+        segmentos.push((fecha.to_string(), contador, directorio.join(&nombre)));
+    }
+    segmentos.sort();
+    Ok(segmentos)
+}
+
+//Todos los segmentos en orden cronológico, con el archivo activo al final si existe
+pub fn segmentos(ruta: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut todos = segmentos_rotados(ruta)?;
+    if ruta.exists() {
+        todos.push(ruta.to_path_buf());
+    }
+    Ok(todos)
+}
+
+//Abre un segmento, descomprimiéndolo según su extensión
+pub fn abrir(ruta: &Path) -> io::Result<Box<dyn BufRead>> {
+    let archivo = File::open(ruta)?;
+    let nombre = ruta.to_string_lossy();
+    Ok(if nombre.ends_with(".gz") {
+        Box::new(BufReader::new(flate2::read::MultiGzDecoder::new(archivo)))
+    } else if nombre.ends_with(".zst") {
+        Box::new(BufReader::new(zstd::stream::read::Decoder::new(archivo)?))
+    } else {
+        Box::new(BufReader::new(archivo))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lector;
+    use crate::prueba::arbol;
+    use crate::Datosuwu;
+
+    fn lineas(ruta: &Path) -> Vec<String> {
+        abrir(ruta).unwrap().lines().map(Result::unwrap).collect()
+    }
+
+    //Todas las líneas de la serie, de la más vieja a la más nueva
+    fn serie(ruta: &Path) -> Vec<String> {
+        segmentos(ruta).unwrap().iter().flat_map(|s| lineas(s)).collect()
+    }
+
+    fn cambiar_fecha(ruta: &Path, fecha: SystemTime) {
+        File::options().write(true).open(ruta).unwrap().set_modified(fecha).unwrap();
+    }
+
+    fn por_tamano(max_bytes: u64, compresion: Compresion) -> PoliticaRotacion {
+        PoliticaRotacion { max_bytes: Some(max_bytes), compresion, ..PoliticaRotacion::default() }
+    }
+
+    #[test]
+    fn rota_antes_de_pasar_del_tamano() {
+        let directorio = arbol(&[]);
+        let ruta = directorio.path().join("datosuwu.jsonl");
+        //Dos líneas de 40 bytes (más su salto de línea) caben en 100; la tercera ya no
+        let mut escritor = EscritorRotativo::new(ruta.clone(), por_tamano(100, Compresion::None));
+        let escritas: Vec<String> = (0..7).map(|i| format!("{:0>40}", i)).collect();
+        for linea in &escritas {
+            assert!(escritor.escribir_linea(linea).unwrap().is_none());
+        }
+        let rotados = segmentos_rotados(&ruta).unwrap();
+        assert_eq!(rotados.len(), 3);
+        assert!(rotados.iter().all(|r| fs::metadata(r).unwrap().len() <= 100));
+        assert_eq!(lineas(&ruta), [escritas[6].clone()]);
+        assert_eq!(serie(&ruta), escritas);
+    }
+
+    #[test]
+    fn rota_al_cambiar_el_dia_o_la_hora() {
+        let directorio = arbol(&[]);
+        let ruta = directorio.path().join("datosuwu.jsonl");
+        for (periodo, hace, formato) in [(Periodo::Daily, 24 * 60 * 60, "%Y%m%d"), (Periodo::Hourly, 60 * 60, "%Y%m%d-%H")] {
+            let politica = PoliticaRotacion { periodo: Some(periodo), ..PoliticaRotacion::default() };
+            let mut escritor = EscritorRotativo::new(ruta.clone(), politica);
+            escritor.escribir_linea("antes").unwrap();
+            //En el mismo periodo no se rota
+            escritor.escribir_linea("antes también").unwrap();
+            assert!(segmentos_rotados(&ruta).unwrap().is_empty());
+            let entonces = SystemTime::now() - Duration::from_secs(hace);
+            cambiar_fecha(&ruta, entonces);
+            escritor.escribir_linea("después").unwrap();
+            let rotados = segmentos_rotados(&ruta).unwrap();
+            assert_eq!(rotados.len(), 1);
+            //El segmento lleva la fecha de su última escritura
+            let sello = DateTime::<Local>::from(entonces).format(formato).to_string();
+            let nombre = rotados[0].file_name().unwrap().to_string_lossy().into_owned();
+            assert!(nombre.starts_with(&format!("datosuwu.{}", sello)), "{}", nombre);
+            assert_eq!(lineas(&rotados[0]), ["antes", "antes también"]);
+            assert_eq!(lineas(&ruta), ["después"]);
+            fs::remove_file(&rotados[0]).unwrap();
+            fs::remove_file(&ruta).unwrap();
+        }
+    }
+
+    #[test]
+    fn los_segmentos_se_comprimen_y_se_leen_de_vuelta() {
+        for (compresion, extension) in [(Compresion::Gzip, ".jsonl.gz"), (Compresion::Zstd, ".jsonl.zst")] {
+            let directorio = arbol(&[]);
+            let ruta = directorio.path().join("datosuwu.jsonl");
+            let mut escritor = EscritorRotativo::new(ruta.clone(), por_tamano(20, compresion));
+            for linea in ["uno", "dos", "tres", "cuatro", "cinco", "seis"] {
+                escritor.escribir_linea(linea).unwrap();
+            }
+            let rotados = segmentos_rotados(&ruta).unwrap();
+            assert!(!rotados.is_empty());
+            assert!(rotados.iter().all(|r| r.to_string_lossy().ends_with(extension)), "{:?}", rotados);
+            //Sin el original ni el temporal de la compresión
+            let archivos = fs::read_dir(directorio.path()).unwrap().count();
+            assert_eq!(archivos, rotados.len() + 1);
+            assert_eq!(serie(&ruta), ["uno", "dos", "tres", "cuatro", "cinco", "seis"]);
+        }
+    }
+
+    #[test]
+    fn se_conservan_los_segmentos_mas_nuevos() {
+        let directorio = arbol(&[]);
+        let ruta = directorio.path().join("datosuwu.jsonl");
+        let politica = PoliticaRotacion { max_archivos: Some(2), ..por_tamano(10, Compresion::Gzip) };
+        let mut escritor = EscritorRotativo::new(ruta.clone(), politica);
+        for i in 0..6 {
+            escritor.escribir_linea(&format!("linea {}", i)).unwrap();
+        }
+        assert_eq!(segmentos_rotados(&ruta).unwrap().len(), 2);
+        assert_eq!(serie(&ruta), ["linea 3", "linea 4", "linea 5"]);
+    }
+
+    #[test]
+    fn se_borran_los_segmentos_viejos() {
+        let directorio = arbol(&[
+            ("datosuwu.20200101-000000.jsonl", "vieja\n"),
+            ("datosuwu.20200102-000000.jsonl.gz", ""),
+            //Otro archivo en el mismo directorio no se toca aunque sea viejo
+            ("notas.txt", "no es un segmento\n"),
+        ]);
+        let ruta = directorio.path().join("datosuwu.jsonl");
+        let hace_un_mes = SystemTime::now() - Duration::from_secs(30 * 24 * 60 * 60);
+        for viejo in ["datosuwu.20200101-000000.jsonl", "datosuwu.20200102-000000.jsonl.gz", "notas.txt"] {
+            cambiar_fecha(&directorio.path().join(viejo), hace_un_mes);
+        }
+        let politica = PoliticaRotacion {
+            max_antiguedad: Some(Duration::from_secs(7 * 24 * 60 * 60)),
+            ..por_tamano(10, Compresion::None)
+        };
+        let mut escritor = EscritorRotativo::new(ruta.clone(), politica);
+        escritor.escribir_linea("primera").unwrap();
+        escritor.escribir_linea("segunda").unwrap();
+        let rotados = segmentos_rotados(&ruta).unwrap();
+        assert_eq!(rotados.len(), 1);
+        assert_eq!(lineas(&rotados[0]), ["primera"]);
+        assert!(directorio.path().join("notas.txt").exists());
+    }
+
+    #[test]
+    fn orden_de_los_segmentos() {
+        let directorio = arbol(&[
+            ("datosuwu.20240102-000000.jsonl.gz", ""),
+            ("datosuwu.20240101-235959-10.jsonl.zst", ""),
+            ("datosuwu.20240101-235959-2.jsonl", ""),
+            ("datosuwu.20240101-235959.jsonl", ""),
+            ("datosuwu.jsonl", ""),
+            //Ni de otra serie ni con otro sello
+            ("eventosuwu.20240101-000000.jsonl", ""),
+            ("datosuwu.respaldo.jsonl", ""),
+            ("datosuwu.20240101-000000.json", ""),
+        ]);
+        let ruta = directorio.path().join("datosuwu.jsonl");
+        let nombres: Vec<_> = segmentos(&ruta)
+            .unwrap()
+            .iter()
+            .map(|s| s.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            nombres,
+            [
+                "datosuwu.20240101-235959.jsonl",
+                "datosuwu.20240101-235959-2.jsonl",
+                "datosuwu.20240101-235959-10.jsonl.zst",
+                "datosuwu.20240102-000000.jsonl.gz",
+                "datosuwu.jsonl",
+            ]
+        );
+    }
+
+    //Al reiniciar el daemon se sigue escribiendo al final del archivo activo
+    #[test]
+    fn se_continua_el_archivo_existente() {
+        let directorio = arbol(&[("datosuwu.jsonl", "de antes\n")]);
+        let ruta = directorio.path().join("datosuwu.jsonl");
+        EscritorRotativo::new(ruta.clone(), por_tamano(1000, Compresion::None)).escribir_linea("de ahora").unwrap();
+        assert_eq!(lineas(&ruta), ["de antes", "de ahora"]);
+    }
+
+    //La serie se lee de vuelta entera, sin importar cómo se comprimió cada segmento
+    #[test]
+    fn la_serie_se_lee_a_traves_de_los_segmentos() {
+        let directorio = arbol(&[]);
+        let ruta = directorio.path().join("datosuwu.jsonl");
+        let muestra = |i: u64| {
+            let mut datos = Datosuwu::vacia(format!("2024-05-01T12:00:{:02}.000Z", i), 1.0);
+            datos.sequence = Some(i);
+            serde_json::to_string(&datos).unwrap()
+        };
+        //Dos muestras por segmento
+        let max_bytes = 2 * (muestra(0).len() as u64 + 1);
+        for (muestras, compresion) in [(0..3, Compresion::Gzip), (3..6, Compresion::Zstd), (6..9, Compresion::None)] {
+            let mut escritor = EscritorRotativo::new(ruta.clone(), por_tamano(max_bytes, compresion));
+            for i in muestras {
+                escritor.escribir_linea(&muestra(i)).unwrap();
+            }
+        }
+        let extensiones: Vec<_> = segmentos_rotados(&ruta)
+            .unwrap()
+            .iter()
+            .map(|s| s.extension().unwrap().to_string_lossy().into_owned())
+            .collect();
+        //El primer segmento de cada escritor todavía tiene la última muestra del anterior
+        assert_eq!(extensiones, ["gz", "zst", "jsonl", "jsonl"]);
+        let secuencias: Vec<_> = lector::leer_serie(&ruta).unwrap().iter().map(|d| d.sequence.unwrap()).collect();
+        assert_eq!(secuencias, (0..9).collect::<Vec<_>>());
+        assert_eq!(lector::ultima_secuencia(&ruta), Some(8));
+    }
+}
@@ -0,0 +1,83 @@
+//Serialización de las cantidades de uom: en el JSON se guardan como números en la unidad que indica el nombre del campo
+use serde::{Deserialize, Deserializer, Serializer};
+use uom::si::frequency::megahertz;
+use uom::si::information::byte;
+use uom::si::information_rate::byte_per_second;
+
+pub type Bytes = uom::si::u64::Information;
+pub type BytesPorSegundo = uom::si::f64::InformationRate;
+pub type Frecuencia = uom::si::u64::Frequency;
+
+pub fn bytes(n: u64) -> Bytes {
+    Bytes::new::<byte>(n)
+}
+
+pub fn kibibytes(n: u64) -> Bytes {
+    Bytes::new::<uom::si::information::kibibyte>(n)
+}
+
+pub fn bytes_por_segundo(n: f64) -> BytesPorSegundo {
+    BytesPorSegundo::new::<byte_per_second>(n)
+}
+
+pub fn megahercios(n: u64) -> Frecuencia {
+    Frecuencia::new::<megahertz>(n)
+}
+
+//Campos `*_bytes`
+pub mod en_bytes {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(valor: &Bytes, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_u64(valor.get::<byte>())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Bytes, D::Error> {
+        u64::deserialize(d).map(bytes)
+    }
+}
+
+//Campos `*_bytes` que no existían en formatos anteriores
+pub mod en_bytes_opcional {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(valor: &Option<Bytes>, s: S) -> Result<S::Ok, S::Error> {
+        match valor {
+            Some(v) => s.serialize_some(&v.get::<byte>()),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Bytes>, D::Error> {
+        Option::<u64>::deserialize(d).map(|v| v.map(bytes))
+    }
+}
+
+//Campos `*_bytes_per_sec`; pueden faltar en registros que no tienen una muestra anterior
+pub mod en_bytes_por_segundo {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(valor: &Option<BytesPorSegundo>, s: S) -> Result<S::Ok, S::Error> {
+        match valor {
+            Some(v) => s.serialize_some(&v.get::<byte_per_second>()),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<BytesPorSegundo>, D::Error> {
+        Option::<f64>::deserialize(d).map(|v| v.map(bytes_por_segundo))
+    }
+}
+
+//Campos `*_mhz`
+pub mod en_megahercios {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(valor: &Frecuencia, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_u64(valor.get::<megahertz>())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Frecuencia, D::Error> {
+        u64::deserialize(d).map(megahercios)
+    }
+}
@@ -0,0 +1,270 @@
+//Sigue el log del kernel (/dev/kmsg, o un archivo con el mismo formato para pruebas) y avisa de los OOM kills,
+//segfaults y errores de hardware (MCE y EDAC). Lo que el kernel dice entre dos muestras no se pierde
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::Duration;
+use chrono::{DateTime, TimeDelta, Utc};
+use sysinfo::System;
+use tokio::sync::mpsc::Sender;
+use crate::datos::rfc3339;
+use crate::eventos::Evento;
+
+//Cada cuánto se lee lo nuevo del log; la lectura no bloquea, así que la tarea se puede cancelar entre lecturas
+const INTERVALO_LECTURA: Duration = Duration::from_secs(1);
+//Los niveles de syslog, de 0 a 7
+const NIVELES: [&str; 8] = ["emerg", "alert", "crit", "err", "warning", "notice", "info", "debug"];
+//Lo que escriben los programas en /dev/kmsg (systemd, por ejemplo) llega con otra facilidad; solo se cree lo del kernel
+const FACILIDAD_KERNEL: u32 = 0;
+
+pub struct VigilanteKernel {
+    archivo: File,
+    //Momento del arranque, para pasar a fecha los microsegundos desde el arranque de cada registro
+    arranque: DateTime<Utc>,
+    //Texto leído que todavía no termina en salto de línea
+    pendiente: Vec<u8>,
+}
+
+impl VigilanteKernel {
+    //De /dev/kmsg solo se leen los mensajes nuevos (los anteriores ya se vieron en otra ejecución, o nadie
+    //los estaba esperando); un archivo normal se lee desde el principio y luego se sigue como `tail -f`
+    pub fn new(ruta: &Path) -> Result<VigilanteKernel, String> {
+        let error = |e: io::Error| format!("{}: {}", ruta.display(), e);
+        let mut opciones = OpenOptions::new();
+        opciones.read(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            opciones.custom_flags(libc::O_NONBLOCK);
+        }
+        let mut archivo = opciones.open(ruta).map_err(error)?;
+        if !archivo.metadata().map_err(error)?.is_file() {
+            archivo.seek(SeekFrom::End(0)).map_err(error)?;
+        }
+        let arranque = DateTime::from_timestamp(System::boot_time() as i64, 0).unwrap_or_default();
+        Ok(VigilanteKernel { archivo, arranque, pendiente: Vec::new() })
+    }
+
+    //Lee lo que haya de nuevo sin esperar y devuelve sus eventos
+    pub fn revisar(&mut self) -> Vec<Evento> {
+        //Cada read() de /dev/kmsg devuelve un registro completo, que cabe en este tamaño
+        let mut buffer = [0u8; 8192];
+        let mut eventos = Vec::new();
+        loop {
+            match self.archivo.read(&mut buffer) {
+                //Fin de un archivo normal; se vuelve a intentar en la siguiente revisión
+                Ok(0) => break,
+                Ok(n) => self.pendiente.extend_from_slice(&buffer[..n]),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                //EPIPE: el kernel sobrescribió registros que no se alcanzaron a leer; se sigue con el siguiente
+                Err(e) if e.kind() == io::ErrorKind::BrokenPipe => continue,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            }
+            while let Some(fin) = self.pendiente.iter().position(|&b| b == b'\n') {
+                let linea: Vec<u8> = self.pendiente.drain(..=fin).collect();
+                if let Some(evento) = self.evento(&String::from_utf8_lossy(&linea[..fin])) {
+                    eventos.push(evento);
+                }
+            }
+        }
+        eventos
+    }
+
+    fn evento(&self, linea: &str) -> Option<Evento> {
+        let registro = registro(linea)?;
+        if registro.facilidad != FACILIDAD_KERNEL {
+            return None;
+        }
+        let timestamp = rfc3339(self.arranque + TimeDelta::microseconds(registro.microsegundos));
+        let message = registro.mensaje.to_string();
+        let priority = Some(NIVELES[registro.nivel as usize].to_string());
+        if let Some((pid, process)) = oom_kill(registro.mensaje) {
+            Some(Evento::OomKill { timestamp, pid, process, message, priority })
+        } else if let Some((pid, process)) = segfault(registro.mensaje) {
+            Some(Evento::Segfault { timestamp, pid, process, message, priority })
+        } else if error_de_hardware(registro.mensaje) {
+            Some(Evento::HardwareError { timestamp, pid: None, process: None, message, priority })
+        } else {
+            None
+        }
+    }
+
+    //Revisa cada segundo hasta que se cierre el canal
+    pub async fn vigilar(mut self, eventos: Sender<Evento>) {
+        let mut reloj = tokio::time::interval(INTERVALO_LECTURA);
+        reloj.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            reloj.tick().await;
+            for evento in self.revisar() {
+                if eventos.send(evento).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+//Un registro de /dev/kmsg
+struct Registro<'a> {
+    facilidad: u32,
+    nivel: u32,
+    //Desde el arranque
+    microsegundos: i64,
+    mensaje: &'a str,
+}
+
+//"6,1234,5678901,-;mensaje": el primer campo junta facilidad y nivel como en syslog (facilidad * 8 + nivel);
+//las líneas que empiezan con espacio son datos extra del registro anterior ("SUBSYSTEM=pci")
+fn registro(linea: &str) -> Option<Registro<'_>> {
+    if linea.starts_with(' ') {
+        return None;
+    }
+    let (encabezado, mensaje) = linea.split_once(';')?;
+    let mut campos = encabezado.split(',');
+    let prioridad: u32 = campos.next()?.parse().ok()?;
+    let microsegundos = campos.nth(1)?.parse().ok()?;
+    Some(Registro { facilidad: prioridad >> 3, nivel: prioridad & 7, microsegundos, mensaje })
+}
+
+//"Out of memory: Killed process 1234 (stress) total-vm:..." y "Memory cgroup out of memory: Killed process ...";
+//los kernels anteriores a 5.x decían "Kill process 1234 (stress) score ..."
+fn oom_kill(mensaje: &str) -> Option<(Option<u32>, Option<String>)> {
+    if !mensaje.contains("out of memory") && !mensaje.contains("Out of memory") {
+        return None;
+    }
+    let resto = mensaje.split_once("Killed process ").or_else(|| mensaje.split_once("Kill process "))?.1;
+    let (pid, resto) = resto.split_once(' ')?;
+    let proceso = resto.strip_prefix('(').and_then(|r| r.split_once(')')).map(|(nombre, _)| nombre.to_string());
+    Some((pid.parse().ok(), proceso))
+}
+
+//"stress[1234]: segfault at 0 ip 00005581 sp 00007ffd error 4 in stress[5581+1000]"
+fn segfault(mensaje: &str) -> Option<(Option<u32>, Option<String>)> {
+    let (proceso, _) = mensaje.split_once(": segfault at ")?;
+    let (nombre, pid) = proceso.rsplit_once('[')?;
+    Some((pid.strip_suffix(']').and_then(|p| p.parse().ok()), Some(nombre.to_string())))
+}
+
+//"mce: [Hardware Error]: Machine check events logged", "EDAC MC0: 1 CE memory read error on ...";
+//otros mensajes de mce (como los de temperatura) y de EDAC (como al cargar el driver) no son errores
+fn error_de_hardware(mensaje: &str) -> bool {
+    mensaje.contains("[Hardware Error]")
+        || mensaje.contains("Machine check")
+        || (mensaje.starts_with("EDAC ") && mensaje.contains(" error"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use tempfile::TempDir;
+    use super::*;
+    use crate::prueba::arbol;
+
+    //Registros como los da /dev/kmsg, con los datos extra de algunos en las líneas con espacio
+    const KMSG: &str = "\
+6,1021,5123456,-;EDAC MC: Ver: 3.0.0
+3,1822,86400123456,-;Out of memory: Killed process 4321 (stress) total-vm:8563412kB, anon-rss:7812345kB, file-rss:4kB, shmem-rss:0kB, UID:1000 pgtables:15432kB oom_score_adj:0
+4,1823,86400223456,-;Memory cgroup out of memory: Killed process 999 (node) total-vm:1124556kB, anon-rss:524288kB, file-rss:0kB, shmem-rss:0kB, UID:0 pgtables:1400kB oom_score_adj:0
+3,1824,86400323456,-;Out of memory: Kill process 1234 (java) score 950 or sacrifice child
+6,1830,86500123456,-;stress[5678]: segfault at 0 ip 000055d1c3a0b1a9 sp 00007ffd5e9c2a60 error 6 in stress[55d1c3a0a000+2000] likely on CPU 1 (core 1, socket 0)
+2,1840,86600123456,-;mce: [Hardware Error]: Machine check events logged
+ SUBSYSTEM=machinecheck
+ DEVICE=+machinecheck:machinecheck0
+4,1841,86600223456,-;EDAC MC0: 1 CE memory read error on CPU_SrcID#0_Ha#0_Chan#1_DIMM#0 (channel:1 slot:0 page:0x12345 offset:0x0 grain:32 syndrome:0x0)
+30,1850,86700123456,-;systemd[1]: stress.service: Main process exited, code=killed, status=9/KILL
+14,1851,86700223456,-;falso[42]: segfault at 0 ip 0 sp 0 error 4 in falso[1000+1000]
+3,1860,86800123456,-;Out of memory: Killed process 77 (tarde) total-vm:1024kB";
+
+    //El log va en <directorio>/kmsg; el directorio se borra al soltarlo
+    fn vigilante(texto: &str) -> (VigilanteKernel, TempDir) {
+        let directorio = arbol(&[("kmsg", texto)]);
+        (VigilanteKernel::new(&directorio.path().join("kmsg")).unwrap(), directorio)
+    }
+
+    #[test]
+    fn eventos_del_log() {
+        let (mut vigilante, directorio) = vigilante(KMSG);
+        let eventos = vigilante.revisar();
+        let resumen: Vec<_> = eventos
+            .iter()
+            .map(|evento| match evento {
+                Evento::OomKill { pid, process, priority, .. } => ("oom_kill", *pid, process.clone(), priority.clone()),
+                Evento::Segfault { pid, process, priority, .. } => ("segfault", *pid, process.clone(), priority.clone()),
+                Evento::HardwareError { pid, process, priority, .. } => {
+                    ("hardware_error", *pid, process.clone(), priority.clone())
+                }
+                otro => panic!("evento inesperado: {:?}", otro),
+            })
+            .collect();
+        let esperado = [
+            ("oom_kill", Some(4321), Some("stress"), "err"),
+            ("oom_kill", Some(999), Some("node"), "warning"),
+            ("oom_kill", Some(1234), Some("java"), "err"),
+            ("segfault", Some(5678), Some("stress"), "info"),
+            ("hardware_error", None, None, "crit"),
+            ("hardware_error", None, None, "warning"),
+        ]
+        .map(|(tipo, pid, proceso, nivel)| (tipo, pid, proceso.map(str::to_string), Some(nivel.to_string())));
+        assert_eq!(resumen, esperado);
+        //En UTC con milisegundos, igual que las muestras
+        let Evento::OomKill { timestamp, .. } = &eventos[0] else { unreachable!() };
+        assert!(timestamp.ends_with('Z') && timestamp.len() == "2025-04-10T21:08:52.123Z".len(), "{}", timestamp);
+        let momento = DateTime::parse_from_rfc3339(timestamp).unwrap();
+        assert_eq!(momento, vigilante.arranque + TimeDelta::microseconds(86400123000));
+        //El mensaje va tal cual, sin el encabezado
+        let Evento::Segfault { message, .. } = &eventos[3] else { unreachable!() };
+        assert!(message.starts_with("stress[5678]: segfault at 0 ip"));
+        //La última línea quedó pendiente hasta que llegue su salto de línea
+        assert!(vigilante.revisar().is_empty());
+        std::fs::OpenOptions::new().append(true).open(directorio.path().join("kmsg")).unwrap().write_all(b"\n").unwrap();
+        let tarde = vigilante.revisar();
+        assert!(matches!(tarde.as_slice(), [Evento::OomKill { pid: Some(77), .. }]), "{:?}", tarde);
+    }
+
+    #[test]
+    fn prioridad_y_facilidad() {
+        let leido = registro("30,1850,86700123456,-;systemd[1]: hola").unwrap();
+        assert_eq!((leido.facilidad, leido.nivel), (3, 6));
+        assert_eq!(leido.microsegundos, 86700123456);
+        assert_eq!(leido.mensaje, "systemd[1]: hola");
+        let leido = registro("0,1,2,-;kernel: emerg").unwrap();
+        assert_eq!((leido.facilidad, leido.nivel), (0, 0));
+        //Con campos extra después de las banderas, como en kernels nuevos
+        let leido = registro("7,5,10,-,caller=T1;debug").unwrap();
+        assert_eq!((leido.facilidad, leido.nivel, leido.microsegundos), (0, 7, 10));
+        assert!(registro(" SUBSYSTEM=pci").is_none());
+        assert!(registro("sin encabezado").is_none());
+        assert!(registro("x,1,2,-;prioridad inválida").is_none());
+    }
+
+    //Un programa puede escribir en /dev/kmsg; sus mensajes no se toman como del kernel
+    #[test]
+    fn lo_que_no_es_del_kernel_se_ignora() {
+        let (mut vigilante, _directorio) = vigilante(
+            "14,1,2,-;falso[42]: segfault at 0 ip 0 sp 0 error 4 in falso[1000+1000]\n\
+             11,2,3,-;Out of memory: Killed process 1 (init)\n",
+        );
+        assert!(vigilante.revisar().is_empty());
+    }
+
+    #[test]
+    fn mensajes() {
+        assert_eq!(
+            oom_kill("Out of memory: Killed process 4321 (stress) total-vm:8563412kB"),
+            Some((Some(4321), Some("stress".to_string())))
+        );
+        assert_eq!(oom_kill("Out of memory: Kill process 1234 (java) score 950"), Some((Some(1234), Some("java".to_string()))));
+        //El resumen previo al kill no es el kill
+        assert_eq!(oom_kill("stress invoked oom-killer: gfp_mask=0x140cca(GFP_HIGHUSER_MOVABLE|__GFP_COMP), order=0"), None);
+        assert_eq!(
+            segfault("my prog[77]: segfault at 10 ip 00 sp 00 error 4 in libc.so.6[7f00+100]"),
+            Some((Some(77), Some("my prog".to_string())))
+        );
+        assert_eq!(segfault("traps: stress[5678] general protection fault ip:55d1"), None);
+        assert!(error_de_hardware("mce: [Hardware Error]: CPU 0: Machine Check: 0 Bank 5: be00000000800400"));
+        assert!(error_de_hardware("EDAC MC0: 1 UE memory read error on DIMM_A1"));
+        assert!(!error_de_hardware("EDAC MC: Ver: 3.0.0"));
+        assert!(!error_de_hardware("mce: CPU0: Core temperature above threshold, cpu clock throttled"));
+    }
+}
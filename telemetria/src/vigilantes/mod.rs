@@ -0,0 +1,6 @@
+//Tareas que corren aparte de la recolección periódica, a su propio ritmo, y mandan Eventos por un canal
+mod kernel;
+mod procesos;
+
+pub use kernel::VigilanteKernel;
+pub use procesos::VigilanteProcesos;
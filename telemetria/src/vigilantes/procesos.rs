@@ -0,0 +1,212 @@
+//Compara la tabla de procesos entre revisiones y avisa qué procesos arrancaron y cuáles terminaron.
+//Un proceso que arranca y termina entre dos revisiones no se ve; por eso se revisa mucho más seguido que se muestrea
+use std::collections::HashMap;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use sysinfo::{ProcessRefreshKind, System, ThreadKind, UpdateKind};
+use tokio::sync::mpsc::Sender;
+use crate::datos::rfc3339;
+use crate::eventos::Evento;
+use crate::unidades::bytes;
+
+//Lo que se recuerda de cada proceso vivo para poder reportarlo cuando termine
+struct Vivo {
+    name: String,
+    cmd: Vec<String>,
+    ppid: Option<u32>,
+    //Segundos desde 1970; junto con el pid distingue a un proceso nuevo que reusó el pid de otro
+    inicio: u64,
+    pico_memoria: u64,
+}
+
+pub struct VigilanteProcesos {
+    system: System,
+    vivos: HashMap<u32, Vivo>,
+}
+
+impl VigilanteProcesos {
+    pub fn new() -> VigilanteProcesos {
+        let mut vigilante = VigilanteProcesos { system: System::new(), vivos: HashMap::new() };
+        //Los procesos que ya estaban no cuentan como arrancados
+        let _ = vigilante.revisar();
+        vigilante
+    }
+
+    //Revisa la tabla de procesos y devuelve los eventos desde la revisión anterior
+    pub fn revisar(&mut self) -> Vec<Evento> {
+        self.system.refresh_processes_specifics(
+            ProcessRefreshKind::new().with_memory().with_cmd(UpdateKind::OnlyIfNotSet),
+        );
+        let tabla = self
+            .system
+            .processes()
+            .iter()
+            //Los hilos de cada proceso también aparecen en la tabla de Linux
+            .filter(|(_, proceso)| proceso.thread_kind() != Some(ThreadKind::Userland))
+            .map(|(pid, proceso)| {
+                let nuevo = move || Vivo {
+                    name: proceso.name().to_string(),
+                    cmd: proceso.cmd().to_vec(),
+                    ppid: proceso.parent().map(|p| p.as_u32()),
+                    inicio: proceso.start_time(),
+                    pico_memoria: proceso.memory(),
+                };
+                (pid.as_u32(), proceso.start_time(), proceso.memory(), nuevo)
+            });
+        comparar(&mut self.vivos, tabla, Utc::now())
+    }
+
+    //Revisa cada `intervalo` hasta que se cierre el canal
+    pub async fn vigilar(mut self, intervalo: Duration, eventos: Sender<Evento>) {
+        let mut reloj = tokio::time::interval(intervalo);
+        reloj.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            reloj.tick().await;
+            for evento in self.revisar() {
+                if eventos.send(evento).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+//Deja en `vivos` los procesos de `tabla` y devuelve los que arrancaron y los que terminaron. Cada fila es el pid,
+//el inicio, la memoria actual y cómo armar el Vivo, que solo se llama para los procesos nuevos
+fn comparar<F: FnOnce() -> Vivo>(
+    vivos: &mut HashMap<u32, Vivo>,
+    tabla: impl IntoIterator<Item = (u32, u64, u64, F)>,
+    ahora: DateTime<Utc>,
+) -> Vec<Evento> {
+    let timestamp = rfc3339(ahora);
+    let mut eventos = Vec::new();
+    let mut actuales = HashMap::with_capacity(vivos.len());
+    for (pid, inicio, memoria, nuevo) in tabla {
+        let vivo = match vivos.remove(&pid) {
+            Some(mut vivo) if vivo.inicio == inicio => {
+                vivo.pico_memoria = vivo.pico_memoria.max(memoria);
+                vivo
+            }
+            //Si el pid lo reusó otro proceso, el anterior terminó entre las dos revisiones
+            anterior => {
+                if let Some(anterior) = anterior {
+                    eventos.push(terminado(&timestamp, ahora, pid, anterior));
+                }
+                let vivo = nuevo();
+                eventos.push(Evento::ProcessStarted {
+                    timestamp: timestamp.clone(),
+                    pid,
+                    name: vivo.name.clone(),
+                    cmd: vivo.cmd.clone(),
+                    ppid: vivo.ppid,
+                    start_time: DateTime::from_timestamp(vivo.inicio as i64, 0).map(rfc3339),
+                });
+                vivo
+            }
+        };
+        actuales.insert(pid, vivo);
+    }
+    //Lo que quedó en `vivos` ya no está
+    for (pid, vivo) in vivos.drain() {
+        eventos.push(terminado(&timestamp, ahora, pid, vivo));
+    }
+    *vivos = actuales;
+    eventos
+}
+
+fn terminado(timestamp: &str, ahora: DateTime<Utc>, pid: u32, vivo: Vivo) -> Evento {
+    Evento::ProcessExited {
+        timestamp: timestamp.to_string(),
+        pid,
+        name: vivo.name,
+        cmd: vivo.cmd,
+        ppid: vivo.ppid,
+        lifetime_secs: (ahora.timestamp().max(0) as u64).saturating_sub(vivo.inicio),
+        peak_memory_bytes: bytes(vivo.pico_memoria),
+    }
+}
+
+impl Default for VigilanteProcesos {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    //2024-05-01T12:00:00Z
+    const INICIO: u64 = 1714564800;
+
+    fn fila(pid: u32, nombre: &str, inicio: u64, memoria: u64) -> (u32, u64, u64, impl FnOnce() -> Vivo) {
+        let nombre = nombre.to_string();
+        let nuevo = move || Vivo {
+            cmd: vec![nombre.clone(), "-v".to_string()],
+            name: nombre,
+            ppid: Some(1),
+            inicio,
+            pico_memoria: memoria,
+        };
+        (pid, inicio, memoria, nuevo)
+    }
+
+    fn en(segundos: u64) -> DateTime<Utc> {
+        DateTime::from_timestamp((INICIO + segundos) as i64, 0).unwrap()
+    }
+
+    //Como JSON y en un orden fijo: los que terminaron salen de un HashMap
+    fn revisar(
+        vivos: &mut HashMap<u32, Vivo>,
+        tabla: Vec<(u32, u64, u64, impl FnOnce() -> Vivo)>,
+        ahora: DateTime<Utc>,
+    ) -> Vec<Value> {
+        let mut eventos: Vec<Value> = comparar(vivos, tabla, ahora).iter().map(|e| serde_json::to_value(e).unwrap()).collect();
+        eventos.sort_by_key(|e| (e["event"].as_str().unwrap().to_string(), e["pid"].as_u64()));
+        eventos
+    }
+
+    #[test]
+    fn arranques_y_terminaciones() {
+        let mut vivos = HashMap::new();
+        let eventos = revisar(&mut vivos, vec![fila(10, "sshd", INICIO, 1000), fila(20, "cron", INICIO + 5, 500)], en(10));
+        assert_eq!(
+            eventos,
+            [
+                json!({"event": "process_started", "timestamp": "2024-05-01T12:00:10.000Z", "pid": 10, "name": "sshd",
+                       "cmd": ["sshd", "-v"], "ppid": 1, "start_time": "2024-05-01T12:00:00.000Z"}),
+                json!({"event": "process_started", "timestamp": "2024-05-01T12:00:10.000Z", "pid": 20, "name": "cron",
+                       "cmd": ["cron", "-v"], "ppid": 1, "start_time": "2024-05-01T12:00:05.000Z"}),
+            ]
+        );
+
+        //Sin cambios no hay eventos, aunque cambie la memoria
+        assert!(revisar(&mut vivos, vec![fila(10, "sshd", INICIO, 4000), fila(20, "cron", INICIO + 5, 500)], en(20)).is_empty());
+        assert!(revisar(&mut vivos, vec![fila(10, "sshd", INICIO, 2000), fila(20, "cron", INICIO + 5, 500)], en(30)).is_empty());
+
+        //Termina con la mayor memoria que se le vio, no la de la última revisión
+        let eventos = revisar(&mut vivos, vec![fila(20, "cron", INICIO + 5, 500)], en(40));
+        assert_eq!(
+            eventos,
+            [json!({"event": "process_exited", "timestamp": "2024-05-01T12:00:40.000Z", "pid": 10, "name": "sshd",
+                    "cmd": ["sshd", "-v"], "ppid": 1, "lifetime_secs": 40, "peak_memory_bytes": 4000})]
+        );
+        assert_eq!(vivos.keys().collect::<Vec<_>>(), [&20]);
+    }
+
+    #[test]
+    fn un_pid_reusado_es_otro_proceso() {
+        let mut vivos = HashMap::new();
+        revisar(&mut vivos, vec![fila(10, "viejo", INICIO, 1000)], en(0));
+        //Mismo pid con otro inicio: terminó uno y arrancó otro entre las dos revisiones
+        let eventos = revisar(&mut vivos, vec![fila(10, "nuevo", INICIO + 50, 200)], en(60));
+        let resumen: Vec<(&str, &str)> =
+            eventos.iter().map(|e| (e["event"].as_str().unwrap(), e["name"].as_str().unwrap())).collect();
+        assert_eq!(resumen, [("process_exited", "viejo"), ("process_started", "nuevo")]);
+        assert_eq!(eventos[0]["lifetime_secs"], 60);
+        assert_eq!(eventos[0]["peak_memory_bytes"], 1000);
+        assert_eq!(vivos[&10].name, "nuevo");
+        assert_eq!(vivos[&10].pico_memoria, 200);
+    }
+}